@@ -27,6 +27,7 @@ PKG_FILES := \
       driver                                   \
       libcargo                                 \
       librusti                                 \
+      librustfmt                               \
       librustc                                 \
       compiletest                              \
       etc                                      \
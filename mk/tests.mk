@@ -22,6 +22,8 @@ CFAIL_RC := $(wildcard $(S)src/test/compile-fail/*.rc)
 CFAIL_RS := $(wildcard $(S)src/test/compile-fail/*.rs)
 BENCH_RS := $(wildcard $(S)src/test/bench/*.rs)
 PRETTY_RS := $(wildcard $(S)src/test/pretty/*.rs)
+DEBUGINFO_RS := $(wildcard $(S)src/test/debug-info/*.rs)
+CODEGEN_RS := $(wildcard $(S)src/test/codegen/*.rs)
 
 # perf tests are the same as bench tests only they run under
 # a performance monitor.
@@ -34,6 +36,8 @@ CFAIL_TESTS := $(CFAIL_RC) $(CFAIL_RS)
 BENCH_TESTS := $(BENCH_RS)
 PERF_TESTS := $(PERF_RS)
 PRETTY_TESTS := $(PRETTY_RS)
+DEBUGINFO_TESTS := $(DEBUGINFO_RS)
+CODEGEN_TESTS := $(CODEGEN_RS)
 
 FT := run_pass_stage2
 FT_LIB := $(call CFG_LIB_NAME,$(FT))
@@ -243,8 +247,11 @@ check-stage$(1)-T-$(2)-H-$(3):     				\
 	check-stage$(1)-T-$(2)-H-$(3)-cfail			\
 	check-stage$(1)-T-$(2)-H-$(3)-bench			\
 	check-stage$(1)-T-$(2)-H-$(3)-pretty        \
+	check-stage$(1)-T-$(2)-H-$(3)-debuginfo		\
+	check-stage$(1)-T-$(2)-H-$(3)-codegen		\
     check-stage$(1)-T-$(2)-H-$(3)-rustdoc       \
     check-stage$(1)-T-$(2)-H-$(3)-rusti       \
+    check-stage$(1)-T-$(2)-H-$(3)-rustfmt       \
     check-stage$(1)-T-$(2)-H-$(3)-cargo       \
     check-stage$(1)-T-$(2)-H-$(3)-doc       \
 
@@ -286,6 +293,12 @@ check-stage$(1)-T-$(2)-H-$(3)-bench:				\
 check-stage$(1)-T-$(2)-H-$(3)-perf:				\
 	check-stage$(1)-T-$(2)-H-$(3)-perf-dummy
 
+check-stage$(1)-T-$(2)-H-$(3)-debuginfo:			\
+	check-stage$(1)-T-$(2)-H-$(3)-debuginfo-dummy
+
+check-stage$(1)-T-$(2)-H-$(3)-codegen:			\
+	check-stage$(1)-T-$(2)-H-$(3)-codegen-dummy
+
 check-stage$(1)-T-$(2)-H-$(3)-pretty:			\
 	check-stage$(1)-T-$(2)-H-$(3)-pretty-rpass	\
 	check-stage$(1)-T-$(2)-H-$(3)-pretty-rpass-full	\
@@ -314,6 +327,9 @@ check-stage$(1)-T-$(2)-H-$(3)-rustdoc:				\
 check-stage$(1)-T-$(2)-H-$(3)-rusti:				\
 	check-stage$(1)-T-$(2)-H-$(3)-rusti-dummy
 
+check-stage$(1)-T-$(2)-H-$(3)-rustfmt:				\
+	check-stage$(1)-T-$(2)-H-$(3)-rustfmt-dummy
+
 check-stage$(1)-T-$(2)-H-$(3)-cargo:				\
 	check-stage$(1)-T-$(2)-H-$(3)-cargo-dummy
 
@@ -428,6 +444,23 @@ check-stage$(1)-T-$(2)-H-$(3)-rusti-dummy:		\
 	$$(Q)$$(call CFG_RUN_TEST,$$<,$(2),$(3)) $$(TESTARGS)	\
 	--logfile tmp/check-stage$(1)-T-$(2)-H-$(3)-rusti.log
 
+# Rules for the rustfmt test runner
+
+$(3)/test/rustfmttest.stage$(1)-$(2)$$(X):					\
+		$$(RUSTFMT_LIB) $$(RUSTFMT_INPUTS)		\
+		$$(TSREQ$(1)_T_$(2)_H_$(3))					\
+		$$(TLIB$(1)_T_$(2)_H_$(3))/$$(CFG_CORELIB)  \
+		$$(TLIB$(1)_T_$(2)_H_$(3))/$$(CFG_STDLIB)   \
+		$$(TLIB$(1)_T_$(2)_H_$(3))/$$(CFG_LIBSYNTAX)
+	@$$(call E, compile_and_link: $$@)
+	$$(STAGE$(1)_T_$(2)_H_$(3)) -o $$@ $$< --test
+
+check-stage$(1)-T-$(2)-H-$(3)-rustfmt-dummy:		\
+		$(3)/test/rustfmttest.stage$(1)-$(2)$$(X)
+	@$$(call E, run: $$<)
+	$$(Q)$$(call CFG_RUN_TEST,$$<,$(2),$(3)) $$(TESTARGS)	\
+	--logfile tmp/check-stage$(1)-T-$(2)-H-$(3)-rustfmt.log
+
 
 # Rules for the cargo test runner
 
@@ -528,6 +561,18 @@ PRETTY_PRETTY_ARGS$(1)-T-$(2)-H-$(3) :=			\
         --build-base $(3)/test/pretty/			\
         --mode pretty
 
+DEBUGINFO_ARGS$(1)-T-$(2)-H-$(3) :=			\
+		$$(CTEST_COMMON_ARGS$(1)-T-$(2)-H-$(3))	\
+        --src-base $$(S)src/test/debug-info/		\
+        --build-base $(3)/test/debug-info/		\
+        --mode debug-info
+
+CODEGEN_ARGS$(1)-T-$(2)-H-$(3) :=			\
+		$$(CTEST_COMMON_ARGS$(1)-T-$(2)-H-$(3))	\
+        --src-base $$(S)src/test/codegen/		\
+        --build-base $(3)/test/codegen/		\
+        --mode codegen
+
 DOC_TUTORIAL_ARGS$(1)-T-$(2)-H-$(3) :=			\
 		$$(CTEST_COMMON_ARGS$(1)-T-$(2)-H-$(3))	\
         --src-base $(3)/test/doc-tutorial/		\
@@ -652,6 +697,27 @@ check-stage$(1)-T-$(2)-H-$(3)-pretty-pretty-dummy:	\
 		$$(PRETTY_PRETTY_ARGS$(1)-T-$(2)-H-$(3)) \
 		--logfile tmp/check-stage$(1)-T-$(2)-H-$(3)-pretty-pretty.log
 
+# The debug-info tests script gdb, so they are skipped if there is none
+ifdef CFG_GDB
+check-stage$(1)-T-$(2)-H-$(3)-debuginfo-dummy:	\
+	        $$(TEST_SREQ$(1)_T_$(2)_H_$(3))		\
+	        $$(DEBUGINFO_TESTS)
+	@$$(call E, run debuginfo: $$<)
+	$$(Q)$$(call CFG_RUN_CTEST,$(1),$$<,$(3)) \
+		$$(DEBUGINFO_ARGS$(1)-T-$(2)-H-$(3)) \
+		--logfile tmp/check-stage$(1)-T-$(2)-H-$(3)-debuginfo.log
+else
+check-stage$(1)-T-$(2)-H-$(3)-debuginfo-dummy:
+endif
+
+check-stage$(1)-T-$(2)-H-$(3)-codegen-dummy:	\
+	        $$(TEST_SREQ$(1)_T_$(2)_H_$(3))		\
+	        $$(CODEGEN_TESTS)
+	@$$(call E, run codegen: $$<)
+	$$(Q)$$(call CFG_RUN_CTEST,$(1),$$<,$(3)) \
+		$$(CODEGEN_ARGS$(1)-T-$(2)-H-$(3)) \
+		--logfile tmp/check-stage$(1)-T-$(2)-H-$(3)-codegen.log
+
 check-stage$(1)-T-$(2)-H-$(3)-doc-tutorial-dummy:       \
 	        $$(TEST_SREQ$(1)_T_$(2)_H_$(3))		\
                 doc-tutorial-extract$(3)
@@ -814,12 +880,21 @@ check-stage$(1)-H-$(2)-pretty-bench:				\
 check-stage$(1)-H-$(2)-pretty-pretty:				\
 	$$(foreach target,$$(CFG_TARGET_TRIPLES),	\
 	 check-stage$(1)-T-$$(target)-H-$(2)-pretty-pretty)
+check-stage$(1)-H-$(2)-debuginfo:				\
+	$$(foreach target,$$(CFG_TARGET_TRIPLES),	\
+	 check-stage$(1)-T-$$(target)-H-$(2)-debuginfo)
+check-stage$(1)-H-$(2)-codegen:				\
+	$$(foreach target,$$(CFG_TARGET_TRIPLES),	\
+	 check-stage$(1)-T-$$(target)-H-$(2)-codegen)
 check-stage$(1)-H-$(2)-rustdoc:					\
 	$$(foreach target,$$(CFG_TARGET_TRIPLES),	\
 	 check-stage$(1)-T-$$(target)-H-$(2)-rustdoc)
 check-stage$(1)-H-$(2)-rusti:					\
 	$$(foreach target,$$(CFG_TARGET_TRIPLES),	\
 	 check-stage$(1)-T-$$(target)-H-$(2)-rusti)
+check-stage$(1)-H-$(2)-rustfmt:					\
+	$$(foreach target,$$(CFG_TARGET_TRIPLES),	\
+	 check-stage$(1)-T-$$(target)-H-$(2)-rustfmt)
 check-stage$(1)-H-$(2)-cargo:					\
 	$$(foreach target,$$(CFG_TARGET_TRIPLES),	\
 	 check-stage$(1)-T-$$(target)-H-$(2)-cargo)
@@ -913,12 +988,21 @@ check-stage$(1)-H-all-pretty-bench: \
 check-stage$(1)-H-all-pretty-pretty: \
 	$$(foreach target,$$(CFG_TARGET_TRIPLES),	\
 	 check-stage$(1)-H-$$(target)-pretty-pretty)
+check-stage$(1)-H-all-debuginfo: \
+	$$(foreach target,$$(CFG_TARGET_TRIPLES),	\
+	 check-stage$(1)-H-$$(target)-debuginfo)
+check-stage$(1)-H-all-codegen: \
+	$$(foreach target,$$(CFG_TARGET_TRIPLES),	\
+	 check-stage$(1)-H-$$(target)-codegen)
 check-stage$(1)-H-all-rustdoc: \
 	$$(foreach target,$$(CFG_TARGET_TRIPLES),	\
 	 check-stage$(1)-H-$$(target)-rustdoc)
 check-stage$(1)-H-all-rusti: \
 	$$(foreach target,$$(CFG_TARGET_TRIPLES),	\
 	 check-stage$(1)-H-$$(target)-rusti)
+check-stage$(1)-H-all-rustfmt: \
+	$$(foreach target,$$(CFG_TARGET_TRIPLES),	\
+	 check-stage$(1)-H-$$(target)-rustfmt)
 check-stage$(1)-H-all-cargo: \
 	$$(foreach target,$$(CFG_TARGET_TRIPLES),	\
 	 check-stage$(1)-H-$$(target)-cargo)
@@ -953,8 +1037,11 @@ check-stage$(1)-pretty-rpass-full: check-stage$(1)-H-$$(CFG_HOST_TRIPLE)-pretty-
 check-stage$(1)-pretty-rfail: check-stage$(1)-H-$$(CFG_HOST_TRIPLE)-pretty-rfail
 check-stage$(1)-pretty-bench: check-stage$(1)-H-$$(CFG_HOST_TRIPLE)-pretty-bench
 check-stage$(1)-pretty-pretty: check-stage$(1)-H-$$(CFG_HOST_TRIPLE)-pretty-pretty
+check-stage$(1)-debuginfo: check-stage$(1)-H-$$(CFG_HOST_TRIPLE)-debuginfo
+check-stage$(1)-codegen: check-stage$(1)-H-$$(CFG_HOST_TRIPLE)-codegen
 check-stage$(1)-rustdoc: check-stage$(1)-H-$$(CFG_HOST_TRIPLE)-rustdoc
 check-stage$(1)-rusti: check-stage$(1)-H-$$(CFG_HOST_TRIPLE)-rusti
+check-stage$(1)-rustfmt: check-stage$(1)-H-$$(CFG_HOST_TRIPLE)-rustfmt
 check-stage$(1)-cargo: check-stage$(1)-H-$$(CFG_HOST_TRIPLE)-cargo
 check-stage$(1)-doc: check-stage$(1)-H-$$(CFG_HOST_TRIPLE)-doc
 check-stage$(1)-doc-tutorial: check-stage$(1)-H-$$(CFG_HOST_TRIPLE)-doc-tutorial
@@ -30,6 +30,10 @@ RUSTDOC_INPUTS := $(wildcard $(S)src/librustdoc/*.rs)
 RUSTI_LIB := $(S)src/librusti/rusti.rc
 RUSTI_INPUTS := $(wildcard $(S)src/librusti/*.rs)
 
+# Rustfmt, the source formatter
+RUSTFMT_LIB := $(S)src/librustfmt/rustfmt.rc
+RUSTFMT_INPUTS := $(wildcard $(S)src/librustfmt/*.rs)
+
 # FIXME: These are only built for the host arch. Eventually we'll
 # have tools that need to built for other targets.
 define TOOLS_STAGE_N_TARGET
@@ -102,6 +106,21 @@ $$(TBIN$(1)_T_$(4)_H_$(3))/rusti$$(X):			\
 	@$$(call E, compile_and_link: $$@)
 	$$(STAGE$(1)_T_$(4)_H_$(3)) --cfg rusti -o $$@ $$<
 
+$$(TLIB$(1)_T_$(4)_H_$(3))/$$(CFG_LIBRUSTFMT):		\
+		$$(RUSTFMT_LIB) $$(RUSTFMT_INPUTS)			\
+		$$(TSREQ$(1)_T_$(4)_H_$(3))					\
+		$$(TLIB$(1)_T_$(4)_H_$(3))/$$(CFG_CORELIB)	\
+		$$(TLIB$(1)_T_$(4)_H_$(3))/$$(CFG_STDLIB)	\
+		$$(TLIB$(1)_T_$(4)_H_$(3))/$$(CFG_LIBSYNTAX)
+	@$$(call E, compile_and_link: $$@)
+	$$(STAGE$(1)_T_$(4)_H_$(3)) -o $$@ $$< && touch $$@
+
+$$(TBIN$(1)_T_$(4)_H_$(3))/rustfmt$$(X):			\
+		$$(DRIVER_CRATE) 							\
+		$$(TLIB$(1)_T_$(4)_H_$(3))/$$(CFG_LIBRUSTFMT)
+	@$$(call E, compile_and_link: $$@)
+	$$(STAGE$(1)_T_$(4)_H_$(3)) --cfg rustfmt -o $$@ $$<
+
 endef
 
 define TOOLS_STAGE_N_HOST
@@ -185,6 +204,23 @@ $$(HBIN$(2)_H_$(4))/rusti$$(X):				\
 	@$$(call E, cp: $$@)
 	$$(Q)cp $$< $$@
 
+$$(HLIB$(2)_H_$(4))/$$(CFG_LIBRUSTFMT):					\
+		$$(TLIB$(1)_T_$(4)_H_$(3))/$$(CFG_LIBRUSTFMT)	\
+		$$(HLIB$(2)_H_$(4))/$$(CFG_LIBSYNTAX)			\
+		$$(HSREQ$(2)_H_$(4))
+	@$$(call E, cp: $$@)
+	$$(Q)cp $$< $$@
+	$$(Q)cp -R $$(TLIB$(1)_T_$(4)_H_$(3))/$(LIBRUSTFMT_GLOB) \
+		$$(wildcard $$(TLIB$(1)_T_$(4)_H_$(3))/$(LIBRUSTFMT_DSYM_GLOB)) \
+	        $$(HLIB$(2)_H_$(4))
+
+$$(HBIN$(2)_H_$(4))/rustfmt$$(X):				\
+		$$(TBIN$(1)_T_$(4)_H_$(3))/rustfmt$$(X)	\
+		$$(HLIB$(2)_H_$(4))/$$(CFG_LIBRUSTFMT)	\
+		$$(HSREQ$(2)_H_$(4))
+	@$$(call E, cp: $$@)
+	$$(Q)cp $$< $$@
+
 endef
 
 $(foreach host,$(CFG_TARGET_TRIPLES),				\
@@ -0,0 +1,197 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use core::prelude::*;
+
+use io;
+use io::ReaderUtil;
+use str;
+use uint;
+use vec;
+
+#[deriving_eq]
+pub enum CheckKind {
+    // "// CHECK: pat": pat is on a later line than the last match
+    check,
+    // "// CHECK-NEXT: pat": pat is on the line after the last match
+    check_next,
+    // "// CHECK-NOT: pat": pat is not between the matches around it
+    check_not,
+}
+
+pub struct Check { line: uint, kind: CheckKind, pattern: ~str }
+
+// Load the CHECK directives of a codegen test, from anywhere in the file
+pub fn load_checks(testfile: &Path) -> ~[Check] {
+    let mut checks = ~[];
+    let rdr = io::file_reader(testfile).get();
+    let mut line_num = 1u;
+    while !rdr.eof() {
+        let ln = rdr.read_line();
+        match parse_check(line_num, ln) {
+          Some(c) => checks.push(c),
+          None => ()
+        }
+        line_num += 1u;
+    }
+    return checks;
+}
+
+fn parse_check(line_num: uint, line: ~str) -> Option<Check> {
+    let directives = ~[(~"CHECK:", check),
+                       (~"CHECK-NEXT:", check_next),
+                       (~"CHECK-NOT:", check_not)];
+
+    let comment = match str::find_str(line, ~"//") {
+      Some(idx) => str::trim(str::slice(line, idx + 2u, str::len(line))),
+      None => return None
+    };
+    for directives.each |&(ref directive, kind)| {
+        if str::starts_with(comment, *directive) {
+            let pattern = str::trim(str::slice(comment, str::len(*directive),
+                                               str::len(comment)));
+            debug!("line=%u %s %s", line_num, *directive, pattern);
+            return Some(Check {line: line_num, kind: kind,
+                               pattern: pattern});
+        }
+    }
+    return None;
+}
+
+fn directive_name(kind: CheckKind) -> ~str {
+    match kind {
+      check => ~"CHECK",
+      check_next => ~"CHECK-NEXT",
+      check_not => ~"CHECK-NOT"
+    }
+}
+
+// Match the checks against the lines of the output, in order, returning a
+// description of the first that fails
+pub fn verify_checks(checks: &[Check], output: ~str) -> Option<~str> {
+    let lines = str::lines_any(output);
+
+    // The first line that is not before the last match
+    let mut pos = 0u;
+    let mut matched = false;
+    let mut nots: ~[Check] = ~[];
+
+    fn failed(c: &Check, why: ~str) -> Option<~str> {
+        Some(fmt!("line %u: %s: %s %s", c.line, directive_name(c.kind),
+                  c.pattern, why))
+    }
+
+    // Checks that none of the CHECK-NOTs is on lines [lo, hi)
+    fn check_nots(nots: &[Check], lines: &[~str], lo: uint,
+                  hi: uint) -> Option<~str> {
+        for nots.each |c| {
+            for uint::range(lo, hi) |i| {
+                if str::contains(lines[i], c.pattern) {
+                    return failed(c, fmt!("found on output line %u",
+                                          i + 1u));
+                }
+            }
+        }
+        return None;
+    }
+
+    for checks.each |c| {
+        match c.kind {
+          check_not => { nots.push(copy *c); loop; }
+          check => {
+            let found = vec::position_between(lines, pos, lines.len(),
+                                              |l| str::contains(*l,
+                                                                c.pattern));
+            match found {
+              Some(i) => {
+                match check_nots(nots, lines, pos, i) {
+                  Some(e) => return Some(e),
+                  None => ()
+                }
+                pos = i + 1u;
+              }
+              None => return failed(c, ~"not found")
+            }
+          }
+          check_next => {
+            if !matched {
+                return failed(c, ~"has no CHECK before it");
+            }
+            if pos >= lines.len() || !str::contains(lines[pos], c.pattern) {
+                return failed(c, ~"not on the next line");
+            }
+            pos += 1u;
+          }
+        }
+        matched = true;
+        nots = ~[];
+    }
+
+    return check_nots(nots, lines, pos, lines.len());
+}
+
+#[cfg(test)]
+mod test {
+    use core::prelude::*;
+    use super::*;
+
+    // The checks on the lines of a test file
+    fn checks(lines: &[~str]) -> ~[Check] {
+        let mut checks = ~[];
+        for lines.eachi |i, ln| {
+            match parse_check(i + 1u, copy *ln) {
+              Some(c) => checks.push(c),
+              None => ()
+            }
+        }
+        checks
+    }
+
+    #[test]
+    fn test_parse() {
+        let cs = checks(~[~"fn f() {}", ~"// CHECK-NEXT:  ret void ",
+                          ~"  // CHECK-NOT: call"]);
+        assert cs.len() == 2u;
+        assert cs[0].line == 2u && cs[0].kind == check_next;
+        assert cs[0].pattern == ~"ret void";
+        assert cs[1].line == 3u && cs[1].kind == check_not;
+    }
+
+    #[test]
+    fn test_check_order() {
+        let cs = checks(~[~"// CHECK: a", ~"// CHECK: b"]);
+        assert verify_checks(cs, ~"a\nx\nb\n").is_none();
+        assert verify_checks(cs, ~"b\na\n") ==
+            Some(~"line 2: CHECK: b not found");
+    }
+
+    #[test]
+    fn test_check_next() {
+        let cs = checks(~[~"// CHECK: a", ~"// CHECK-NEXT: b"]);
+        assert verify_checks(cs, ~"a\nb\n").is_none();
+        assert verify_checks(cs, ~"a\nx\nb\n") ==
+            Some(~"line 2: CHECK-NEXT: b not on the next line");
+        let cs = checks(~[~"// CHECK-NEXT: b"]);
+        assert verify_checks(cs, ~"b\n") ==
+            Some(~"line 1: CHECK-NEXT: b has no CHECK before it");
+    }
+
+    #[test]
+    fn test_check_not() {
+        let cs = checks(~[~"// CHECK: a", ~"// CHECK-NOT: x",
+                          ~"// CHECK: b"]);
+        assert verify_checks(cs, ~"a\nb\nx\n").is_none();
+        assert verify_checks(cs, ~"a\nx\nb\n") ==
+            Some(~"line 2: CHECK-NOT: x found on output line 2");
+        let cs = checks(~[~"// CHECK: a", ~"// CHECK-NOT: x"]);
+        assert verify_checks(cs, ~"a\nb\nx\n") ==
+            Some(~"line 2: CHECK-NOT: x found on output line 3");
+    }
+}
@@ -18,6 +18,8 @@ pub enum mode {
     mode_run_fail,
     mode_run_pass,
     mode_pretty,
+    mode_debug_info,
+    mode_codegen,
 }
 
 pub type config = {
@@ -42,7 +44,8 @@ pub type config = {
     // The name of the stage being built (stage1, etc)
     stage_id: ~str,
 
-    // The test mode, compile-fail, run-fail, run-pass
+    // The test mode, compile-fail, run-fail, run-pass, pretty, debug-info,
+    // codegen
     mode: mode,
 
     // Run ignored tests
@@ -22,7 +22,10 @@ use common::mode_run_pass;
 use common::mode_run_fail;
 use common::mode_compile_fail;
 use common::mode_pretty;
+use common::mode_debug_info;
+use common::mode_codegen;
 use common::config;
+use checks;
 use errors;
 use header;
 use header::load_props;
@@ -43,7 +46,9 @@ pub fn run(config: config, testfile: ~str) {
       mode_compile_fail => run_cfail_test(config, props, &testfile),
       mode_run_fail => run_rfail_test(config, props, &testfile),
       mode_run_pass => run_rpass_test(config, props, &testfile),
-      mode_pretty => run_pretty_test(config, props, &testfile)
+      mode_pretty => run_pretty_test(config, props, &testfile),
+      mode_debug_info => run_debuginfo_test(config, props, &testfile),
+      mode_codegen => run_codegen_test(config, props, &testfile)
     }
 }
 
@@ -224,6 +229,77 @@ actual:\n\
     }
 }
 
+fn run_debuginfo_test(config: config, props: TestProps, testfile: &Path) {
+    // compile test file with full debug info
+    let mut ProcRes = compile_test_(config, props, testfile, [~"--xg"]);
+    if ProcRes.status != 0 {
+        fatal_ProcRes(~"compilation failed!", ProcRes);
+    }
+
+    // write debugger script
+    let script_str = str::connect(props.debugger_cmds, ~"\n") +
+        ~"\nquit\n";
+    debug!("script_str = %s", script_str);
+    dump_output_file(config, testfile, script_str, ~"debugger.script");
+
+    // run debugger script with gdb
+    let debugger_script = make_out_name(config, testfile,
+                                        ~"debugger.script");
+    let debugger_opts = ~[~"-quiet", ~"-batch", ~"-nx",
+                          ~"-command=" + debugger_script.to_str(),
+                          make_exe_name(config, testfile).to_str()];
+    ProcRes = compose_and_run(config, testfile,
+                              ProcArgs {prog: ~"gdb", args: debugger_opts},
+                              ~[], config.run_lib_path, None);
+    if ProcRes.status != 0 {
+        fatal_ProcRes(~"gdb failed to execute", ProcRes);
+    }
+
+    // check that the check lines appear in the debugger output, in order
+    let num_check_lines = props.check_lines.len();
+    if num_check_lines > 0u {
+        let mut i = 0u;
+        for str::lines_each(ProcRes.stdout) |line| {
+            if str::contains(line, str::trim(props.check_lines[i])) {
+                i += 1u;
+            }
+            if i == num_check_lines { break; }
+        }
+        if i != num_check_lines {
+            fatal_ProcRes(fmt!("line not found in debugger output: %s",
+                               props.check_lines[i]), ProcRes);
+        }
+    }
+}
+
+fn run_codegen_test(config: config, props: TestProps, testfile: &Path) {
+    let expected_checks = checks::load_checks(testfile);
+    if expected_checks.is_empty() {
+        fatal(~"no CHECK directives in " + testfile.to_str());
+    }
+
+    // compile test file to LLVM assembly, without running anything
+    let link_args = ~[~"-L", aux_output_dir_name(config, testfile).to_str(),
+                      ~"--emit-llvm", ~"-S"];
+    let ProcRes = compose_and_run_compiler(
+        config, props, testfile,
+        make_compile_args(config, props, link_args,
+                          |a, b| make_out_name(a, b, ~"ll"), testfile),
+        None);
+    if ProcRes.status != 0 {
+        fatal_ProcRes(~"compilation failed!", ProcRes);
+    }
+
+    let llfile = make_out_name(config, testfile, ~"ll");
+    let ir = io::read_whole_file_str(&llfile).get();
+    match checks::verify_checks(expected_checks, ir) {
+      Some(e) => {
+        fatal_ProcRes(fmt!("%s in %s", e, llfile.to_str()), ProcRes);
+      }
+      None => ()
+    }
+}
+
 fn check_error_patterns(props: TestProps,
                         testfile: &Path,
                         ProcRes: ProcRes) {
@@ -0,0 +1,191 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Package versions, and the requirements that queries put on them.
+//!
+//! A requirement is a comma-separated list of comparisons, all of which a
+//! version must satisfy, such as `>=0.2, <0.4`. A bare version, as given
+//! by `extern mod foo(vers = "0.2")`, must match exactly, since that is
+//! what rustc will look for; an empty requirement or `*` allows any.
+
+use core::char;
+use core::option::{Option, Some, None};
+use core::str;
+use core::uint;
+
+#[deriving_eq]
+pub enum Comparison {
+    Exactly,
+    Below,
+    AtMost,
+    Above,
+    AtLeast
+}
+
+#[deriving_eq]
+pub struct VersionReq {
+    comparisons: ~[(Comparison, ~str)]
+}
+
+/// The requirement that any version satisfies
+pub fn any() -> VersionReq {
+    VersionReq { comparisons: ~[] }
+}
+
+/// The requirement that only `vers` satisfies
+pub fn exactly(vers: &str) -> VersionReq {
+    VersionReq { comparisons: ~[(Exactly, str::from_slice(vers))] }
+}
+
+fn is_version(s: &str) -> bool {
+    !s.is_empty() && s.all(|c| {
+        char::is_alphanumeric(c) || c == '.' || c == '-' || c == '_'
+    })
+}
+
+pub fn parse(s: &str) -> Option<VersionReq> {
+    let s = str::trim(s);
+    if s.is_empty() || s == ~"*" {
+        return Some(any());
+    }
+
+    let mut comparisons = ~[];
+    for str::split_char(s, ',').each |part| {
+        let part = str::trim(*part);
+        let (comparison, vers) = if part.starts_with(">=") {
+            (AtLeast, part.slice(2, part.len()))
+        } else if part.starts_with("<=") {
+            (AtMost, part.slice(2, part.len()))
+        } else if part.starts_with(">") {
+            (Above, part.slice(1, part.len()))
+        } else if part.starts_with("<") {
+            (Below, part.slice(1, part.len()))
+        } else if part.starts_with("=") {
+            (Exactly, part.slice(1, part.len()))
+        } else {
+            (Exactly, copy part)
+        };
+        let vers = str::trim(vers);
+        if !is_version(vers) {
+            return None;
+        }
+        comparisons.push((comparison, vers));
+    }
+    Some(VersionReq { comparisons: comparisons })
+}
+
+pub fn to_str(req: &VersionReq) -> ~str {
+    if req.comparisons.is_empty() {
+        return ~"*";
+    }
+    let parts = do req.comparisons.map |&(comparison, ref vers)| {
+        let op = match comparison {
+            Exactly => "",
+            Below => "<",
+            AtMost => "<=",
+            Above => ">",
+            AtLeast => ">="
+        };
+        op.to_owned() + *vers
+    };
+    str::connect(parts, ", ")
+}
+
+/**
+ * Compares two versions, returning a negative number, zero, or a positive
+ * number as `a` is older than, the same as, or newer than `b`.
+ *
+ * Versions are compared a dot-separated component at a time, numerically
+ * where both components are numbers, so `0.10` is newer than `0.9`. A
+ * version that runs out of components first is the older.
+ */
+pub fn compare(a: &str, b: &str) -> int {
+    let a = str::split_char(a, '.');
+    let b = str::split_char(b, '.');
+    let mut i = 0;
+    while i < a.len() && i < b.len() {
+        let order = match (uint::from_str(a[i]), uint::from_str(b[i])) {
+            (Some(x), Some(y)) => (x as int) - (y as int),
+            _ => if a[i] < b[i] { -1 } else if a[i] > b[i] { 1 } else { 0 }
+        };
+        if order != 0 {
+            return order;
+        }
+        i += 1;
+    }
+    (a.len() as int) - (b.len() as int)
+}
+
+pub fn matches(req: &VersionReq, vers: &str) -> bool {
+    do req.comparisons.all |&(comparison, ref bound)| {
+        let order = compare(vers, *bound);
+        match comparison {
+            Exactly => order == 0,
+            Below => order < 0,
+            AtMost => order <= 0,
+            Above => order > 0,
+            AtLeast => order >= 0
+        }
+    }
+}
+
+/// The newest of the versions that satisfy the requirement
+pub fn newest(req: &VersionReq, versions: &[~str]) -> Option<~str> {
+    let mut best: Option<~str> = None;
+    for versions.each |vers| {
+        if !matches(req, *vers) { loop; }
+        let newer = match best {
+            Some(ref b) => compare(*vers, *b) > 0,
+            None => true
+        };
+        if newer {
+            best = Some(copy *vers);
+        }
+    }
+    best
+}
+
+#[test]
+fn test_parse() {
+    assert parse("") == Some(any());
+    assert parse("*") == Some(any());
+    assert parse("0.2") == Some(exactly("0.2"));
+    assert parse(">=0.2, <0.4") == Some(VersionReq {
+        comparisons: ~[(AtLeast, ~"0.2"), (Below, ~"0.4")]
+    });
+    assert parse(">=").is_none();
+    assert parse("0.2 beta").is_none();
+}
+
+#[test]
+fn test_compare() {
+    assert compare("0.10", "0.9") > 0;
+    assert compare("1.0", "1.0") == 0;
+    assert compare("1.0", "1.0.1") < 0;
+    assert compare("0.2", "0.10") < 0;
+}
+
+#[test]
+fn test_matches() {
+    let req = parse(">=0.2, <0.4").get();
+    assert !matches(&req, "0.1");
+    assert matches(&req, "0.2");
+    assert matches(&req, "0.3.1");
+    assert !matches(&req, "0.4");
+    assert matches(&any(), "7");
+}
+
+#[test]
+fn test_newest() {
+    let versions = ~[~"0.1", ~"0.3", ~"0.10", ~"0.2"];
+    assert newest(&any(), versions) == Some(~"0.10");
+    assert newest(&parse("<0.5").get(), versions) == Some(~"0.3");
+    assert newest(&parse(">1").get(), versions).is_none();
+}
@@ -18,15 +18,17 @@
 //! Flag := [ 0#+-]
 //! Width := Parameter | [0-9]+
 //! Precision := '.' [0-9]+
-//! Type := [bcdfiostuxX?]
+//! Type := [bcdfiostuxXT?]
 //! ~~~~~~
 //!
-//! * Parameter is the 1-based argument to apply the format to. Currently not
-//! implemented.
+//! * Parameter is the 1-based argument to apply the format to, so that
+//! `fmt!("%2$s %1$s", "world", "hello")` is `"hello world"`. Conversions
+//! without one take the arguments that follow the last such conversion, in
+//! order, and every argument must be used.
 //! * Flag 0 causes leading zeros to be used for padding when converting
 //! numbers.
 //! * Flag # causes the conversion to be done in an *alternative* manner.
-//! Currently not implemented.
+//! Currently only implemented by `T`, where its meaning is up to the type.
 //! * Flag + causes signed numbers to always be prepended with a sign
 //! character.
 //! * Flag - left justifies the result
@@ -49,7 +51,12 @@
 //! * x - uint as lower-case hexadecimal
 //! * X - uint as upper-case hexadecimal
 //! * s - str (any flavor)
+//! * T - any type that implements the `Format` trait
 //! * ? - arbitrary type (does not use the to_str trait)
+//!
+//! `write_fmt!(writer, ...)` takes the same format string and arguments as
+//! `fmt!`, but writes the result to an `io::Writer` a piece at a time
+//! instead of building a string.
 
 // Transitional
 #[allow(structural_records)]; // Macros -- needs a snapshot
@@ -84,6 +91,33 @@ use option::{Some, None};
 use prelude::*;
 use str;
 
+/// The flags, width and precision of a `%T` conversion
+pub struct Spec {
+    /// The `-` flag
+    left_justify: bool,
+    /// The `0` flag
+    zero_pad: bool,
+    /// The ` ` flag
+    space_for_sign: bool,
+    /// The `+` flag
+    sign_always: bool,
+    /// The `#` flag
+    alternate: bool,
+    width: Option<uint>,
+    precision: Option<uint>,
+}
+
+/**
+ * A type that formats itself for the `%T` conversion of `fmt!`.
+ *
+ * The result is padded out to the width of the conversion afterwards, as
+ * the `-` flag says, so an implementation usually only has to heed the
+ * precision and the other flags.
+ */
+pub trait Format {
+    pure fn format(&self, spec: &Spec) -> ~str;
+}
+
 /*
  * We have a 'ct' (compile-time) module that parses format strings into a
  * sequence of conversions. From those conversions AST fragments are built
@@ -119,6 +153,7 @@ pub mod ct {
         TyHex(Caseness),
         TyOctal,
         TyFloat,
+        TyFormat,
         TyPoly,
     }
 
@@ -330,6 +365,7 @@ pub mod ct {
             't' as u8 => TyBits,
             'o' as u8 => TyOctal,
             'f' as u8 => TyFloat,
+            'T' as u8 => TyFormat,
             '?' as u8 => TyPoly,
             _ => err(~"unknown type in conversion: " + s.substr(i, 1))
         };
@@ -439,6 +475,7 @@ pub mod ct {
         assert test("t", TyBits);
         assert test("x", TyHex(CaseLower));
         assert test("X", TyHex(CaseUpper));
+        assert test("T", TyFormat);
         assert test("?", TyPoly);
     }
 
@@ -478,7 +515,9 @@ pub mod ct {
 // implement it this way, I think.
 #[doc(hidden)]
 pub mod rt {
+    use extfmt::{Format, Spec};
     use float;
+    use io::{Writer, WriterUtil};
     use str;
     use sys;
     use uint;
@@ -569,6 +608,27 @@ pub mod rt {
         let s = sys::log_str(v);
         return conv_str(cv, s);
     }
+    pub pure fn conv_format<T: Format>(cv: Conv, v: &T) -> ~str {
+        pure fn count(c: Count) -> Option<uint> {
+            match c { CountIs(n) => Some(n), CountImplied => None }
+        }
+        let spec = Spec {
+            left_justify: have_flag(cv.flags, flag_left_justify),
+            zero_pad: have_flag(cv.flags, flag_left_zero_pad),
+            space_for_sign: have_flag(cv.flags, flag_space_for_sign),
+            sign_always: have_flag(cv.flags, flag_sign_always),
+            alternate: have_flag(cv.flags, flag_alternate),
+            width: count(cv.width),
+            precision: count(cv.precision),
+        };
+        let s = v.format(&spec);
+        return unsafe { pad(cv, move s, PadNozero) };
+    }
+
+    // Writes a piece of the output of write_fmt!
+    pub fn write_str(w: Writer, s: &str) {
+        w.write_str(s);
+    }
 
     // Convert an int to string with minimum number of digits. If precision is
     // 0 and num is 0 then the result is the empty string.
@@ -574,6 +574,14 @@ pub fn write_repr<T>(writer: @Writer, object: &T) {
     }
 }
 
+/// Formats a value the way `write_repr` does, into a string
+pub pure fn repr_to_str<T>(object: &T) -> ~str {
+    unsafe {
+        // Not really unsafe: the writer only fills in the string
+        io::with_str_writer(|w| write_repr(w, object))
+    }
+}
+
 #[test]
 struct P {a: int, b: float}
 
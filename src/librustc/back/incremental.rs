@@ -0,0 +1,521 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * Incremental recompilation.
+ *
+ * With `-Z incremental=DIR`, the driver hashes every item of the crate after
+ * analysis. The hash is of the item's pretty-printed source, so it does not
+ * change when an edit elsewhere moves the item to another line. With `-g`,
+ * the item's position is hashed too, since the debug info records it;
+ * without it, the code of a reused item reports the line the item was on
+ * when it was translated in assertion and bounds-check failures.
+ *
+ * The crate is translated in codegen units: as many as `-Z codegen-units`
+ * asks for, or one for each top-level module by default. Each unit has a
+ * key, which is a hash of
+ *
+ *  - the compiler version, the options that affect code generation and the
+ *    metadata hashes of the crates linked against;
+ *  - the interface of the crate: all of any item whose code may end up in
+ *    another unit (types, traits, impls, consts and generic functions), and
+ *    the signature of every other function;
+ *  - the hashes of the items that the unit owns.
+ *
+ * The unoptimised bitcode of each unit is cached as `DIR/<crate>.<key>.bc`,
+ * and `DIR/<crate>.manifest` lists the unit keys and item hashes of the last
+ * build. On the next run, the units whose keys are in the manifest are
+ * loaded from the cache and only the others are translated, so editing the
+ * body of a function only retranslates the unit that it is in.
+ *
+ * A library's primary unit holds the metadata of the whole crate, so its key
+ * covers every item, and the library is retranslated as a whole when any of
+ * them changes.
+ */
+
+use core::prelude::*;
+
+use back::abi;
+use back::link;
+use driver::session::Session;
+use lib::llvm::{llvm, ModuleRef};
+use metadata::cstore;
+use middle::trans::partition::CodegenUnit;
+
+use core::hash;
+use core::hashmap::linear::LinearMap;
+use core::io;
+use core::os;
+use core::result;
+use core::str;
+use core::vec;
+use std::json;
+use std::sort;
+use syntax::ast;
+use syntax::print::pprust;
+
+/// Bump this whenever the manifest layout or the hashing scheme changes.
+pub const manifest_version: uint = 2;
+
+pub struct Fingerprint {
+    /// Hash of everything outside the crate that affects its translation
+    env_hash: ~str,
+    /// Hash of the interface of the crate, which every unit depends on
+    iface_hash: ~str,
+    /// Item path => item hash
+    items: LinearMap<~str, ~str>,
+    /// Item id => item path, for the items of the crate being compiled
+    paths: LinearMap<ast::node_id, ~str>
+}
+
+pub impl Fingerprint {
+    /// Paths of items that were added, removed or changed since `old`
+    fn changed_items(&self, old: &Fingerprint) -> ~[~str] {
+        let mut changed = ~[];
+        for self.items.each |k, v| {
+            match old.items.find(k) {
+                Some(ov) if *ov == *v => (),
+                _ => changed.push(copy *k)
+            }
+        }
+        for old.items.each_key |k| {
+            if !self.items.contains_key(k) { changed.push(copy *k); }
+        }
+        sort::merge_sort(changed, |a, b| *a <= *b)
+    }
+
+    fn same_env(&self, old: &Fingerprint) -> bool {
+        self.env_hash == old.env_hash
+    }
+
+    /// The key under which the bitcode of `unit` is cached. A unit whose
+    /// code depends on the whole crate, rather than on the items it owns
+    /// and the interface of the others, is keyed by all of the items.
+    fn unit_key(&self, unit: &CodegenUnit, whole_crate: bool) -> ~str {
+        let mut owned = ~[];
+        for self.paths.each |id, path| {
+            if whole_crate || unit.owns_item(*id) {
+                owned.push(fmt!("%s=%s", *path, *self.items.get(path)));
+            }
+        }
+        let mut parts = ~[copy self.env_hash, copy self.iface_hash];
+        parts += sort::merge_sort(owned, |a, b| *a <= *b);
+        hash_str(@hash::default_state(), str::connect(parts, "\n"))
+    }
+
+    fn to_json(&self, keys: &[~str]) -> json::Json {
+        let mut items = ~LinearMap::new();
+        for self.items.each |k, v| {
+            items.insert(copy *k, json::String(copy *v));
+        }
+        let mut obj = ~LinearMap::new();
+        obj.insert(~"version", json::Number(manifest_version as float));
+        obj.insert(~"env", json::String(copy self.env_hash));
+        obj.insert(~"interface", json::String(copy self.iface_hash));
+        obj.insert(~"units", json::List(keys.map(|k| json::String(copy *k))));
+        obj.insert(~"items", json::Object(move items));
+        json::Object(move obj)
+    }
+
+    /// Reads a manifest back, along with the keys of the codegen units
+    /// whose bitcode it describes
+    static fn from_json(j: &json::Json) -> Option<(Fingerprint, ~[~str])> {
+        match *j {
+            json::Object(ref obj) => {
+                match obj.find(&~"version") {
+                    Some(&json::Number(v)) if v == manifest_version as float
+                        => (),
+                    _ => return None
+                }
+                let env_hash = match obj.find(&~"env") {
+                    Some(&json::String(ref s)) => copy *s,
+                    _ => return None
+                };
+                let iface_hash = match obj.find(&~"interface") {
+                    Some(&json::String(ref s)) => copy *s,
+                    _ => return None
+                };
+                let mut keys = ~[];
+                match obj.find(&~"units") {
+                    Some(&json::List(ref ks)) => {
+                        for ks.each |k| {
+                            match *k {
+                                json::String(ref k) => keys.push(copy *k),
+                                _ => return None
+                            }
+                        }
+                    }
+                    _ => return None
+                }
+                let mut items = LinearMap::new();
+                match obj.find(&~"items") {
+                    Some(&json::Object(ref is)) => {
+                        for is.each |k, v| {
+                            match *v {
+                                json::String(ref h) => {
+                                    items.insert(copy *k, copy *h);
+                                }
+                                _ => return None
+                            }
+                        }
+                    }
+                    _ => return None
+                }
+                Some((Fingerprint { env_hash: move env_hash,
+                                    iface_hash: move iface_hash,
+                                    items: move items,
+                                    paths: LinearMap::new() }, move keys))
+            }
+            _ => None
+        }
+    }
+}
+
+fn hash_str(hasher: &hash::State, s: &str) -> ~str {
+    hasher.reset();
+    hasher.write_str(s);
+    link::truncated_hash_result(hasher)
+}
+
+/// Hash of the compiler, the options that affect code generation and the
+/// crates linked against
+pub fn env_hash(sess: Session, hasher: &hash::State) -> ~str {
+    let opts = sess.opts;
+    let mut env = ~[];
+    env.push(str::from_slice(env!("CFG_VERSION")));
+    env.push(fmt!("abi=%u", abi::abi_version));
+    env.push(fmt!("crate_type=%u", opts.crate_type as uint));
+    env.push(fmt!("lib=%b", sess.building_library));
+    env.push(fmt!("static=%b gc=%b test=%b", opts.static, opts.gc,
+                  opts.test));
+    env.push(fmt!("opt=%u", opts.optimize as uint));
+    env.push(fmt!("g=%b xg=%b", opts.debuginfo, opts.extra_debuginfo));
+    env.push(fmt!("output=%u", opts.output_type as uint));
+    env.push(fmt!("units=%u", sess.codegen_units()));
+    env.push(fmt!("Z=%u", opts.debugging_opts));
+    env.push(copy opts.target_triple);
+    for opts.cfg.each |mi| {
+        env.push(pprust::meta_item_to_str(*mi, sess.intr()));
+    }
+    for cstore::get_dep_hashes(sess.cstore).each |dh| {
+        env.push(copy *dh);
+    }
+    hash_str(hasher, str::connect(env, "\n"))
+}
+
+// The part of `item`, whose source is `src`, that the code of other units
+// may depend on: the signature of a function that is not generic, and all
+// of anything else
+fn item_interface(sess: Session, item: @ast::item, src: ~str) -> ~str {
+    match item.node {
+        ast::item_fn(ref decl, purity, ref tps, _) if tps.is_empty() => {
+            let intr = sess.intr();
+            let mut sig = ~[];
+            for item.attrs.each |a| {
+                sig.push(pprust::attribute_to_str(*a, intr));
+            }
+            sig.push(pprust::visibility_to_str(item.vis));
+            sig.push(pprust::purity_to_str(purity));
+            sig.push(pprust::fun_to_str(*decl, item.ident, ~[], intr));
+            str::connect(sig, " ")
+        }
+        _ => src
+    }
+}
+
+/// Hashes every item in the crate, keyed by its module path
+pub fn fingerprint(sess: Session, crate: @ast::crate) -> Fingerprint {
+    let hasher = @hash::default_state();
+    let intr = sess.intr();
+    let mut items = LinearMap::new();
+    let mut paths = LinearMap::new();
+
+    let mut iface = ~[];
+    for crate.node.attrs.each |a| {
+        iface.push(pprust::attribute_to_str(*a, intr));
+    }
+
+    fn hash_mod(sess: Session, hasher: &hash::State, prefix: &str,
+                m: &ast::_mod, items: &mut LinearMap<~str, ~str>,
+                paths: &mut LinearMap<ast::node_id, ~str>,
+                iface: &mut ~[~str]) {
+        let intr = sess.intr();
+        for m.view_items.each |vi| {
+            iface.push(prefix.to_owned() +
+                       pprust::to_str(*vi, pprust::print_view_item, intr));
+        }
+
+        for m.items.each |item| {
+            let mut path = prefix.to_owned() + sess.str_of(item.ident);
+            // Impls and foreign mods share names; disambiguate them
+            let mut n = 1u;
+            while items.contains_key(&path) {
+                path = fmt!("%s%s#%u", prefix, sess.str_of(item.ident), n);
+                n += 1u;
+            }
+            match item.node {
+                ast::item_mod(ref sub) => {
+                    hash_mod(sess, hasher, path + ~"::", sub, items, paths,
+                             iface);
+                }
+                _ => {
+                    let mut src = pprust::item_to_str(*item, intr);
+                    if sess.opts.debuginfo {
+                        src += sess.codemap.span_to_str(item.span);
+                    }
+                    iface.push(path + ~" " +
+                               item_interface(sess, *item, copy src));
+                    paths.insert(item.id, copy path);
+                    items.insert(move path, hash_str(hasher, src));
+                }
+            }
+        }
+    }
+    hash_mod(sess, hasher, "", &crate.node.module, &mut items, &mut paths,
+             &mut iface);
+
+    // Sorted, so that moving items around does not change the interface
+    let iface = sort::merge_sort(iface, |a, b| *a <= *b);
+    Fingerprint {
+        env_hash: env_hash(sess, hasher),
+        iface_hash: hash_str(hasher, str::connect(iface, "\n")),
+        items: move items,
+        paths: move paths
+    }
+}
+
+pub fn manifest_path(dir: &Path, crate_name: &str) -> Path {
+    dir.push(crate_name.to_owned() + ~".manifest")
+}
+
+pub fn bitcode_path(dir: &Path, crate_name: &str, key: &str) -> Path {
+    dir.push(fmt!("%s.%s.bc", crate_name, key))
+}
+
+pub fn read_manifest(path: &Path) -> Option<(Fingerprint, ~[~str])> {
+    if !os::path_exists(path) { return None; }
+    match io::read_whole_file_str(path) {
+        result::Ok(ref s) => match json::from_str(*s) {
+            result::Ok(ref j) => Fingerprint::from_json(j),
+            result::Err(_) => None
+        },
+        result::Err(_) => None
+    }
+}
+
+/**
+ * Loads the cached, unoptimised module of each codegen unit whose key in
+ * `keys` was also the key of a unit in the last build. The result has
+ * `None` for each unit that has to be translated.
+ */
+pub fn load_cached_units(sess: Session, dir: &Path, crate_name: &str,
+                         fp: &Fingerprint, keys: &[~str])
+    -> ~[Option<ModuleRef>] {
+    let none = vec::from_elem(keys.len(), None);
+    let (old, old_keys) =
+        match read_manifest(&manifest_path(dir, crate_name)) {
+            Some(move old) => move old,
+            None => return none
+        };
+    if !old.same_env(fp) {
+        if sess.verbose() {
+            sess.note(~"incremental: options or dependencies changed, \
+                        retranslating crate");
+        }
+        return none;
+    }
+    let changed = fp.changed_items(&old);
+    if sess.verbose() {
+        for changed.each |p| {
+            sess.note(fmt!("incremental: `%s` changed", *p));
+        }
+    }
+
+    let mut reused = 0u;
+    let llmods = do vec::from_fn(keys.len()) |i| {
+        let llmod = if vec::contains(old_keys, &keys[i]) {
+            let llmod = load_bitcode(bitcode_path(dir, crate_name, keys[i]));
+            if llmod.is_none() {
+                sess.warn(fmt!("incremental: ignoring missing or damaged \
+                                cache file for unit %u of %s", i,
+                               crate_name));
+            }
+            llmod
+        } else {
+            None
+        };
+        if llmod.is_some() {
+            reused += 1u;
+            if sess.verbose() {
+                sess.note(fmt!("incremental: reusing codegen unit %u of %s",
+                               i, crate_name));
+            }
+        }
+        llmod
+    };
+    if sess.profiling() {
+        sess.profile.count("incremental.items_changed", changed.len());
+        sess.profile.count("incremental.units_reused", reused);
+    }
+    llmods
+}
+
+/// Frees modules loaded from the cache that are not going to be used
+pub fn discard_modules(llmods: &[Option<ModuleRef>]) {
+    for llmods.each |llmod| {
+        for llmod.each |llmod| {
+            unsafe { llvm::LLVMDisposeModule(*llmod); }
+        }
+    }
+}
+
+fn load_bitcode(bc: Path) -> Option<ModuleRef> {
+    if !os::path_exists(&bc) { return None; }
+    unsafe {
+        let llmb = str::as_c_str(bc.to_str(), |buf| {
+            llvm::LLVMRustCreateMemoryBufferWithContentsOfFile(buf)
+        });
+        if llmb as int == 0 { return None; }
+        let llmod = llvm::LLVMRustParseBitcode(llmb);
+        llvm::LLVMDisposeMemoryBuffer(llmb);
+        if llmod as int == 0 { None } else { Some(llmod) }
+    }
+}
+
+/**
+ * Records the freshly translated, not yet optimised modules of the units
+ * keyed by `keys` in the cache, and forgets the units of the last build
+ * that are not among them. `fresh` has `None` for the units that were
+ * loaded from the cache, which are already in it.
+ */
+pub fn save_units(sess: Session, dir: &Path, crate_name: &str,
+                  fp: &Fingerprint, keys: &[~str],
+                  fresh: &[Option<ModuleRef>]) {
+    if !os::path_is_dir(dir) && !os::make_dir(dir, 493_i32 /* oct: 755 */) {
+        sess.warn(fmt!("incremental: could not create %s", dir.to_str()));
+        return;
+    }
+    // The old manifest goes first and the new one is written last, so that
+    // an interrupted write never pairs a fingerprint with the wrong bitcode
+    let manifest = manifest_path(dir, crate_name);
+    let old_keys = match read_manifest(&manifest) {
+        Some((_, move old_keys)) => move old_keys,
+        None => ~[]
+    };
+    if os::path_exists(&manifest) && !os::remove_file(&manifest) {
+        sess.warn(fmt!("incremental: could not remove %s",
+                       manifest.to_str()));
+        return;
+    }
+    for old_keys.each |k| {
+        if !vec::contains(keys, k) {
+            os::remove_file(&bitcode_path(dir, crate_name, *k));
+        }
+    }
+    for fresh.eachi |i, llmod| {
+        for llmod.each |llmod| {
+            let bc = bitcode_path(dir, crate_name, keys[i]);
+            unsafe {
+                let _: () = str::as_c_str(bc.to_str(), |buf| {
+                    llvm::LLVMWriteBitcodeToFile(*llmod, buf);
+                });
+            }
+        }
+    }
+    match io::file_writer(&manifest, ~[io::Create, io::Truncate]) {
+        result::Ok(w) => json::to_writer(w, &fp.to_json(keys)),
+        result::Err(e) => {
+            sess.warn(fmt!("incremental: could not write %s: %s",
+                           manifest.to_str(), e));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::prelude::*;
+
+    use back::incremental::{Fingerprint, fingerprint};
+    use driver::driver::build_session;
+    use driver::session;
+    use middle::trans::partition::partition_crate;
+
+    use core::hashmap::linear::LinearMap;
+    use core::uint;
+    use syntax::diagnostic;
+    use syntax::parse;
+
+    fn fp(env: &str, items: &[(~str, ~str)]) -> Fingerprint {
+        let mut map = LinearMap::new();
+        for items.each |&(k, v)| { map.insert(copy k, copy v); }
+        Fingerprint { env_hash: env.to_owned(), iface_hash: ~"i",
+                      items: move map, paths: LinearMap::new() }
+    }
+
+    // The keys of the codegen units of `src`, one for each top-level module
+    fn unit_keys(src: ~str) -> ~[~str] {
+        let sess = build_session(session::basic_options(), diagnostic::emit);
+        let crate = parse::parse_crate_from_source_str(
+            ~"<test>", @src, ~[], sess.parse_sess);
+        let fp = fingerprint(sess, crate);
+        do partition_crate(crate, 10u).map |unit| {
+            fp.unit_key(*unit, false)
+        }
+    }
+
+    #[test]
+    fn test_changed_items() {
+        let old = fp("e", ~[(~"a", ~"1"), (~"b", ~"2"), (~"c", ~"3")]);
+        let new = fp("e", ~[(~"a", ~"1"), (~"b", ~"x"), (~"d", ~"4")]);
+        assert new.changed_items(&old) == ~[~"b", ~"c", ~"d"];
+        assert old.changed_items(&old).is_empty();
+    }
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let f = fp("env", ~[(~"m::f", ~"abc")]);
+        let (g, keys) =
+            Fingerprint::from_json(&f.to_json(~[~"k0", ~"k1"])).get();
+        assert keys == ~[~"k0", ~"k1"];
+        assert g.same_env(&f);
+        assert g.changed_items(&f).is_empty();
+    }
+
+    #[test]
+    fn test_editing_a_function_keeps_other_units() {
+        let old = unit_keys(~"fn main() { a::f(); }\n\
+                              mod a { pub fn f() -> int { 1 } }\n\
+                              mod b { pub fn g() -> int { 2 } }\n");
+        assert old.len() == 3u;
+
+        // Moving things down a line changes nothing
+        let moved = unit_keys(~"// a comment\n\nfn main() { a::f(); }\n\
+                                mod a { pub fn f() -> int { 1 } }\n\
+                                mod b { pub fn g() -> int { 2 } }\n");
+        assert moved == old;
+
+        // Changing the body of `a::f` only changes the unit of `a`, and the
+        // cached bitcode of the others is reused
+        let edited = unit_keys(~"fn main() { a::f(); }\n\
+                                 mod a { pub fn f() -> int { 3 } }\n\
+                                 mod b { pub fn g() -> int { 2 } }\n");
+        let mut same = 0u;
+        for uint::range(0u, 3u) |i| {
+            if edited[i] == old[i] { same += 1u; }
+        }
+        assert same == 2u;
+
+        // Changing its signature changes the code of its callers
+        let retyped = unit_keys(~"fn main() { a::f(); }\n\
+                                  mod a { pub fn f() -> uint { 1 } }\n\
+                                  mod b { pub fn g() -> int { 2 } }\n");
+        for uint::range(0u, 3u) |i| { assert retyped[i] != old[i]; }
+    }
+}
@@ -88,14 +88,16 @@ pub fn WriteOutputFile(sess: Session,
 }
 
 pub mod jit {
-    use back::link::llvm_err;
+    use back::link::{llvm_err, write};
     use lib::llvm::llvm;
-    use lib::llvm::{ModuleRef, PassManagerRef, mk_target_data};
+    use lib::llvm::{ModuleRef, PassManagerRef, mk_pass_manager};
+    use lib::llvm::mk_target_data;
     use metadata::cstore;
     use session::Session;
 
     use core::cast;
     use core::libc::c_int;
+    use core::path::Path;
     use core::ptr;
     use core::str;
 
@@ -162,6 +164,31 @@ pub mod jit {
             }
         }
     }
+
+    /// Runs a crate from the bitcode written for it with `--emit-llvm`,
+    /// where `exec` runs a crate just translated.
+    pub fn exec_bitcode(sess: Session, bitcode: &Path) {
+        unsafe {
+            let llmb = do str::as_c_str(bitcode.to_str()) |buf| {
+                llvm::LLVMRustCreateMemoryBufferWithContentsOfFile(buf)
+            };
+            if llmb as int == 0 {
+                llvm_err(sess, ~"Could not read " + bitcode.to_str());
+            }
+            let llmod = llvm::LLVMRustParseBitcode(llmb);
+            llvm::LLVMDisposeMemoryBuffer(llmb);
+            if llmod as int == 0 {
+                llvm_err(sess, ~"Could not parse " + bitcode.to_str());
+            }
+
+            let pm = mk_pass_manager();
+            let td = mk_target_data(
+                /*bad*/copy sess.targ_cfg.target_strs.data_layout);
+            llvm::LLVMAddTargetData(td.lltd, pm.llpm);
+            exec(sess, pm.llpm, llmod,
+                 write::codegen_opt_level(sess.opts.optimize), true);
+        }
+    }
 }
 
 pub mod write {
@@ -173,13 +200,19 @@ pub mod write {
     use driver::session;
     use lib::llvm::llvm;
     use lib::llvm::{False, True, ModuleRef, mk_pass_manager, mk_target_data};
+    use lib::llvm::{PassManagerRef, target_data};
     use lib;
     use session::Session;
 
     use core::char;
     use core::libc::{c_char, c_int, c_uint};
+    use core::os;
     use core::path::Path;
+    use core::pipes;
+    use core::result::{Result, Ok, Err};
     use core::str;
+    use core::task;
+    use core::uint;
     use core::vec;
 
     pub fn is_object_or_assembly_or_exe(ot: output_type) -> bool {
@@ -190,6 +223,181 @@ pub mod write {
         return false;
     }
 
+    pub fn codegen_opt_level(optimize: session::OptLevel) -> c_int {
+        let LLVMOptNone       = 0 as c_int; // -O0
+        let LLVMOptLess       = 1 as c_int; // -O1
+        let LLVMOptDefault    = 2 as c_int; // -O2, -Os
+        let LLVMOptAggressive = 3 as c_int; // -O3
+
+        match optimize {
+          session::No => LLVMOptNone,
+          session::Less => LLVMOptLess,
+          session::Default => LLVMOptDefault,
+          session::Aggressive => LLVMOptAggressive
+        }
+    }
+
+    // Runs the function passes over `llmod` right away and adds the module
+    // passes to `llpm`.
+    pub fn add_optimization_passes(td: target_data, llpm: PassManagerRef,
+                                   llmod: ModuleRef,
+                                   optimize: session::OptLevel) {
+        unsafe {
+            let fpm = mk_pass_manager();
+            llvm::LLVMAddTargetData(td.lltd, fpm.llpm);
+
+            let FPMB = llvm::LLVMPassManagerBuilderCreate();
+            llvm::LLVMPassManagerBuilderSetOptLevel(FPMB, 2u as c_uint);
+            llvm::LLVMPassManagerBuilderPopulateFunctionPassManager(
+                FPMB, fpm.llpm);
+            llvm::LLVMPassManagerBuilderDispose(FPMB);
+
+            llvm::LLVMRunPassManager(fpm.llpm, llmod);
+            let mut threshold = 225;
+            if optimize == session::Aggressive { threshold = 275; }
+
+            let MPMB = llvm::LLVMPassManagerBuilderCreate();
+            llvm::LLVMPassManagerBuilderSetOptLevel(MPMB,
+                                                    optimize as c_uint);
+            llvm::LLVMPassManagerBuilderSetSizeLevel(MPMB, False);
+            llvm::LLVMPassManagerBuilderSetDisableUnitAtATime(MPMB, False);
+            llvm::LLVMPassManagerBuilderSetDisableUnrollLoops(MPMB, False);
+            llvm::LLVMPassManagerBuilderSetDisableSimplifyLibCalls(MPMB,
+                                                                   False);
+
+            if threshold != 0u {
+                llvm::LLVMPassManagerBuilderUseInlinerWithThreshold
+                    (MPMB, threshold as c_uint);
+            }
+            llvm::LLVMPassManagerBuilderPopulateModulePassManager(
+                MPMB, llpm);
+
+            llvm::LLVMPassManagerBuilderDispose(MPMB);
+        }
+    }
+
+    /// The object file each codegen unit is emitted to. The first unit uses
+    /// the crate's object file name, the others `<stem>.N.o`.
+    pub fn unit_obj_filenames(obj_filename: &Path, n: uint) -> ~[Path] {
+        do vec::from_fn(n) |i| {
+            if i == 0u {
+                copy *obj_filename
+            } else {
+                obj_filename.with_filetype(fmt!("%u.o", i))
+            }
+        }
+    }
+
+    // Everything a codegen unit's thread needs to know about the session,
+    // which itself cannot leave the main task.
+    struct UnitConfig {
+        optimize: session::OptLevel,
+        verify: bool,
+        data_layout: ~str,
+        target_triple: ~str,
+    }
+
+    // Parses a codegen unit's bitcode into a context of its own, optimises
+    // it and writes its object file.
+    fn emit_unit(cfg: &UnitConfig, bitcode: &Path, output: &Path)
+              -> Result<(), ~str> {
+        unsafe {
+            let llcx = llvm::LLVMContextCreate();
+            let llmb = str::as_c_str(bitcode.to_str(), |buf| {
+                llvm::LLVMRustCreateMemoryBufferWithContentsOfFile(buf)
+            });
+            if llmb as int == 0 {
+                llvm::LLVMContextDispose(llcx);
+                return Err(fmt!("could not read %s", bitcode.to_str()));
+            }
+            let llmod = llvm::LLVMRustParseBitcodeInContext(llmb, llcx);
+            llvm::LLVMDisposeMemoryBuffer(llmb);
+            if llmod as int == 0 {
+                llvm::LLVMContextDispose(llcx);
+                return Err(fmt!("could not parse %s", bitcode.to_str()));
+            }
+
+            let ok = {
+                let pm = mk_pass_manager();
+                let td = mk_target_data(copy cfg.data_layout);
+                llvm::LLVMAddTargetData(td.lltd, pm.llpm);
+                if cfg.optimize != session::No {
+                    add_optimization_passes(td, pm.llpm, llmod,
+                                            cfg.optimize);
+                }
+                if cfg.verify { llvm::LLVMAddVerifierPass(pm.llpm); }
+                do str::as_c_str(cfg.target_triple) |buf_t| {
+                    do str::as_c_str(output.to_str()) |buf_o| {
+                        llvm::LLVMRustWriteOutputFile(
+                            pm.llpm, llmod, buf_t, buf_o,
+                            lib::llvm::ObjectFile as c_uint,
+                            codegen_opt_level(cfg.optimize), true)
+                    }
+                }
+            };
+            llvm::LLVMDisposeModule(llmod);
+            llvm::LLVMContextDispose(llcx);
+            if ok {
+                Ok(())
+            } else {
+                Err(fmt!("could not write %s", output.to_str()))
+            }
+        }
+    }
+
+    /**
+     * Optimises and emits the modules of several codegen units in parallel,
+     * one OS thread per unit.
+     *
+     * LLVM cannot work on modules of one context from several threads, and
+     * trans builds every module in the global context. So each module is
+     * written out as bitcode first, and each thread reads its unit back
+     * into a context of its own.
+     */
+    pub fn run_passes_parallel(sess: Session, llmods: &[ModuleRef],
+                               outputs: &[Path]) {
+        assert llmods.len() == outputs.len();
+        let bitcodes = do outputs.map |o| { o.with_filetype("unit.bc") };
+        unsafe {
+            for llmods.eachi |i, llmod| {
+                do str::as_c_str(bitcodes[i].to_str()) |buf| {
+                    llvm::LLVMWriteBitcodeToFile(*llmod, buf);
+                }
+                llvm::LLVMDisposeModule(*llmod);
+            }
+            llvm::LLVMRustStartMultithreading();
+        }
+
+        let (port, chan) = pipes::stream();
+        let chan = pipes::SharedChan(chan);
+        for uint::range(0u, llmods.len()) |i| {
+            let cfg = UnitConfig {
+                optimize: sess.opts.optimize,
+                verify: !sess.no_verify(),
+                data_layout: copy sess.targ_cfg.target_strs.data_layout,
+                target_triple: copy sess.targ_cfg.target_strs.target_triple
+            };
+            let bitcode = copy bitcodes[i];
+            let output = copy outputs[i];
+            let chan = chan.clone();
+            do task::task().sched_mode(task::SingleThreaded).spawn
+                    |move cfg, move bitcode, move output, move chan| {
+                chan.send(emit_unit(&cfg, &bitcode, &output));
+            }
+        }
+        for uint::range(0u, llmods.len()) |_| {
+            match port.recv() {
+              Ok(()) => (),
+              Err(e) => sess.err(e)
+            }
+        }
+
+        if !sess.opts.save_temps {
+            for bitcodes.each |bc| { os::remove_file(bc); }
+        }
+        sess.abort_if_errors();
+    }
+
     pub fn run_passes(sess: Session, llmod: ModuleRef, output: &Path) {
         unsafe {
             let opts = sess.opts;
@@ -232,53 +440,11 @@ pub mod write {
             // tool?
 
             if opts.optimize != session::No {
-                let fpm = mk_pass_manager();
-                llvm::LLVMAddTargetData(td.lltd, fpm.llpm);
-
-                let FPMB = llvm::LLVMPassManagerBuilderCreate();
-                llvm::LLVMPassManagerBuilderSetOptLevel(FPMB, 2u as c_uint);
-                llvm::LLVMPassManagerBuilderPopulateFunctionPassManager(
-                    FPMB, fpm.llpm);
-                llvm::LLVMPassManagerBuilderDispose(FPMB);
-
-                llvm::LLVMRunPassManager(fpm.llpm, llmod);
-                let mut threshold = 225;
-                if opts.optimize == session::Aggressive { threshold = 275; }
-
-                let MPMB = llvm::LLVMPassManagerBuilderCreate();
-                llvm::LLVMPassManagerBuilderSetOptLevel(MPMB,
-                                                        opts.optimize as
-                                                            c_uint);
-                llvm::LLVMPassManagerBuilderSetSizeLevel(MPMB, False);
-                llvm::LLVMPassManagerBuilderSetDisableUnitAtATime(MPMB,
-                                                                  False);
-                llvm::LLVMPassManagerBuilderSetDisableUnrollLoops(MPMB,
-                                                                  False);
-                llvm::LLVMPassManagerBuilderSetDisableSimplifyLibCalls(MPMB,
-                                                                       False);
-
-                if threshold != 0u {
-                    llvm::LLVMPassManagerBuilderUseInlinerWithThreshold
-                        (MPMB, threshold as c_uint);
-                }
-                llvm::LLVMPassManagerBuilderPopulateModulePassManager(
-                    MPMB, pm.llpm);
-
-                llvm::LLVMPassManagerBuilderDispose(MPMB);
+                add_optimization_passes(td, pm.llpm, llmod, opts.optimize);
             }
             if !sess.no_verify() { llvm::LLVMAddVerifierPass(pm.llpm); }
             if is_object_or_assembly_or_exe(opts.output_type) || opts.jit {
-                let LLVMOptNone       = 0 as c_int; // -O0
-                let LLVMOptLess       = 1 as c_int; // -O1
-                let LLVMOptDefault    = 2 as c_int; // -O2, -Os
-                let LLVMOptAggressive = 3 as c_int; // -O3
-
-                let mut CodeGenOptLevel = match opts.optimize {
-                  session::No => LLVMOptNone,
-                  session::Less => LLVMOptLess,
-                  session::Default => LLVMOptDefault,
-                  session::Aggressive => LLVMOptAggressive
-                };
+                let mut CodeGenOptLevel = codegen_opt_level(opts.optimize);
 
                 if opts.jit {
                     // If we are using JIT, go ahead and create and
@@ -717,7 +883,7 @@ pub fn output_dll_filename(os: session::os, lm: link_meta) -> ~str {
 // If the user wants an exe generated we need to invoke
 // cc to link the object file with some libs
 pub fn link_binary(sess: Session,
-                   obj_filename: &Path,
+                   obj_filenames: &[Path],
                    out_filename: &Path,
                    lm: link_meta) {
     // Converts a library file-stem into a cc -l argument
@@ -764,7 +930,7 @@ pub fn link_binary(sess: Session,
         vec::append(~[stage], sess.targ_cfg.target_strs.cc_args);
     cc_args.push(~"-o");
     cc_args.push(output.to_str());
-    cc_args.push(obj_filename.to_str());
+    for obj_filenames.each |obj| { cc_args.push(obj.to_str()); }
 
     let mut lib_cmd;
     let os = sess.targ_cfg.os;
@@ -880,11 +1046,13 @@ pub fn link_binary(sess: Session,
         run::run_program(~"dsymutil", ~[output.to_str()]);
     }
 
-    // Remove the temporary object file if we aren't saving temps
+    // Remove the temporary object files if we aren't saving temps
     if !sess.opts.save_temps {
-        if ! os::remove_file(obj_filename) {
-            sess.warn(fmt!("failed to delete object file `%s`",
-                           obj_filename.to_str()));
+        for obj_filenames.each |obj| {
+            if ! os::remove_file(obj) {
+                sess.warn(fmt!("failed to delete object file `%s`",
+                               obj.to_str()));
+            }
         }
     }
 }
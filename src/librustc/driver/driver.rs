@@ -10,10 +10,13 @@
 
 use core::prelude::*;
 
+use back::incremental;
 use back::link;
 use back::{arm, x86, x86_64};
+use driver::profile;
 use front;
-use lib::llvm::llvm;
+use lib::llvm::{llvm, ModuleRef};
+use metadata::common::link_meta;
 use metadata::{creader, cstore, filesearch};
 use metadata;
 use middle::{trans, freevars, kind, ty, typeck, lint, astencode};
@@ -23,6 +26,7 @@ use session;
 use util::ppaux;
 
 use core::cmp;
+use core::hash;
 use core::int;
 use core::io::WriterUtil;
 use core::io;
@@ -30,6 +34,7 @@ use core::option;
 use core::os;
 use core::result::{Ok, Err};
 use core::str;
+use core::uint;
 use core::vec;
 use std::getopts::groups::{optopt, optmulti, optflag, optflagopt, getopts};
 use std::getopts::groups;
@@ -54,6 +59,11 @@ pub enum pp_mode {
     ppm_expanded_identified
 }
 
+pub enum ls_mode {
+    ls_text,
+    ls_json
+}
+
 /**
  * The name used for source code that doesn't originate in a file
  * (e.g. source from stdin or a string)
@@ -175,6 +185,19 @@ pub fn time<T>(do_it: bool, what: ~str, thunk: fn() -> T) -> T {
     move rv
 }
 
+/// Times a compiler phase: records it in the session profile and, under
+/// `-Z time-passes`, also prints it as it finishes.
+pub fn time_phase<T>(sess: Session, what: ~str, thunk: fn() -> T) -> T {
+    if !sess.profiling() { return thunk(); }
+    let rv = sess.profile.time(what, thunk);
+    if sess.time_passes() {
+        let phase = &sess.profile.phases[sess.profile.phases.len() - 1u];
+        io::stdout().write_str(fmt!("time: %3.3f s\t%s\n",
+                                    phase.secs, what));
+    }
+    move rv
+}
+
 pub enum compile_upto {
     cu_parse,
     cu_expand,
@@ -190,125 +213,158 @@ pub impl compile_upto : cmp::Eq {
     pure fn ne(&self, other: &compile_upto) -> bool { !(*self).eq(other) }
 }
 
+// Compiles the input as far as `upto`, returning the crate as it was then,
+// its type context once it has been type checked, and the moves it makes
+// once they have been computed.
 pub fn compile_upto(sess: Session, cfg: ast::crate_cfg,
                     input: input, upto: compile_upto,
                     outputs: Option<output_filenames>)
-                 -> {crate: @ast::crate, tcx: Option<ty::ctxt>} {
-    let time_passes = sess.time_passes();
-    let mut crate = time(time_passes, ~"parsing",
+                 -> {crate: @ast::crate, tcx: Option<ty::ctxt>,
+                     moves: Option<middle::moves::MoveMaps>} {
+    let mut crate = time_phase(sess, ~"parsing",
                          || parse_input(sess, copy cfg, input) );
-    if upto == cu_parse { return {crate: crate, tcx: None}; }
+    if upto == cu_parse { return {crate: crate, tcx: None, moves: None}; }
 
     sess.building_library = session::building_library(
         sess.opts.crate_type, crate, sess.opts.test);
 
-    crate = time(time_passes, ~"configuration", ||
+    crate = time_phase(sess, ~"configuration", ||
         front::config::strip_unconfigured_items(crate));
 
-    crate = time(time_passes, ~"maybe building test harness", ||
+    crate = time_phase(sess, ~"maybe building test harness", ||
         front::test::modify_for_testing(sess, crate));
 
-    crate = time(time_passes, ~"expansion", ||
-        syntax::ext::expand::expand_crate(sess.parse_sess, copy cfg,
-                                          crate));
-
-    if upto == cu_expand { return {crate: crate, tcx: None}; }
+    let imported_macros = time_phase(sess, ~"exported macro loading", ||
+        creader::read_exported_macros(sess.diagnostic(), *crate,
+                                      sess.filesearch,
+                                      session::sess_os_to_meta_os(
+                                          sess.targ_cfg.os),
+                                      sess.opts.static,
+                                      sess.parse_sess.interner));
+
+    let (expanded, exported_macros) = time_phase(sess, ~"expansion", ||
+        syntax::ext::expand::expand_crate_with_macros(sess.parse_sess,
+                                                      copy cfg,
+                                                      copy imported_macros,
+                                                      crate));
+    crate = expanded;
+    sess.exported_macros = exported_macros;
+
+    // Expansion carries on after syntax errors in the expansions of macros,
+    // so as to report them all; this is as far as they should get
+    sess.abort_if_errors();
+
+    if upto == cu_expand {
+        return {crate: crate, tcx: None, moves: None};
+    }
 
-    crate = time(time_passes, ~"intrinsic injection", ||
+    crate = time_phase(sess, ~"intrinsic injection", ||
         front::intrinsic_inject::inject_intrinsic(sess, crate));
 
-    crate = time(time_passes, ~"core injection", ||
+    crate = time_phase(sess, ~"core injection", ||
         front::core_inject::maybe_inject_libcore_ref(sess, crate));
 
-    time(time_passes, ~"building lint settings table", ||
+    time_phase(sess, ~"building lint settings table", ||
         lint::build_settings_crate(sess, crate));
 
-    let ast_map = time(time_passes, ~"ast indexing", ||
+    let ast_map = time_phase(sess, ~"ast indexing", ||
             syntax::ast_map::map_crate(sess.diagnostic(), *crate));
 
-    time(time_passes, ~"external crate/lib resolution", ||
+    time_phase(sess, ~"external crate/lib resolution", ||
         creader::read_crates(sess.diagnostic(), *crate, sess.cstore,
                              sess.filesearch,
                              session::sess_os_to_meta_os(sess.targ_cfg.os),
                              sess.opts.static,
                              sess.parse_sess.interner));
 
-    let lang_items = time(time_passes, ~"language item collection", ||
+    let lang_items = time_phase(sess, ~"language item collection", ||
          middle::lang_items::collect_language_items(crate, sess));
 
     let { def_map: def_map,
           exp_map2: exp_map2,
           trait_map: trait_map } =
-        time(time_passes, ~"resolution", ||
+        time_phase(sess, ~"resolution", ||
              middle::resolve::resolve_crate(sess, lang_items, crate));
 
-    let freevars = time(time_passes, ~"freevar finding", ||
+    let freevars = time_phase(sess, ~"freevar finding", ||
         freevars::annotate_freevars(def_map, crate));
 
-    let region_map = time(time_passes, ~"region resolution", ||
+    let region_map = time_phase(sess, ~"region resolution", ||
         middle::region::resolve_crate(sess, def_map, crate));
 
-    let rp_set = time(time_passes, ~"region parameterization inference", ||
+    let rp_set = time_phase(sess, ~"region parameterization inference", ||
         middle::region::determine_rp_in_crate(sess, ast_map, def_map, crate));
 
 
     let outputs = outputs.get();
 
-    let (llmod, link_meta) = {
+    let (llmods, link_meta, ty_cx, move_maps) = {
 
         let ty_cx = ty::mk_ctxt(sess, def_map, ast_map, freevars,
                                 region_map, rp_set, move lang_items, crate);
 
         let (method_map, vtable_map) =
-            time(time_passes, ~"typechecking", ||
+            time_phase(sess, ~"typechecking", ||
                  typeck::check_crate(ty_cx,
                                      trait_map,
                                      crate));
 
         // These next two const passes can probably be merged
-        time(time_passes, ~"const marking", ||
+        time_phase(sess, ~"const marking", ||
              middle::const_eval::process_crate(crate, def_map, ty_cx));
 
-        time(time_passes, ~"const checking", ||
+        time_phase(sess, ~"const checking", ||
              middle::check_const::check_crate(sess, crate, ast_map, def_map,
                                               method_map, ty_cx));
 
-        if upto == cu_typeck { return {crate: crate, tcx: Some(ty_cx)}; }
+        if sess.save_analysis() {
+            time_phase(sess, ~"saving analysis", ||
+                 middle::save_analysis::save_crate(
+                     ty_cx, method_map, crate,
+                     &outputs.out_filename.with_filetype("csv")));
+        }
+
+        if upto == cu_typeck {
+            return {crate: crate, tcx: Some(ty_cx), moves: None};
+        }
 
-        time(time_passes, ~"privacy checking", ||
+        time_phase(sess, ~"privacy checking", ||
              middle::privacy::check_crate(ty_cx, &method_map, crate));
 
-        time(time_passes, ~"loop checking", ||
+        time_phase(sess, ~"loop checking", ||
              middle::check_loop::check_crate(ty_cx, crate));
 
-        let middle::moves::MoveMaps {moves_map, variable_moves_map,
-                                     capture_map} =
-            time(time_passes, ~"compute moves", ||
+        let move_maps =
+            time_phase(sess, ~"compute moves", ||
                  middle::moves::compute_moves(ty_cx, method_map, crate));
+        let middle::moves::MoveMaps {moves_map, variable_moves_map,
+                                     capture_map} = copy move_maps;
 
-        time(time_passes, ~"match checking", ||
+        time_phase(sess, ~"match checking", ||
              middle::check_match::check_crate(ty_cx, method_map,
                                               moves_map, crate));
 
         let last_use_map =
-            time(time_passes, ~"liveness checking", ||
+            time_phase(sess, ~"liveness checking", ||
                  middle::liveness::check_crate(ty_cx, method_map,
                                                variable_moves_map,
                                                capture_map, crate));
 
         let (root_map, mutbl_map, write_guard_map) =
-            time(time_passes, ~"borrow checking", ||
+            time_phase(sess, ~"borrow checking", ||
                  middle::borrowck::check_crate(ty_cx, method_map,
                                                moves_map, capture_map,
                                                crate));
 
-        time(time_passes, ~"kind checking", ||
+        time_phase(sess, ~"kind checking", ||
              kind::check_crate(ty_cx, method_map, last_use_map, crate));
 
-        time(time_passes, ~"lint checking", ||
+        time_phase(sess, ~"lint checking", ||
              lint::check_crate(ty_cx, crate));
 
-        if upto == cu_no_trans { return {crate: crate, tcx: Some(ty_cx)}; }
+        if upto == cu_no_trans {
+            return {crate: crate, tcx: Some(ty_cx), moves: Some(move_maps)};
+        }
 
         let maps = astencode::Maps {
             mutbl_map: mutbl_map,
@@ -321,31 +377,113 @@ pub fn compile_upto(sess: Session, cfg: ast::crate_cfg,
             capture_map: capture_map
         };
 
-        time(time_passes, ~"translation", ||
-             trans::base::trans_crate(sess, crate, ty_cx,
-                                      &outputs.obj_filename,
-                                      exp_map2, maps))
-
+        let (llmods, link_meta) = match sess.opts.incremental {
+          None => {
+            time_phase(sess, ~"translation", ||
+                 trans::base::trans_crate(sess, crate, ty_cx,
+                                          &outputs.obj_filename,
+                                          exp_map2, maps))
+          }
+          Some(ref dir) => {
+            trans_incrementally(sess, dir, crate, ty_cx, &outputs,
+                                exp_map2, maps)
+          }
+        };
+        (llmods, link_meta, ty_cx, move_maps)
     };
 
 
-    time(time_passes, ~"LLVM passes", ||
-        link::write::run_passes(sess, llmod,
-                                &outputs.obj_filename));
+    let obj_filenames = link::write::unit_obj_filenames(
+        &outputs.obj_filename, llmods.len());
+    if llmods.len() == 1u {
+        time_phase(sess, ~"LLVM passes", ||
+            link::write::run_passes(sess, llmods[0],
+                                    &outputs.obj_filename));
+    } else {
+        time_phase(sess, ~"LLVM passes", ||
+            link::write::run_passes_parallel(sess, llmods, obj_filenames));
+    }
 
     let stop_after_codegen =
         sess.opts.output_type != link::output_type_exe ||
         (sess.opts.static && sess.building_library)    ||
         sess.opts.jit;
 
-    if stop_after_codegen { return {crate: crate, tcx: None}; }
+    if stop_after_codegen {
+        return {crate: crate, tcx: Some(ty_cx), moves: Some(move_maps)};
+    }
 
-    time(time_passes, ~"linking", ||
+    time_phase(sess, ~"linking", ||
          link::link_binary(sess,
-                           &outputs.obj_filename,
+                           obj_filenames,
                            &outputs.out_filename, link_meta));
 
-    return {crate: crate, tcx: None};
+    return {crate: crate, tcx: Some(ty_cx), moves: Some(move_maps)};
+}
+
+/**
+ * Translates the crate, reusing the bitcode cached in `dir` by a previous
+ * run for each codegen unit whose items, and whose view of the rest of the
+ * crate, have not changed since.
+ */
+fn trans_incrementally(sess: Session, dir: &Path, crate: @ast::crate,
+                       ty_cx: ty::ctxt, outputs: &output_filenames,
+                       exp_map2: middle::resolve::ExportMap2,
+                       maps: astencode::Maps)
+                    -> (~[ModuleRef], link_meta) {
+    let fp = time_phase(sess, ~"incremental fingerprinting", ||
+        incremental::fingerprint(sess, crate));
+    let name = outputs.obj_filename.filestem().get();
+    let units = trans::partition::partition_crate(crate,
+                                                  sess.codegen_units());
+    let keys = do units.map |unit| {
+        fp.unit_key(*unit, sess.building_library && unit.is_primary())
+    };
+    let mut cached =
+        incremental::load_cached_units(sess, dir, name, &fp, keys);
+    // A library's metadata, in its primary unit, needs the symbols of every
+    // item, which only translating all of the units provides
+    if sess.building_library && cached[0].is_none() {
+        incremental::discard_modules(cached);
+        cached = vec::from_elem(units.len(), None);
+    }
+
+    let mut stale = ~[];
+    for units.eachi |i, unit| {
+        if cached[i].is_none() { stale.push(*unit); }
+    }
+    if sess.profiling() {
+        sess.profile.count("incremental.units_translated", stale.len());
+    }
+    if stale.is_empty() {
+        if sess.verbose() {
+            sess.note(fmt!("incremental: reusing cached translation of %s",
+                           name));
+        }
+        let llmods = cached.map(|m| m.get());
+        return (llmods, link::build_link_meta(sess, crate,
+                                              &outputs.obj_filename,
+                                              @hash::default_state()));
+    }
+
+    let (fresh, link_meta) = time_phase(sess, ~"translation", ||
+        trans::base::trans_units(sess, crate, ty_cx, &outputs.obj_filename,
+                                 exp_map2, maps, stale));
+    let mut next = 0u;
+    let mut saved = ~[];
+    let llmods = do vec::from_fn(units.len()) |i| {
+        match cached[i] {
+          Some(llmod) => { saved.push(None); llmod }
+          None => {
+            let llmod = fresh[next];
+            next += 1u;
+            saved.push(Some(llmod));
+            llmod
+          }
+        }
+    };
+    incremental::save_units(sess, dir, name, &fp, keys, saved);
+    (llmods, link_meta)
 }
 
 pub fn compile_input(sess: Session, +cfg: ast::crate_cfg, input: input,
@@ -356,6 +494,21 @@ pub fn compile_input(sess: Session, +cfg: ast::crate_cfg, input: input,
                else { cu_everything };
     let outputs = build_output_filenames(input, outdir, output, sess);
     compile_upto(sess, cfg, input, upto, Some(outputs));
+    write_profile(sess, input);
+}
+
+/// Writes the JSON compilation profile requested by `-Z profile-out`
+pub fn write_profile(sess: Session, input: input) {
+    match sess.opts.profile_out {
+      Some(ref path) => {
+        match sess.profile.write_report(path, source_name(input)) {
+          Ok(()) => (),
+          Err(e) => sess.err(fmt!("failed to write profile to %s: %s",
+                                  path.to_str(), e))
+        }
+      }
+      None => ()
+    }
 }
 
 pub fn pretty_print_input(sess: Session, +cfg: ast::crate_cfg, input: input,
@@ -410,7 +563,7 @@ pub fn pretty_print_input(sess: Session, +cfg: ast::crate_cfg, input: input,
       ppm_typed => cu_typeck,
       _ => cu_parse
     };
-    let {crate, tcx} = compile_upto(sess, cfg, input, upto, None);
+    let {crate, tcx, _} = compile_upto(sess, cfg, input, upto, None);
 
     let ann = match ppm {
       ppm_typed => {
@@ -551,9 +704,47 @@ pub fn build_session_options(+binary: ~str,
     }
 
     let mut debugging_opts = 0u;
+    let mut profile_out = None;
+    let mut incremental = None;
+    let mut codegen_units = 1u;
+    let mut codegen_units_given = false;
     let debug_flags = getopts::opt_strs(matches, ~"Z");
     let debug_map = session::debugging_opts_map();
     for debug_flags.each |debug_flag| {
+        // A few debugging options take an argument rather than being a flag
+        fn opt_arg(demitter: diagnostic::emitter, flag: &str, name: &str)
+                   -> Option<~str> {
+            let prefix = name.to_owned() + ~"=";
+            if !str::starts_with(flag, prefix) { return None; }
+            let arg = str::slice(flag, prefix.len(), flag.len());
+            if arg.is_empty() {
+                early_error(demitter, fmt!("-Z %s requires an argument",
+                                           name))
+            }
+            Some(arg)
+        }
+        match opt_arg(demitter, *debug_flag, "profile-out") {
+          Some(p) => { profile_out = Some(Path(p)); loop; }
+          None => ()
+        }
+        match opt_arg(demitter, *debug_flag, "incremental") {
+          Some(p) => { incremental = Some(Path(p)); loop; }
+          None => ()
+        }
+        match opt_arg(demitter, *debug_flag, "codegen-units") {
+          Some(n) => {
+            match uint::from_str(n) {
+              Some(n) if n > 0u => {
+                codegen_units = n;
+                codegen_units_given = true;
+              }
+              _ => early_error(demitter, ~"-Z codegen-units requires \
+                                           a positive number")
+            }
+            loop;
+          }
+          None => ()
+        }
         let mut this_bit = 0u;
         for debug_map.each |pair| {
             let (name, _, bit) = /*bad*/copy *pair;
@@ -616,6 +807,19 @@ pub fn build_session_options(+binary: ~str,
             }
         } else { No }
     };
+    // Several codegen units are only joined back together by the linker
+    if codegen_units > 1u &&
+            (output_type != link::output_type_exe || jit) {
+        demitter(None, ~"ignoring -Z codegen-units: it is only supported \
+                         when linking", diagnostic::warning);
+        codegen_units = 1u;
+    }
+    // Incremental builds cache the code of each top-level module on its own
+    // unless told how many units to split the crate into
+    if incremental.is_some() && !codegen_units_given &&
+            output_type == link::output_type_exe && !jit {
+        codegen_units = uint::max_value;
+    }
     let target =
         match target_opt {
             None => host_triple(),
@@ -646,7 +850,10 @@ pub fn build_session_options(+binary: ~str,
           test: test,
           parse_only: parse_only,
           no_trans: no_trans,
-          debugging_opts: debugging_opts};
+          debugging_opts: debugging_opts,
+          profile_out: profile_out,
+          incremental: incremental,
+          codegen_units: codegen_units};
     return sopts;
 }
 
@@ -684,8 +891,10 @@ pub fn build_session_(sopts: @session::options,
                span_diagnostic: span_diagnostic_handler,
                filesearch: filesearch,
                mut building_library: false,
+               mut exported_macros: ~[],
                working_dir: os::getcwd(),
-               lint_settings: lint_settings})
+               lint_settings: lint_settings,
+               profile: profile::Profile()})
 }
 
 pub fn parse_pretty(sess: Session, &&name: ~str) -> pp_mode {
@@ -703,6 +912,16 @@ pub fn parse_pretty(sess: Session, &&name: ~str) -> pp_mode {
     }
 }
 
+pub fn parse_ls(sess: Session, &&name: ~str) -> ls_mode {
+    match name {
+      ~"text" => ls_text,
+      ~"json" => ls_json,
+      _ => {
+        sess.fatal(~"argument to `ls` must be one of `text` or `json`");
+      }
+    }
+}
+
 // rustc command line options
 pub fn optgroups() -> ~[getopts::groups::OptGroup] {
  ~[
@@ -718,7 +937,11 @@ pub fn optgroups() -> ~[getopts::groups::OptGroup] {
   optmulti(~"L", ~"",   ~"Add a directory to the library search path",
                               ~"PATH"),
   optflag(~"",  ~"lib", ~"Compile a library crate"),
-  optflag(~"",  ~"ls",  ~"List the symbols defined by a library crate"),
+  optflagopt(~"", ~"ls",
+                        ~"List the symbols defined by a library crate;
+                          valid formats are: text (attributes and
+                          dependencies) or json (every item with its
+                          type, visibility and impls)", ~"FORMAT"),
   optflag(~"",  ~"jit", ~"Execute using JIT (experimental)"),
   optflag(~"", ~"no-trans",
                         ~"Run all passes except translation; no output"),
@@ -849,10 +1072,90 @@ pub fn early_error(emitter: diagnostic::emitter, msg: ~str) -> ! {
     die!();
 }
 
-pub fn list_metadata(sess: Session, path: &Path, out: io::Writer) {
-    metadata::loader::list_file_metadata(
-        sess.parse_sess.interner,
-        session::sess_os_to_meta_os(sess.targ_cfg.os), path, out);
+pub fn list_metadata(sess: Session, path: &Path, mode: ls_mode,
+                     out: io::Writer) {
+    match mode {
+      ls_text => {
+        metadata::loader::list_file_metadata(
+            sess.parse_sess.interner,
+            session::sess_os_to_meta_os(sess.targ_cfg.os), path, out);
+      }
+      ls_json => list_metadata_json(sess, path, out)
+    }
+}
+
+/**
+ * Creates a type context for decoding the types of the crates in the
+ * session's crate store, when listing metadata rather than compiling.  There
+ * being no local crate, it is empty apart from what the crate store
+ * provides.
+ */
+fn mk_extern_ty_ctxt(sess: Session) -> ty::ctxt {
+    let crate = @codemap::spanned {
+        node: ast::crate_ {
+            module: ast::_mod { view_items: ~[], items: ~[] },
+            attrs: ~[],
+            config: ~[]
+        },
+        span: codemap::dummy_sp()
+    };
+    ty::mk_ctxt(sess, HashMap(), HashMap(), HashMap(), HashMap(),
+                HashMap(), middle::lang_items::LanguageItems::new(), crate)
+}
+
+fn list_metadata_json(sess: Session, path: &Path, out: io::Writer) {
+    let intr = sess.parse_sess.interner;
+    let cnum = match creader::read_crate_file(
+        sess.diagnostic(), sess.cstore, sess.filesearch,
+        session::sess_os_to_meta_os(sess.targ_cfg.os), intr, path) {
+      Some(cnum) => cnum,
+      None => {
+        sess.fatal(~"could not find metadata in " + path.to_str());
+      }
+    };
+
+    let tcx = mk_extern_ty_ctxt(sess);
+    let cdata = cstore::get_crate_data(sess.cstore, cnum);
+    let json = metadata::decoder::crate_metadata_to_json(intr, cdata, tcx);
+    write_sorted_json(out, &json, 0u);
+    out.write_char('\n');
+}
+
+// Like `json::to_pretty_writer`, but with the keys of every object in
+// sorted order, so that listings of two versions of a crate can be diffed.
+fn write_sorted_json(out: io::Writer, j: &std::json::Json, indent: uint) {
+    match *j {
+      std::json::List(ref elts) => {
+        if elts.is_empty() { out.write_str("[]"); return; }
+        out.write_str("[\n");
+        for elts.eachi |i, elt| {
+            if i != 0u { out.write_str(",\n"); }
+            out.write_str(str::repeat(" ", indent + 2u));
+            write_sorted_json(out, elt, indent + 2u);
+        }
+        out.write_char('\n');
+        out.write_str(str::repeat(" ", indent));
+        out.write_char(']');
+      }
+      std::json::Object(ref obj) => {
+        if obj.is_empty() { out.write_str("{}"); return; }
+        let mut keys = ~[];
+        for obj.each_key |k| { keys.push(copy *k); }
+        let keys = std::sort::merge_sort(keys, |a, b| *a <= *b);
+        out.write_str("{\n");
+        for keys.eachi |i, k| {
+            if i != 0u { out.write_str(",\n"); }
+            out.write_str(str::repeat(" ", indent + 2u));
+            out.write_str(std::json::to_str(&std::json::String(copy *k)));
+            out.write_str(": ");
+            write_sorted_json(out, obj.get(k), indent + 2u);
+        }
+        out.write_char('\n');
+        out.write_str(str::repeat(" ", indent));
+        out.write_char('}');
+      }
+      _ => out.write_str(std::json::to_str(j))
+    }
 }
 
 #[cfg(test)]
@@ -861,10 +1164,16 @@ pub mod test {
 
     use driver::driver::{build_configuration, build_session};
     use driver::driver::{build_session_options, optgroups, str_input};
+    use driver::driver::{compile_upto, cu_typeck, write_profile};
+    use driver::session;
 
+    use core::io;
+    use core::os;
     use core::vec;
     use std::getopts::groups::getopts;
     use std::getopts;
+    use std::json;
+    use std::tempfile;
     use syntax::attr;
     use syntax::diagnostic;
 
@@ -903,6 +1212,81 @@ pub mod test {
         let test_items = attr::find_meta_items_by_name(cfg, ~"test");
         assert (vec::len(test_items) == 1u);
     }
+
+    #[test]
+    pub fn test_profile_out_sets_profiling() {
+        let matches =
+            &match getopts(~[~"-Z", ~"profile-out=prof.json"], optgroups()) {
+              Ok(copy m) => m,
+              Err(copy f) => die!(~"test_profile_out_sets_profiling: " +
+                             getopts::fail_str(f))
+            };
+        let sessopts = build_session_options(
+            ~"rustc", matches, diagnostic::emit);
+        assert sessopts.profile_out == Some(Path("prof.json"));
+        assert sessopts.debugging_opts & session::time_passes == 0u;
+        let sess = build_session(sessopts, diagnostic::emit);
+        assert sess.profiling();
+    }
+
+    #[test]
+    pub fn test_write_profile() {
+        let dir = match tempfile::mkdtemp(&os::tmpdir(), "profile-out") {
+          Some(dir) => dir,
+          None => die!(~"test_write_profile: couldn't create a directory")
+        };
+        let path = dir.push("profile.json");
+        let flag = ~"profile-out=" + path.to_str();
+        let matches =
+            &match getopts(~[~"-Z", flag], optgroups()) {
+              Ok(copy m) => m,
+              Err(copy f) => die!(~"test_write_profile: " +
+                             getopts::fail_str(f))
+            };
+        let sessopts = build_session_options(
+            ~"rustc", matches, diagnostic::emit);
+        let sess = build_session(sessopts, diagnostic::emit);
+        let input = str_input(~"fn main() { let x = 1; }");
+        let cfg = build_configuration(sess, ~"rustc", copy input);
+        compile_upto(sess, cfg, copy input, cu_typeck, None);
+        write_profile(sess, input);
+
+        let src = io::read_whole_file_str(&path).get();
+        os::remove_file(&path);
+        os::remove_dir(&dir);
+        match json::from_str(src) {
+          Ok(json::Object(ref report)) => {
+            assert *report.get(&~"version") == json::Number(1f);
+            let names = match *report.get(&~"phases") {
+              json::List(ref phases) => do phases.map |p| {
+                match *p {
+                  json::Object(ref p) => copy *p.get(&~"name"),
+                  _ => die!(~"test_write_profile: phase is not an object")
+                }
+              },
+              _ => die!(~"test_write_profile: phases is not a list")
+            };
+            assert names.contains(&json::String(~"parsing"));
+            assert names.contains(&json::String(~"typechecking"));
+          }
+          _ => die!(~"test_write_profile: the profile is not an object")
+        }
+    }
+
+    #[test]
+    pub fn test_save_analysis_flag() {
+        let matches =
+            &match getopts(~[~"-Z", ~"save-analysis"], optgroups()) {
+              Ok(copy m) => m,
+              Err(copy f) => die!(~"test_save_analysis_flag: " +
+                             getopts::fail_str(f))
+            };
+        let sessopts = build_session_options(
+            ~"rustc", matches, diagnostic::emit);
+        let sess = build_session(sessopts, diagnostic::emit);
+        assert sess.save_analysis();
+        assert !sess.time_passes();
+    }
 }
 
 // Local Variables:
@@ -12,3 +12,4 @@ pub use syntax::diagnostic;
 
 pub mod driver;
 pub mod session;
+pub mod profile;
@@ -0,0 +1,193 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * Per-crate compilation profile.
+ *
+ * When `-Z time-passes` or `-Z profile-out=FILE` is given, every phase run
+ * by `driver::compile_upto` is recorded here together with its wall time and
+ * the memory high-water mark of the process at the end of the phase.  The
+ * passes that used to print their own statistics (trans, metadata, borrowck)
+ * also deposit their counters here, so that the whole thing can be written
+ * out as a single JSON report and compared across commits.
+ */
+
+use core::prelude::*;
+
+use core::hashmap::linear::LinearMap;
+use core::io;
+use core::result;
+use core::str;
+use core::uint;
+use std::json;
+use std::oldmap::HashMap;
+use std::oldmap;
+use std::time;
+
+/// Bump this whenever the layout of the JSON report changes.
+pub const profile_format_version: uint = 1;
+
+pub struct Phase {
+    name: ~str,
+    /// Wall-clock time spent in the phase, in seconds
+    secs: float,
+    /// Peak resident set size of the process after the phase, in bytes
+    mem_hwm: Option<uint>
+}
+
+pub struct Profile {
+    mut phases: ~[Phase],
+    counters: HashMap<~str, uint>
+}
+
+pub fn Profile() -> @Profile {
+    @Profile {
+        mut phases: ~[],
+        counters: oldmap::HashMap()
+    }
+}
+
+pub impl Profile {
+    /// Runs `thunk`, recording it as the phase `what`.
+    fn time<T>(what: &str, thunk: fn() -> T) -> T {
+        let start = time::precise_time_s();
+        let rv = thunk();
+        let end = time::precise_time_s();
+        self.phases.push(Phase {
+            name: str::from_slice(what),
+            secs: end - start,
+            mem_hwm: mem_high_water_mark()
+        });
+        move rv
+    }
+
+    /// Sets the counter `name`, e.g. `trans.n_fns`.
+    fn count(name: &str, value: uint) {
+        self.counters.insert(str::from_slice(name), value);
+    }
+
+    /// Adds `value` to the counter `name`.
+    fn add(name: &str, value: uint) {
+        let name = str::from_slice(name);
+        let old = self.counters.find(&name).get_or_default(0u);
+        self.counters.insert(name, old + value);
+    }
+
+    fn total_secs() -> float {
+        let mut total = 0f;
+        for self.phases.each |p| { total += p.secs; }
+        total
+    }
+
+    fn to_json(crate_name: &str) -> json::Json {
+        let mut phases = ~[];
+        for self.phases.each |p| {
+            let mut obj = ~LinearMap::new();
+            obj.insert(~"name", json::String(copy p.name));
+            obj.insert(~"seconds", json::Number(p.secs));
+            obj.insert(~"mem_hwm_bytes", match p.mem_hwm {
+                Some(b) => json::Number(b as float),
+                None => json::Null
+            });
+            phases.push(json::Object(move obj));
+        }
+
+        let mut counters = ~LinearMap::new();
+        for self.counters.each_ref |&k, &v| {
+            counters.insert(copy k, json::Number(v as float));
+        }
+
+        let mut report = ~LinearMap::new();
+        report.insert(~"version",
+                      json::Number(profile_format_version as float));
+        report.insert(~"crate", json::String(str::from_slice(crate_name)));
+        report.insert(~"total_seconds", json::Number(self.total_secs()));
+        report.insert(~"phases", json::List(move phases));
+        report.insert(~"counters", json::Object(move counters));
+        json::Object(move report)
+    }
+
+    fn write_report(path: &Path, crate_name: &str)
+                 -> result::Result<(), ~str> {
+        match io::file_writer(path, ~[io::Create, io::Truncate]) {
+            result::Ok(writer) => {
+                json::to_pretty_writer(writer, &self.to_json(crate_name));
+                writer.write_char('\n');
+                result::Ok(())
+            }
+            result::Err(move e) => result::Err(move e)
+        }
+    }
+}
+
+/// Returns the peak resident set size of the compiler, where the platform
+/// makes it available.
+#[cfg(target_os = "linux")]
+#[cfg(target_os = "android")]
+pub fn mem_high_water_mark() -> Option<uint> {
+    let status = match io::read_whole_file_str(&Path("/proc/self/status")) {
+        result::Ok(move s) => move s,
+        result::Err(_) => return None
+    };
+    for str::lines_each(status) |line| {
+        if str::starts_with(line, "VmHWM:") {
+            let words = str::words(str::slice(line, 6, line.len()));
+            if words.len() == 2u && words[1] == ~"kB" {
+                return uint::from_str(words[0]).map(|kb| *kb * 1024u);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(target_os = "win32")]
+#[cfg(target_os = "macos")]
+#[cfg(target_os = "freebsd")]
+pub fn mem_high_water_mark() -> Option<uint> { None }
+
+#[cfg(test)]
+mod test {
+    use core::prelude::*;
+
+    use driver::profile::Profile;
+
+    use std::json;
+
+    #[test]
+    fn test_phases_are_recorded_in_order() {
+        let p = Profile();
+        let a = p.time("parsing", || 1);
+        let b = p.time("expansion", || 2);
+        assert a + b == 3;
+        assert p.phases.len() == 2u;
+        assert p.phases[0].name == ~"parsing";
+        assert p.phases[1].name == ~"expansion";
+    }
+
+    #[test]
+    fn test_report_contains_counters() {
+        let p = Profile();
+        p.count("trans.n_fns", 10u);
+        match p.to_json("foo") {
+            json::Object(ref report) => {
+                assert report.find(&~"crate") ==
+                    Some(&json::String(~"foo"));
+                match report.find(&~"counters") {
+                    Some(&json::Object(ref counters)) => {
+                        assert counters.find(&~"trans.n_fns") ==
+                            Some(&json::Number(10f));
+                    }
+                    _ => die!(~"missing counters")
+                }
+            }
+            _ => die!(~"report is not an object")
+        }
+    }
+}
@@ -14,6 +14,7 @@ use back::link;
 use back::target_strs;
 use back;
 use driver;
+use driver::profile;
 use driver::session;
 use metadata::filesearch;
 use metadata;
@@ -75,6 +76,7 @@ pub const count_type_sizes: uint = 1 << 14;
 pub const meta_stats: uint = 1 << 15;
 pub const no_opt: uint = 1 << 16;
 pub const no_monomorphic_collapse: uint = 1 << 17;
+pub const save_analysis: uint = 1 << 18;
 
 pub fn debugging_opts_map() -> ~[(~str, ~str, uint)] {
     ~[(~"verbose", ~"in general, enable more debug printouts", verbose),
@@ -102,6 +104,8 @@ pub fn debugging_opts_map() -> ~[(~str, ~str, uint)] {
      (~"no-opt", ~"do not optimize, even if -O is passed", no_opt),
      (~"no-monomorphic-collapse", ~"do not collapse template instantiations",
       no_monomorphic_collapse),
+     (~"save-analysis", ~"write an index of the names used in the crate \
+                         next to the output", save_analysis),
     ]
 }
 
@@ -145,6 +149,13 @@ pub type options =
      parse_only: bool,
      no_trans: bool,
      debugging_opts: uint,
+     // Where to write the JSON compilation profile (-Z profile-out=FILE)
+     profile_out: Option<Path>,
+     // Where to cache translation results (-Z incremental=DIR)
+     incremental: Option<Path>,
+     // How many LLVM modules to split the crate into (-Z codegen-units=N);
+     // uint::max_value for one per top-level module
+     codegen_units: uint,
     };
 
 pub type crate_metadata = {name: ~str, data: ~[u8]};
@@ -159,8 +170,11 @@ pub type Session_ = {targ_cfg: @config,
                      span_diagnostic: diagnostic::span_handler,
                      filesearch: filesearch::FileSearch,
                      mut building_library: bool,
+                     // The sources of the macros marked #[macro_export]
+                     mut exported_macros: ~[~str],
                      working_dir: Path,
-                     lint_settings: lint::lint_settings};
+                     lint_settings: lint::lint_settings,
+                     profile: @profile::Profile};
 
 pub enum Session {
     Session_(@Session_)
@@ -243,6 +257,9 @@ pub impl Session {
     }
     fn verbose() -> bool { self.debugging_opt(verbose) }
     fn time_passes() -> bool { self.debugging_opt(time_passes) }
+    fn profiling() -> bool {
+        self.time_passes() || self.opts.profile_out.is_some()
+    }
     fn count_llvm_insns() -> bool { self.debugging_opt(count_llvm_insns) }
     fn count_type_sizes() -> bool { self.debugging_opt(count_type_sizes) }
     fn time_llvm_passes() -> bool { self.debugging_opt(time_llvm_passes) }
@@ -255,9 +272,15 @@ pub impl Session {
     fn borrowck_stats() -> bool { self.debugging_opt(borrowck_stats) }
     fn borrowck_note_pure() -> bool { self.debugging_opt(borrowck_note_pure) }
     fn borrowck_note_loan() -> bool { self.debugging_opt(borrowck_note_loan) }
+    fn codegen_units() -> uint {
+        // Static libraries are emitted as a single object file
+        if self.opts.static && self.building_library { 1u }
+        else { self.opts.codegen_units }
+    }
     fn no_monomorphic_collapse() -> bool {
         self.debugging_opt(no_monomorphic_collapse)
     }
+    fn save_analysis() -> bool { self.debugging_opt(save_analysis) }
 
     fn str_of(id: ast::ident) -> ~str {
         /*bad*/copy *self.parse_sess.interner.get(id)
@@ -291,7 +314,10 @@ pub fn basic_options() -> @options {
         test: false,
         parse_only: false,
         no_trans: false,
-        debugging_opts: 0u
+        debugging_opts: 0u,
+        profile_out: None,
+        incremental: None,
+        codegen_units: 1u
     }
 }
 
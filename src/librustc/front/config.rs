@@ -183,8 +183,8 @@ pub fn metas_in_cfg(cfg: ast::crate_cfg,
     let cfg_metas = attr::find_meta_items_by_name(metas, ~"cfg");
 
     // Pull the inner meta_items from the #[cfg(meta_item, ...)]  attributes,
-    // so we can match against them. This is the list of configurations for
-    // which the item is valid
+    // so we can match against them. This is the list of predicates, one of
+    // which must hold for the item to be valid
     let cfg_metas =
         vec::concat(
             vec::filter_map(cfg_metas, |i| attr::get_meta_item_list(i)));
@@ -193,7 +193,7 @@ pub fn metas_in_cfg(cfg: ast::crate_cfg,
     if !has_cfg_metas { return true; }
 
     for cfg_metas.each |cfg_mi| {
-        if attr::contains(cfg, *cfg_mi) { return true; }
+        if attr::cfg_matches(cfg, *cfg_mi) { return true; }
     }
 
     return false;
@@ -1149,6 +1149,9 @@ pub extern mod llvm {
     /** Returns the minimum alignment of a type when part of a call frame. */
     pub unsafe fn LLVMCallFrameAlignmentOfType(TD: TargetDataRef,
                                     Ty: TypeRef) -> c_uint;
+    /** Returns the offset in bytes of an element of a structure type. */
+    pub unsafe fn LLVMOffsetOfElement(TD: TargetDataRef, StructTy: TypeRef,
+                                      Element: c_uint) -> c_ulonglong;
 
     /** Disposes target data. */
     pub unsafe fn LLVMDisposeTargetData(TD: TargetDataRef);
@@ -1284,6 +1287,15 @@ pub extern mod llvm {
     /** Parses the bitcode in the given memory buffer. */
     pub unsafe fn LLVMRustParseBitcode(MemBuf: MemoryBufferRef) -> ModuleRef;
 
+    /** Parses the bitcode in the given memory buffer into a module owned
+        by the given context. */
+    pub unsafe fn LLVMRustParseBitcodeInContext(MemBuf: MemoryBufferRef,
+                                                C: ContextRef) -> ModuleRef;
+
+    /** Makes LLVM safe to use from several threads at once, as long as
+        each thread works in its own context. */
+    pub unsafe fn LLVMRustStartMultithreading() -> bool;
+
     /** Parses LLVM asm in the given file */
     pub unsafe fn LLVMRustParseAssemblyFile(Filename: *c_char) -> ModuleRef;
 
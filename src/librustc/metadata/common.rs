@@ -153,5 +153,14 @@ pub const tag_lang_items_item: uint = 0x73;
 pub const tag_lang_items_item_id: uint = 0x74;
 pub const tag_lang_items_item_node_id: uint = 0x75;
 
+// The declared visibility of an item or method, encoded like the
+// visibility of a field ('g', 'j' or 'N').
+pub const tag_items_data_item_visibility: uint = 0x76;
+
+// The sources of the macro_rules! definitions marked #[macro_export], as a
+// top-level directory of strings for other crates to load before expansion.
+pub const tag_exported_macros: uint = 0x77;
+pub const tag_exported_macro: uint = 0x78;
+
 pub type link_meta = {name: @str, vers: @str, extras_hash: @str};
 
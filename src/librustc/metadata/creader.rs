@@ -59,6 +59,144 @@ pub fn read_crates(diag: span_handler,
     warn_if_multiple_versions(e, diag, e.crate_cache.get());
 }
 
+/**
+ * Registers the library at `path` in `cstore`, together with every crate it
+ * depends on, as though it had been named by an `extern mod`.  Returns the
+ * crate number it was given, or `None` if the file holds no metadata.
+ */
+pub fn read_crate_file(diag: span_handler,
+                       cstore: cstore::CStore,
+                       filesearch: FileSearch,
+                       os: loader::os,
+                       intr: @ident_interner,
+                       path: &Path) -> Option<ast::crate_num> {
+    let e = @{diag: diag,
+              filesearch: filesearch,
+              cstore: cstore,
+              os: os,
+              static: false,
+              crate_cache: DVec(),
+              mut next_crate_num: 1,
+              intr: intr};
+    let cdata = match loader::get_metadata_section(os, path) {
+      Some(data) => data,
+      None => return None
+    };
+
+    let attrs = decoder::get_crate_attributes(cdata);
+    let linkage_metas = attr::find_linkage_metas(attrs);
+    let cname =
+        match attr::last_meta_item_value_str_by_name(linkage_metas,
+                                                     ~"name") {
+          Some(ref v) => (/*bad*/copy *v),
+          None => path.filestem().get_or_default(~"")
+        };
+
+    let cnum = e.next_crate_num;
+    e.crate_cache.push({cnum: cnum, span: dummy_sp(),
+                        hash: decoder::get_crate_hash(cdata),
+                        metas: @linkage_metas});
+    e.next_crate_num += 1;
+
+    let cnum_map = resolve_crate_deps(e, cdata);
+    cstore::set_crate_data(cstore, cnum,
+                           @{name: cname, data: cdata,
+                             cnum_map: cnum_map, cnum: cnum});
+    Some(cnum)
+}
+
+/**
+ * Returns the names of the crates that `crate` declares with `extern mod`
+ * but for which no matching library can be found.  `read_crates` treats a
+ * missing crate as a fatal error; tools that can do without cross-crate
+ * information use this to check first.  Only the crates named directly by
+ * `crate` are looked for, not their own dependencies.
+ */
+pub fn find_missing_crates(diag: span_handler,
+                           crate: ast::crate,
+                           filesearch: FileSearch,
+                           os: loader::os,
+                           static: bool,
+                           intr: @ident_interner) -> ~[~str] {
+    let missing = @mut ~[];
+    let v =
+        visit::mk_simple_visitor(@visit::SimpleVisitor {
+            visit_view_item: |i| {
+                match /*bad*/copy i.node {
+                  ast::view_item_use(ident, meta_items, _) => {
+                    let metas = metas_with_ident(copy *intr.get(ident),
+                                                 meta_items);
+                    let load_ctxt: loader::ctxt = {
+                        diag: diag,
+                        filesearch: filesearch,
+                        span: i.span,
+                        ident: ident,
+                        metas: metas,
+                        hash: ~"",
+                        os: os,
+                        static: static,
+                        intr: intr
+                    };
+                    if loader::find_library_crate(load_ctxt).is_none() {
+                        missing.push(copy *intr.get(ident));
+                    }
+                  }
+                  _ => ()
+                }
+            },
+            .. *visit::default_simple_visitor()});
+    visit::visit_crate(crate, (), v);
+    copy *missing
+}
+
+/**
+ * Returns the sources of the macros exported with `#[macro_export]` by the
+ * crates that `crate` declares with `extern mod`.  This runs before
+ * expansion, so it can't wait for `read_crates`, which loads the crates
+ * again once expansion has added any `extern mod`s of its own.  A crate
+ * that can't be found is left for `read_crates` to report.
+ */
+pub fn read_exported_macros(diag: span_handler,
+                            crate: ast::crate,
+                            filesearch: FileSearch,
+                            os: loader::os,
+                            static: bool,
+                            intr: @ident_interner) -> ~[~str] {
+    let macros = @mut ~[];
+    let v =
+        visit::mk_simple_visitor(@visit::SimpleVisitor {
+            visit_view_item: |i| {
+                match /*bad*/copy i.node {
+                  ast::view_item_use(ident, meta_items, _) => {
+                    let metas = metas_with_ident(copy *intr.get(ident),
+                                                 meta_items);
+                    let load_ctxt: loader::ctxt = {
+                        diag: diag,
+                        filesearch: filesearch,
+                        span: i.span,
+                        ident: ident,
+                        metas: metas,
+                        hash: ~"",
+                        os: os,
+                        static: static,
+                        intr: intr
+                    };
+                    match loader::find_library_crate(load_ctxt) {
+                      Some(ref cinfo) => {
+                        macros.push_all(
+                            decoder::get_exported_macros(cinfo.data));
+                      }
+                      None => ()
+                    }
+                  }
+                  _ => ()
+                }
+            },
+            .. *visit::default_simple_visitor()});
+    visit::visit_crate(crate, (), v);
+    copy *macros
+}
+
 type cache_entry = {
     cnum: int,
     span: span,
@@ -24,11 +24,13 @@ use metadata::cstore;
 use metadata::decoder;
 use metadata::tydecode::{parse_ty_data, parse_def_id, parse_bounds_data};
 use metadata::tydecode::{parse_ident};
+use middle::lang_items::LanguageItems;
 use middle::ty;
 use util::ppaux::ty_to_str;
 
 use core::cmp;
 use core::dvec;
+use core::hashmap::linear::LinearMap;
 use core::int;
 use core::io;
 use core::option;
@@ -36,6 +38,7 @@ use core::str;
 use core::vec;
 use std::ebml::reader;
 use std::ebml;
+use std::json;
 use std::oldmap::HashMap;
 use std::oldmap;
 use std::serialize::Decodable;
@@ -201,6 +204,16 @@ fn each_reexport(d: ebml::Doc, f: fn(ebml::Doc) -> bool) {
     }
 }
 
+fn item_visibility(d: ebml::Doc) -> Option<ast::visibility> {
+    do reader::maybe_get_doc(d, tag_items_data_item_visibility).map |doc| {
+        match reader::doc_as_u8(*doc) as char {
+          'g' => ast::public,
+          'j' => ast::private,
+          _   => ast::inherited
+        }
+    }
+}
+
 fn field_mutability(d: ebml::Doc) -> ast::struct_mutability {
     // Use maybe_get_doc in case it's a method
     option::map_default(
@@ -1050,6 +1063,22 @@ fn list_crate_deps(intr: @ident_interner, data: @~[u8], out: io::Writer) {
     out.write_str(~"\n");
 }
 
+/// The sources of the macros that the crate exports with #[macro_export]
+pub fn get_exported_macros(data: @~[u8]) -> ~[~str] {
+    let mut macros = ~[];
+    let cratedoc = reader::Doc(data);
+    // Crates built before macros could be exported have no such directory
+    match reader::maybe_get_doc(cratedoc, tag_exported_macros) {
+      Some(macros_doc) => {
+        for reader::tagged_docs(macros_doc, tag_exported_macro) |doc| {
+            macros.push(str::from_bytes(reader::doc_data(doc)));
+        }
+      }
+      None => ()
+    }
+    return macros;
+}
+
 pub fn get_crate_hash(data: @~[u8]) -> ~str {
     let cratedoc = reader::Doc(data);
     let hashdoc = reader::get_doc(cratedoc, tag_crate_hash);
@@ -1087,6 +1116,186 @@ pub fn list_crate_metadata(intr: @ident_interner, bytes: @~[u8],
     list_crate_deps(intr, bytes, out);
 }
 
+fn visibility_to_str(vis: ast::visibility) -> ~str {
+    match vis {
+      ast::public => ~"pub",
+      ast::private => ~"priv",
+      ast::inherited => ~"inherited"
+    }
+}
+
+fn path_doc_to_str(intr: @ident_interner, item: ebml::Doc) -> ~str {
+    match reader::maybe_get_doc(item, tag_path) {
+      Some(_) => ast_map::path_to_str_with_sep(item_path(intr, item),
+                                               ~"::", intr),
+      None => ~""
+    }
+}
+
+fn str_or_null(s: Option<~str>) -> json::Json {
+    match s {
+      Some(move s) => json::String(move s),
+      None => json::Null
+    }
+}
+
+fn item_to_json(intr: @ident_interner, cdata: cmd, tcx: ty::ctxt,
+                item: ebml::Doc) -> json::Json {
+    let fam = item_family(item);
+    let mut obj = ~LinearMap::new();
+
+    obj.insert(~"id", json::Number(item_def_id(item, cdata).node as float));
+    obj.insert(~"path", json::String(path_doc_to_str(intr, item)));
+    obj.insert(~"kind", json::String(item_family_to_str(fam)));
+    obj.insert(~"visibility",
+               str_or_null(item_visibility(item).map(
+                   |v| visibility_to_str(*v))));
+    obj.insert(~"type",
+               str_or_null(reader::maybe_get_doc(item,
+                                                 tag_items_data_item_type)
+                           .map(|_| ty_to_str(tcx, doc_type(item, tcx,
+                                                            cdata)))));
+    obj.insert(~"ty_params",
+               json::Number(item_ty_param_count(item) as float));
+    obj.insert(~"symbol",
+               str_or_null(reader::maybe_get_doc(item,
+                                                 tag_items_data_item_symbol)
+                           .map(|_| item_symbol(item))));
+    // Generic and #[inline] fns and methods carry their AST so that other
+    // crates can instantiate them, which makes their bodies part of the
+    // ABI.  So do enums, struct destructors and intrinsics, so this only
+    // says whether the entry has an AST, not that it can be inlined.
+    obj.insert(~"has_ast", json::Boolean(
+        reader::maybe_get_doc(item, tag_ast as uint).is_some()));
+
+    match fam {
+      Impl => {
+        let traits = do item_impl_traits(item, tcx, cdata).map |t| {
+            json::String(ty_to_str(tcx, *t))
+        };
+        obj.insert(~"traits", json::List(move traits));
+
+        let mut methods = ~[];
+        for reader::tagged_docs(item, tag_item_impl_method) |doc| {
+            let did = reader::with_doc_data(doc, |d| parse_def_id(d));
+            let mth = lookup_item(did.node, cdata.data);
+            methods.push(json::String(copy *intr.get(item_name(intr, mth))));
+        }
+        obj.insert(~"methods", json::List(move methods));
+      }
+      Trait => {
+        let mut methods = ~[];
+        for reader::tagged_docs(item, tag_item_trait_method) |mth| {
+            let mut m = ~LinearMap::new();
+            m.insert(~"name",
+                     json::String(copy *intr.get(item_name(intr, mth))));
+            m.insert(~"type",
+                     json::String(ty_to_str(tcx, doc_type(mth, tcx, cdata))));
+            m.insert(~"provided",
+                     json::Boolean(item_method_sort(mth) == 'p'));
+            methods.push(json::Object(move m));
+        }
+        obj.insert(~"methods", json::List(move methods));
+      }
+      Struct => {
+        let mut fields = ~[];
+        for reader::tagged_docs(item, tag_item_field) |f| {
+            let fam = item_family(f);
+            if fam != PublicField && fam != PrivateField &&
+                    fam != InheritedField {
+                loop;
+            }
+            let mut field = ~LinearMap::new();
+            field.insert(~"name",
+                         json::String(copy *intr.get(item_name(intr, f))));
+            field.insert(~"visibility", json::String(
+                visibility_to_str(family_to_visibility(fam))));
+            fields.push(json::Object(move field));
+        }
+        obj.insert(~"fields", json::List(move fields));
+      }
+      Variant => {
+        obj.insert(~"discriminant", match variant_disr_val(item) {
+            Some(v) => json::Number(v as float),
+            None => json::Null
+        });
+      }
+      Mod => {
+        let mut reexports = ~[];
+        for each_reexport(item) |doc| {
+            let did_doc =
+                reader::get_doc(doc, tag_items_data_item_reexport_def_id);
+            let did = reader::with_doc_data(did_doc, |d| parse_def_id(d));
+            let name_doc =
+                reader::get_doc(doc, tag_items_data_item_reexport_name);
+            let mut r = ~LinearMap::new();
+            r.insert(~"name", json::String(reader::doc_as_str(name_doc)));
+            r.insert(~"target", json::String(
+                ty::item_path_str(tcx, translate_def_id(cdata, did))));
+            reexports.push(json::Object(move r));
+        }
+        obj.insert(~"reexports", json::List(move reexports));
+      }
+      _ => ()
+    }
+
+    json::Object(move obj)
+}
+
+/**
+ * Describes the whole of a crate's metadata as JSON: its attributes,
+ * dependencies and language items, and for every item its path, kind,
+ * visibility, type signature and whether its AST is encoded in the
+ * metadata (`has_ast`).  This is what `rustc --ls=json` prints.
+ *
+ * Types are printed through `tcx`, so `cdata` and every crate it depends
+ * on must have been registered in the crate store of `tcx`'s session.
+ */
+pub fn crate_metadata_to_json(intr: @ident_interner, cdata: cmd,
+                              tcx: ty::ctxt) -> json::Json {
+    let data = cdata.data;
+    let root = reader::Doc(data);
+    let items_data = reader::get_doc(reader::get_doc(root, tag_items),
+                                     tag_items_data);
+
+    let mut items = ~[];
+    for reader::tagged_docs(items_data, tag_items_data_item) |item_doc| {
+        items.push(item_to_json(intr, cdata, tcx, item_doc));
+    }
+
+    let mut lang_items = ~[];
+    for each_lang_item(cdata) |node_id, id| {
+        let mut obj = ~LinearMap::new();
+        obj.insert(~"name", json::String(
+            str::from_slice(LanguageItems::item_name(id))));
+        obj.insert(~"path", json::String(
+            path_doc_to_str(intr, lookup_item(node_id, data))));
+        lang_items.push(json::Object(move obj));
+    }
+
+    let attrs = do get_crate_attributes(data).map |attr| {
+        json::String(pprust::attribute_to_str(*attr, intr))
+    };
+
+    let deps = do get_crate_deps(intr, data).map |dep| {
+        let mut obj = ~LinearMap::new();
+        obj.insert(~"name", json::String(copy *intr.get(dep.name)));
+        obj.insert(~"vers", json::String(copy dep.vers));
+        obj.insert(~"hash", json::String(copy dep.hash));
+        json::Object(move obj)
+    };
+
+    let mut obj = ~LinearMap::new();
+    obj.insert(~"name", json::String(copy cdata.name));
+    obj.insert(~"vers", json::String(get_crate_vers(data)));
+    obj.insert(~"hash", json::String(get_crate_hash(data)));
+    obj.insert(~"attributes", json::List(move attrs));
+    obj.insert(~"deps", json::List(move deps));
+    obj.insert(~"items", json::List(move items));
+    obj.insert(~"lang_items", json::List(move lang_items));
+    json::Object(move obj)
+}
+
 // Translates a def_id from an external crate to a def_id for the current
 // compilation environment. We use this when trying to load types from
 // external crates - if those types further refer to types in other crates
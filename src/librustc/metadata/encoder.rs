@@ -76,6 +76,7 @@ type stats = {
     mut attr_bytes: uint,
     mut dep_bytes: uint,
     mut lang_item_bytes: uint,
+    mut macro_bytes: uint,
     mut item_bytes: uint,
     mut index_bytes: uint,
     mut zero_bytes: uint,
@@ -382,6 +383,16 @@ fn encode_visibility(ebml_w: writer::Encoder, visibility: visibility) {
     });
 }
 
+fn encode_item_visibility(ebml_w: writer::Encoder, visibility: visibility) {
+    ebml_w.start_tag(tag_items_data_item_visibility);
+    ebml_w.writer.write(&[match visibility {
+        public => 'g',
+        private => 'j',
+        inherited => 'N'
+    } as u8]);
+    ebml_w.end_tag();
+}
+
 fn encode_self_type(ebml_w: writer::Encoder, self_type: ast::self_ty_) {
     ebml_w.start_tag(tag_item_trait_method_self_ty);
 
@@ -502,6 +513,7 @@ fn encode_info_for_method(ecx: @encode_ctxt,
     encode_type_param_bounds(ebml_w, ecx, all_tps);
     encode_type(ecx, ebml_w, node_id_to_type(ecx.tcx, m.id));
     encode_name(ecx, ebml_w, m.ident);
+    encode_item_visibility(ebml_w, m.vis);
     encode_path(ecx, ebml_w, impl_path, ast_map::path_name(m.ident));
     encode_self_type(ebml_w, m.self_ty.node);
     if len > 0u || should_inline {
@@ -568,6 +580,7 @@ fn encode_info_for_item(ecx: @encode_ctxt, ebml_w: writer::Encoder,
         ebml_w.start_tag(tag_items_data_item);
         encode_def_id(ebml_w, local_def(item.id));
         encode_family(ebml_w, 'c');
+        encode_item_visibility(ebml_w, item.vis);
         encode_type(ecx, ebml_w, node_id_to_type(tcx, item.id));
         encode_symbol(ecx, ebml_w, item.id);
         encode_path(ecx, ebml_w, path, ast_map::path_name(item.ident));
@@ -578,6 +591,7 @@ fn encode_info_for_item(ecx: @encode_ctxt, ebml_w: writer::Encoder,
         ebml_w.start_tag(tag_items_data_item);
         encode_def_id(ebml_w, local_def(item.id));
         encode_family(ebml_w, purity_fn_family(purity));
+        encode_item_visibility(ebml_w, item.vis);
         let tps_len = tps.len();
         encode_type_param_bounds(ebml_w, ecx, tps);
         encode_type(ecx, ebml_w, node_id_to_type(tcx, item.id));
@@ -608,6 +622,7 @@ fn encode_info_for_item(ecx: @encode_ctxt, ebml_w: writer::Encoder,
         ebml_w.start_tag(tag_items_data_item);
         encode_def_id(ebml_w, local_def(item.id));
         encode_family(ebml_w, 'y');
+        encode_item_visibility(ebml_w, item.vis);
         encode_type_param_bounds(ebml_w, ecx, tps);
         encode_type(ecx, ebml_w, node_id_to_type(tcx, item.id));
         encode_name(ecx, ebml_w, item.ident);
@@ -620,6 +635,7 @@ fn encode_info_for_item(ecx: @encode_ctxt, ebml_w: writer::Encoder,
         do ebml_w.wr_tag(tag_items_data_item) {
             encode_def_id(ebml_w, local_def(item.id));
             encode_family(ebml_w, 't');
+            encode_item_visibility(ebml_w, item.vis);
             encode_type_param_bounds(ebml_w, ecx, *tps);
             encode_type(ecx, ebml_w, node_id_to_type(tcx, item.id));
             encode_name(ecx, ebml_w, item.ident);
@@ -672,6 +688,7 @@ fn encode_info_for_item(ecx: @encode_ctxt, ebml_w: writer::Encoder,
         ebml_w.start_tag(tag_items_data_item);
         encode_def_id(ebml_w, local_def(item.id));
         encode_family(ebml_w, 'S');
+        encode_item_visibility(ebml_w, item.vis);
         encode_type_param_bounds(ebml_w, ecx, tps);
         encode_type(ecx, ebml_w, node_id_to_type(tcx, item.id));
         encode_name(ecx, ebml_w, item.ident);
@@ -754,6 +771,7 @@ fn encode_info_for_item(ecx: @encode_ctxt, ebml_w: writer::Encoder,
         ebml_w.start_tag(tag_items_data_item);
         encode_def_id(ebml_w, local_def(item.id));
         encode_family(ebml_w, 'I');
+        encode_item_visibility(ebml_w, item.vis);
         encode_region_param(ecx, ebml_w, item);
         encode_type_param_bounds(ebml_w, ecx, *tps);
         encode_type(ecx, ebml_w, node_id_to_type(tcx, item.id));
@@ -845,6 +863,7 @@ fn encode_info_for_foreign_item(ecx: @encode_ctxt,
     index.push({val: nitem.id, pos: ebml_w.writer.tell()});
 
     ebml_w.start_tag(tag_items_data_item);
+    encode_item_visibility(ebml_w, nitem.vis);
     match /*bad*/copy nitem.node {
       foreign_item_fn(_, purity, tps) => {
         encode_def_id(ebml_w, local_def(nitem.id));
@@ -1138,6 +1157,16 @@ fn encode_lang_items(ecx: @encode_ctxt, ebml_w: writer::Encoder) {
     ebml_w.end_tag();   // tag_lang_items
 }
 
+fn encode_exported_macros(ecx: @encode_ctxt, ebml_w: writer::Encoder) {
+    ebml_w.start_tag(tag_exported_macros);
+    for ecx.tcx.sess.exported_macros.each |source| {
+        ebml_w.start_tag(tag_exported_macro);
+        ebml_w.writer.write(str::to_bytes(*source));
+        ebml_w.end_tag();
+    }
+    ebml_w.end_tag();
+}
+
 fn encode_crate_dep(ecx: @encode_ctxt, ebml_w: writer::Encoder,
                     dep: decoder::crate_dep) {
     ebml_w.start_tag(tag_crate_dep);
@@ -1173,6 +1202,7 @@ pub fn encode_metadata(parms: encode_parms, crate: &crate) -> ~[u8] {
          mut attr_bytes: 0,
          mut dep_bytes: 0,
          mut lang_item_bytes: 0,
+         mut macro_bytes: 0,
          mut item_bytes: 0,
          mut index_bytes: 0,
          mut zero_bytes: 0,
@@ -1210,6 +1240,11 @@ pub fn encode_metadata(parms: encode_parms, crate: &crate) -> ~[u8] {
     encode_lang_items(ecx, ebml_w);
     ecx.stats.lang_item_bytes = wr.pos - i;
 
+    // Encode the exported macros.
+    i = wr.pos;
+    encode_exported_macros(ecx, ebml_w);
+    ecx.stats.macro_bytes = wr.pos - i;
+
     // Encode and index the items.
     ebml_w.start_tag(tag_items);
     i = wr.pos;
@@ -1240,12 +1275,25 @@ pub fn encode_metadata(parms: encode_parms, crate: &crate) -> ~[u8] {
         io::println(fmt!(" attribute bytes: %u", ecx.stats.attr_bytes));
         io::println(fmt!("       dep bytes: %u", ecx.stats.dep_bytes));
         io::println(fmt!(" lang item bytes: %u", ecx.stats.lang_item_bytes));
+        io::println(fmt!("     macro bytes: %u", ecx.stats.macro_bytes));
         io::println(fmt!("      item bytes: %u", ecx.stats.item_bytes));
         io::println(fmt!("     index bytes: %u", ecx.stats.index_bytes));
         io::println(fmt!("      zero bytes: %u", ecx.stats.zero_bytes));
         io::println(fmt!("     total bytes: %u", ecx.stats.total_bytes));
     }
 
+    if parms.tcx.sess.profiling() {
+        let p = parms.tcx.sess.profile;
+        p.count("meta.inline_bytes", ecx.stats.inline_bytes);
+        p.count("meta.attr_bytes", ecx.stats.attr_bytes);
+        p.count("meta.dep_bytes", ecx.stats.dep_bytes);
+        p.count("meta.lang_item_bytes", ecx.stats.lang_item_bytes);
+        p.count("meta.macro_bytes", ecx.stats.macro_bytes);
+        p.count("meta.item_bytes", ecx.stats.item_bytes);
+        p.count("meta.index_bytes", ecx.stats.index_bytes);
+        p.count("meta.total_bytes", ecx.stats.total_bytes);
+    }
+
     // Pad this, since something (LLVM, presumably) is cutting off the
     // remaining % 4 bytes.
     wr.write(&[0u8, 0u8, 0u8, 0u8]);
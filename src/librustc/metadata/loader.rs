@@ -66,7 +66,8 @@ pub fn load_library_crate(cx: ctxt) -> {ident: ~str, data: @~[u8]} {
     }
 }
 
-fn find_library_crate(cx: ctxt) -> Option<{ident: ~str, data: @~[u8]}> {
+pub fn find_library_crate(cx: ctxt)
+    -> Option<{ident: ~str, data: @~[u8]}> {
     attr::require_unique_names(cx.diag, cx.metas);
     find_library_crate_aux(cx, libname(cx), cx.filesearch)
 }
@@ -191,8 +192,8 @@ pub fn metadata_matches(extern_metas: ~[@ast::meta_item],
     return true;
 }
 
-fn get_metadata_section(os: os,
-                        filename: &Path) -> Option<@~[u8]> {
+pub fn get_metadata_section(os: os,
+                            filename: &Path) -> Option<@~[u8]> {
     unsafe {
         let mb = str::as_c_str(filename.to_str(), |buf| {
             llvm::LLVMRustCreateMemoryBufferWithContentsOfFile(buf)
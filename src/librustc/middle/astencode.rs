@@ -510,6 +510,7 @@ impl freevar_entry: tr {
     fn tr(xcx: extended_decode_ctxt) -> freevar_entry {
         freevar_entry {
             def: self.def.tr(xcx),
+            ident: self.ident,
             span: self.span.tr(xcx),
         }
     }
@@ -533,6 +534,7 @@ impl moves::CaptureVar : tr {
     fn tr(xcx: extended_decode_ctxt) -> moves::CaptureVar {
         moves::CaptureVar {
             def: self.def.tr(xcx),
+            ident: self.ident,
             span: self.span.tr(xcx),
             mode: self.mode
         }
@@ -293,6 +293,15 @@ pub fn check_crate(
                          make_stat(bccx, bccx.req_pure_paths)));
     }
 
+    if tcx.sess.profiling() {
+        let p = tcx.sess.profile;
+        p.count("borrowck.guaranteed_paths", bccx.guaranteed_paths);
+        p.count("borrowck.loaned_paths_same", bccx.loaned_paths_same);
+        p.count("borrowck.loaned_paths_imm", bccx.loaned_paths_imm);
+        p.count("borrowck.stable_paths", bccx.stable_paths);
+        p.count("borrowck.req_pure_paths", bccx.req_pure_paths);
+    }
+
     return (bccx.root_map, bccx.mutbl_map, bccx.write_guard_map);
 
     fn make_stat(bccx: &BorrowckCtxt, stat: uint) -> ~str {
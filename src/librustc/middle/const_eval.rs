@@ -86,6 +86,7 @@ pub fn classify(e: @expr,
               ast::expr_lit(lit) => {
                 match lit.node {
                   ast::lit_str(*) |
+                  ast::lit_binary(*) |
                   ast::lit_float(*) => general_const,
                   _ => integral_const
                 }
@@ -227,7 +228,8 @@ pub enum const_val {
     const_int(i64),
     const_uint(u64),
     const_str(~str),
-    const_bool(bool)
+    const_bool(bool),
+    const_binary(~[u8])
 }
 
 pub fn eval_const_expr(tcx: middle::ty::ctxt, e: @expr) -> const_val {
@@ -249,6 +251,7 @@ pub fn eval_const_expr_partial(tcx: middle::ty::ctxt, e: @expr)
           Ok(const_uint(i)) => Ok(const_uint(-i)),
           Ok(const_str(_)) => Err(~"Negate on string"),
           Ok(const_bool(_)) => Err(~"Negate on boolean"),
+          Ok(const_binary(_)) => Err(~"Negate on byte string"),
           ref err => (/*bad*/copy *err)
         }
       }
@@ -408,7 +411,8 @@ pub fn lit_to_const(lit: @lit) -> const_val {
       lit_float_unsuffixed(n) =>
         const_float(float::from_str(*n).get() as f64),
       lit_nil => const_int(0i64),
-      lit_bool(b) => const_bool(b)
+      lit_bool(b) => const_bool(b),
+      lit_binary(bytes) => const_binary(/*bad*/copy *bytes)
     }
 }
 
@@ -459,6 +463,15 @@ pub fn compare_const_vals(a: const_val, b: const_val) -> int {
             1
         }
     }
+    (&const_binary(ref a), &const_binary(ref b)) => {
+        if (*a) == (*b) {
+            0
+        } else if (*a) < (*b) {
+            -1
+        } else {
+            1
+        }
+    }
     _ => die!(~"compare_const_vals: ill-typed comparison")
   }
 }
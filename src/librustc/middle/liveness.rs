@@ -614,7 +614,7 @@ fn visit_expr(expr: @expr, &&self: @IrMaps, vt: vt<@IrMaps>) {
       expr_break(_) | expr_again(_) | expr_lit(_) | expr_ret(*) |
       expr_block(*) | expr_assign(*) |
       expr_swap(*) | expr_assign_op(*) | expr_mac(*) | expr_struct(*) |
-      expr_repeat(*) | expr_paren(*) => {
+      expr_repeat(*) | expr_paren(*) | expr_err => {
           visit::visit_expr(expr, self, vt);
       }
     }
@@ -1347,6 +1347,10 @@ impl Liveness {
           expr_mac(*) => {
             self.tcx.sess.span_bug(expr.span, ~"unexpanded macro");
           }
+
+          expr_err => {
+            self.tcx.sess.span_bug(expr.span, ~"unreported syntax error");
+          }
         }
     }
 
@@ -1611,7 +1615,7 @@ fn check_expr(expr: @expr, &&self: @Liveness, vt: vt<@Liveness>) {
       expr_cast(*) | expr_unary(*) |
       expr_ret(*) | expr_break(*) | expr_again(*) | expr_lit(_) |
       expr_block(*) | expr_swap(*) | expr_mac(*) | expr_addr_of(*) |
-      expr_struct(*) | expr_repeat(*) | expr_paren(*) => {
+      expr_struct(*) | expr_repeat(*) | expr_paren(*) | expr_err => {
         visit::visit_expr(expr, self, vt);
       }
     }
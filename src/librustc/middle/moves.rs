@@ -238,6 +238,7 @@ pub enum CaptureMode {
 #[auto_decode]
 pub struct CaptureVar {
     def: def,         // Variable being accessed free
+    ident: ident,     // Name the variable is accessed by
     span: span,       // Location of an access to this variable
     mode: CaptureMode // How variable is being accessed
 }
@@ -658,6 +659,12 @@ impl VisitContext {
                     expr.span,
                     ~"macro expression remains after expansion");
             }
+
+            expr_err => {
+                self.tcx.sess.span_bug(
+                    expr.span,
+                    ~"syntax error remains after parsing");
+            }
         }
     }
 
@@ -820,7 +827,8 @@ impl VisitContext {
             // &fn() captures everything by ref
             at_vec::from_fn(freevars.len(), |i| {
                 let fvar = &freevars[i];
-                CaptureVar {def: fvar.def, span: fvar.span, mode: CapRef}
+                CaptureVar {def: fvar.def, ident: fvar.ident, span: fvar.span,
+                            mode: CapRef}
             })
         } else {
             // @fn() and ~fn() capture by copy or by move depending on type
@@ -835,7 +843,8 @@ impl VisitContext {
                 } else {
                     CapCopy
                 };
-                CaptureVar {def: fvar.def, span: fvar.span, mode:mode}
+                CaptureVar {def: fvar.def, ident: fvar.ident, span: fvar.span,
+                            mode: mode}
             })
         }
     }
@@ -4428,6 +4428,24 @@ pub impl Resolver {
 
     fn resolve_bare_identifier_pattern(name: ident)
                                     -> BareIdentifierPatternResolution {
+        match self.resolve_bare_identifier_pattern_(name) {
+            BareIdentifierPatternUnresolved => {
+                // An identifier marked by macro expansion names the same
+                // variants and constants as the one that it was made from.
+                let unmarked = self.intr.unmark(name);
+                if unmarked == name {
+                    return BareIdentifierPatternUnresolved;
+                }
+                return self.resolve_bare_identifier_pattern_(unmarked);
+            }
+            resolution => {
+                return resolution;
+            }
+        }
+    }
+
+    fn resolve_bare_identifier_pattern_(name: ident)
+                                     -> BareIdentifierPatternResolution {
         match self.resolve_item_in_lexical_scope(self.current_module,
                                                  name,
                                                  ValueNS,
@@ -4515,8 +4533,22 @@ pub impl Resolver {
             }
         }
 
-        return self.resolve_item_by_identifier_in_lexical_scope(identifier,
-                                                                namespace);
+        match self.resolve_item_by_identifier_in_lexical_scope(identifier,
+                                                               namespace) {
+            Some(def) => {
+                return Some(def);
+            }
+            None => {
+                // Items are found by the original name of an identifier
+                // that macro expansion has marked.
+                let unmarked = self.intr.unmark(identifier);
+                if unmarked == identifier {
+                    return None;
+                }
+                return self.resolve_item_by_identifier_in_lexical_scope(
+                    unmarked, namespace);
+            }
+        }
     }
 
     // XXX: Merge me with resolve_name_in_module?
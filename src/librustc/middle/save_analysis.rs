@@ -0,0 +1,439 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * Saves an index of the names used in a crate, for editors and other tools
+ * (-Z save-analysis).
+ *
+ * The crate is walked once typeck is done, and every path, field, method
+ * call and binding is written as a row of CSV:
+ *
+ *     role,kind,name,file,line,col,end_line,end_col,crate,node,path,type
+ *
+ * `role` is `ref` for a use of a name and `def` for the place an item,
+ * method, field, variant or local is defined in this crate. `crate` and
+ * `node` are the `def_id` that resolve (or typeck, for fields and methods)
+ * settled on, so a `ref` can be joined to its `def`. `path` is the full
+ * path of an item, read from the crate's metadata when it lives in another
+ * crate, and `type` is the type of the expression or item, where there is
+ * one. Columns are 1-based lines and 0-based character offsets.
+ */
+
+use core::prelude::*;
+
+use middle::ty;
+use middle::typeck::{method_map, method_origin, method_param, method_self};
+use middle::typeck::{method_static, method_super, method_trait};
+use util::ppaux::ty_to_str;
+
+use core::dvec::DVec;
+use core::io;
+use core::io::WriterUtil;
+use core::path::Path;
+use core::result;
+use core::str;
+use core::vec;
+use std::oldsmallintmap;
+use syntax::ast;
+use syntax::ast_util::{def_id_of_def, local_def};
+use syntax::codemap::span;
+use syntax::visit;
+
+struct Ctxt {
+    tcx: ty::ctxt,
+    method_map: method_map,
+    rows: DVec<~str>
+}
+
+/// Walks `crate` and writes the index of its names to `out`
+pub fn save_crate(tcx: ty::ctxt, method_map: method_map, crate: @ast::crate,
+                  out: &Path) {
+    let cx = @Ctxt {tcx: tcx, method_map: method_map, rows: DVec()};
+    let visitor = visit::mk_simple_visitor(@visit::SimpleVisitor {
+        visit_item: |i| visit_item(cx, i),
+        visit_fn: |fk, _d, _b, sp, id| visit_fn(cx, fk, sp, id),
+        visit_ty_method: |m| {
+            def_row(cx, ~"method", m.ident, m.span, local_def(m.id))
+        },
+        visit_expr: |e| visit_expr(cx, e),
+        visit_pat: |p| visit_pat(cx, p),
+        visit_ty: |t| visit_ty(cx, t),
+        .. *visit::default_simple_visitor()
+    });
+    visit::visit_crate(*crate, (), visitor);
+
+    match io::file_writer(out, ~[io::Create, io::Truncate]) {
+      result::Ok(wr) => {
+        wr.write_line("role,kind,name,file,line,col,end_line,end_col,\
+                       crate,node,path,type");
+        for cx.rows.each |row| { wr.write_line(*row); }
+      }
+      result::Err(move e) => {
+        tcx.sess.err(fmt!("failed to write analysis to %s: %s",
+                          out.to_str(), e));
+      }
+    }
+}
+
+fn visit_item(cx: @Ctxt, item: @ast::item) {
+    let kind = match item.node {
+      ast::item_const(*) => ~"const",
+      ast::item_fn(*) => ~"fn",
+      ast::item_mod(*) => ~"mod",
+      ast::item_foreign_mod(*) => ~"foreign_mod",
+      ast::item_ty(*) | ast::item_enum(*) | ast::item_trait(*) => ~"type",
+      ast::item_struct(*) => ~"struct",
+      // Impls are anonymous, and their methods are seen by visit_fn
+      ast::item_impl(*) | ast::item_mac(*) => return
+    };
+    def_row(cx, kind, item.ident, item.span, local_def(item.id));
+
+    match item.node {
+      ast::item_enum(ref enum_def, _) => {
+        for enum_def.variants.each |v| {
+            def_row(cx, ~"variant", v.node.name, v.span,
+                    local_def(v.node.id));
+        }
+      }
+      ast::item_struct(struct_def, _) => {
+        let path = ty::item_path_str(cx.tcx, local_def(item.id));
+        for struct_def.fields.each |f| {
+            match f.node.kind {
+              ast::named_field(ident, _, _) => {
+                add_row(cx, "def", "field", ident, f.span,
+                        local_def(f.node.id),
+                        path + ~"::" + cx.tcx.sess.str_of(ident),
+                        node_ty(cx, f.node.id));
+              }
+              ast::unnamed_field => ()
+            }
+        }
+      }
+      _ => ()
+    }
+}
+
+fn visit_fn(cx: @Ctxt, fk: visit::fn_kind, sp: span, id: ast::node_id) {
+    match fk {
+      visit::fk_method(ident, _, _) => {
+        def_row(cx, ~"method", ident, sp, local_def(id));
+      }
+      _ => ()
+    }
+}
+
+fn visit_expr(cx: @Ctxt, e: @ast::expr) {
+    match e.node {
+      ast::expr_path(path) => {
+        path_row(cx, path, e.id, node_ty(cx, e.id));
+      }
+      ast::expr_struct(path, ref fields, _) => {
+        path_row(cx, path, e.id, node_ty(cx, e.id));
+        match ty::get(ty::expr_ty(cx.tcx, e)).sty {
+          ty::ty_struct(did, _) => {
+            for fields.each |f| {
+                field_row(cx, did, f.node.ident, f.span,
+                          node_ty(cx, f.node.expr.id));
+            }
+          }
+          _ => ()
+        }
+      }
+      ast::expr_field(base, ident, _) => {
+        if cx.method_map.contains_key(&e.id) {
+            method_row(cx, e, ident);
+        } else {
+            let base_ty = ty::type_autoderef(cx.tcx,
+                                             ty::expr_ty(cx.tcx, base));
+            match ty::get(base_ty).sty {
+              ty::ty_struct(did, _) => {
+                field_row(cx, did, ident, e.span, node_ty(cx, e.id));
+              }
+              _ => ()
+            }
+        }
+      }
+      ast::expr_method_call(_, ident, _, _, _) => method_row(cx, e, ident),
+      _ => ()
+    }
+}
+
+fn visit_pat(cx: @Ctxt, p: @ast::pat) {
+    match p.node {
+      ast::pat_ident(_, path, _) => {
+        match cx.tcx.def_map.find(&p.id) {
+          // A binding is the definition of a local, not a use of one
+          Some(def) if def_id_of_def(def) == local_def(p.id) => {
+            add_row(cx, "def", def_kind(def), vec::last(path.idents),
+                    p.span, local_def(p.id),
+                    cx.tcx.sess.str_of(vec::last(path.idents)),
+                    node_ty(cx, p.id));
+          }
+          _ => path_row(cx, path, p.id, node_ty(cx, p.id))
+        }
+      }
+      ast::pat_enum(path, _) | ast::pat_struct(path, _, _) => {
+        path_row(cx, path, p.id, node_ty(cx, p.id));
+      }
+      _ => ()
+    }
+}
+
+fn visit_ty(cx: @Ctxt, t: @ast::Ty) {
+    match t.node {
+      ast::ty_path(path, id) => path_row(cx, path, id, None),
+      _ => ()
+    }
+}
+
+// A use of whatever resolve took `path`, written at node `id`, to name
+fn path_row(cx: @Ctxt, path: @ast::path, id: ast::node_id,
+            ty: Option<ty::t>) {
+    let def = match cx.tcx.def_map.find(&id) {
+      Some(ast::def_prim_ty(_)) | None => return,
+      Some(def) => def
+    };
+    let name = vec::last(path.idents);
+    let did = def_id_of_def(def);
+    let path_str = if is_item(def) {
+        ty::item_path_str(cx.tcx, did)
+    } else {
+        cx.tcx.sess.str_of(name)
+    };
+    add_row(cx, "ref", def_kind(def), name, path.span, did, path_str, ty);
+}
+
+fn field_row(cx: @Ctxt, struct_id: ast::def_id, ident: ast::ident,
+             sp: span, ty: Option<ty::t>) {
+    for ty::lookup_struct_fields(cx.tcx, struct_id).each |f| {
+        if f.ident == ident {
+            let path = ty::item_path_str(cx.tcx, struct_id) + ~"::" +
+                cx.tcx.sess.str_of(ident);
+            add_row(cx, "ref", "field", ident, sp, f.id, path, ty);
+            return;
+        }
+    }
+}
+
+fn method_row(cx: @Ctxt, e: @ast::expr, ident: ast::ident) {
+    let did = match cx.method_map.find(&e.id) {
+      Some(ref entry) => method_def_id(cx.tcx, &entry.origin),
+      None => return
+    };
+    add_row(cx, "ref", "method", ident, e.span, did,
+            ty::item_path_str(cx.tcx, did), node_ty(cx, e.callee_id));
+}
+
+// The method that a call was resolved to: the impl's own method where it
+// is known statically, and the trait's declaration of it otherwise
+fn method_def_id(tcx: ty::ctxt, origin: &method_origin) -> ast::def_id {
+    match *origin {
+      method_static(did) => did,
+      method_param(ref mp) => trait_method_def_id(tcx, mp.trait_id,
+                                                  mp.method_num),
+      method_trait(trait_id, n, _) | method_self(trait_id, n) |
+      method_super(trait_id, n) => trait_method_def_id(tcx, trait_id, n)
+    }
+}
+
+fn trait_method_def_id(tcx: ty::ctxt, trait_id: ast::def_id, n: uint)
+    -> ast::def_id {
+    ty::trait_methods(tcx, trait_id)[n].def_id
+}
+
+// The definition of a local item
+fn def_row(cx: @Ctxt, kind: ~str, name: ast::ident, sp: span,
+           did: ast::def_id) {
+    let ty = match cx.tcx.tcache.find(&did) {
+      Some(tpt) => Some(tpt.ty),
+      None => None
+    };
+    add_row(cx, "def", kind, name, sp, did, ty::item_path_str(cx.tcx, did),
+            ty);
+}
+
+fn add_row(cx: @Ctxt, role: &str, kind: &str, name: ast::ident, sp: span,
+           did: ast::def_id, path: ~str, ty: Option<ty::t>) {
+    let cm = cx.tcx.sess.codemap;
+    let lo = cm.lookup_char_pos(sp.lo);
+    let hi = cm.lookup_char_pos(sp.hi);
+    let ty_str = match ty {
+      Some(t) => ty_to_str(cx.tcx, t),
+      None => ~""
+    };
+    cx.rows.push(fmt!("%s,%s,%s,%s,%u,%u,%u,%u,%d,%d,%s,%s",
+                      role, kind, csv_quote(cx.tcx.sess.str_of(name)),
+                      csv_quote(lo.file.name), lo.line, lo.col.to_uint(),
+                      hi.line, hi.col.to_uint(), did.crate, did.node,
+                      csv_quote(path), csv_quote(ty_str)));
+}
+
+fn node_ty(cx: @Ctxt, id: ast::node_id) -> Option<ty::t> {
+    oldsmallintmap::find(*cx.tcx.node_types, id as uint)
+}
+
+// Whether `def` names an item, which has a path of its own
+fn is_item(def: ast::def) -> bool {
+    match def {
+      ast::def_fn(*) | ast::def_static_method(*) | ast::def_mod(_) |
+      ast::def_foreign_mod(_) | ast::def_const(_) | ast::def_variant(*) |
+      ast::def_ty(_) | ast::def_struct(_) => true,
+      _ => false
+    }
+}
+
+fn def_kind(def: ast::def) -> ~str {
+    match def {
+      ast::def_fn(*) => ~"fn",
+      ast::def_static_method(*) => ~"static_method",
+      ast::def_self(*) => ~"self",
+      ast::def_self_ty(_) => ~"self_ty",
+      ast::def_mod(_) => ~"mod",
+      ast::def_foreign_mod(_) => ~"foreign_mod",
+      ast::def_const(_) => ~"const",
+      ast::def_arg(*) => ~"arg",
+      ast::def_local(*) | ast::def_binding(*) => ~"local",
+      ast::def_variant(*) => ~"variant",
+      ast::def_ty(_) => ~"type",
+      ast::def_prim_ty(_) => ~"prim_ty",
+      ast::def_ty_param(*) | ast::def_typaram_binder(_) => ~"ty_param",
+      ast::def_use(_) => ~"use",
+      ast::def_upvar(_, inner, _, _) => def_kind(*inner),
+      ast::def_struct(_) => ~"struct",
+      ast::def_region(_) => ~"region",
+      ast::def_label(_) => ~"label"
+    }
+}
+
+// Quotes a field of CSV, doubling the quotes within it
+fn csv_quote(s: &str) -> ~str {
+    ~"\"" + str::replace(s, "\"", "\"\"") + ~"\""
+}
+
+#[cfg(test)]
+mod test {
+    use core::prelude::*;
+
+    use driver::driver::{build_configuration, build_output_filenames};
+    use driver::driver::{build_session, build_session_options};
+    use driver::driver::{compile_upto, cu_typeck, optgroups, str_input};
+
+    use core::io;
+    use core::os;
+    use core::str;
+    use std::getopts::groups::getopts;
+    use std::getopts;
+    use std::tempfile;
+    use syntax::diagnostic;
+
+    // The rows of the index saved for `src`, each split into its fields
+    fn analyse(src: ~str) -> ~[~[~str]] {
+        let matches =
+            &match getopts(~[~"-Z", ~"save-analysis"], optgroups()) {
+              Ok(copy m) => m,
+              Err(copy f) => die!(~"analyse: " + getopts::fail_str(f))
+            };
+        let sessopts = build_session_options(
+            ~"rustc", matches, diagnostic::emit);
+        let sess = build_session(sessopts, diagnostic::emit);
+        let dir = match tempfile::mkdtemp(&os::tmpdir(), "save-analysis") {
+          Some(dir) => dir,
+          None => die!(~"analyse: couldn't create a directory")
+        };
+        let input = str_input(src);
+        let cfg = build_configuration(sess, ~"rustc", copy input);
+        let outputs = build_output_filenames(copy input, &Some(copy dir),
+                                             &None, sess);
+        compile_upto(sess, cfg, input, cu_typeck, Some(outputs));
+
+        let csv = outputs.out_filename.with_filetype("csv");
+        let rows = match io::read_whole_file_str(&csv) {
+          Ok(move s) => str::lines(s).tail().map(|r| split_row(*r)),
+          Err(move e) => die!(e)
+        };
+        os::remove_file(&csv);
+        os::remove_dir(&dir);
+        rows
+    }
+
+    // Splits a row of CSV at the commas outside of quotes. The quotes are
+    // dropped, which is enough for the rows written here.
+    fn split_row(row: &str) -> ~[~str] {
+        let mut fields = ~[];
+        let mut field = ~"";
+        let mut quoted = false;
+        for str::each_char(row) |c| {
+            if c == '"' {
+                quoted = !quoted;
+            } else if c == ',' && !quoted {
+                fields.push(move field);
+                field = ~"";
+            } else {
+                str::push_char(&mut field, c);
+            }
+        }
+        fields.push(move field);
+        fields
+    }
+
+    fn find_row(rows: &[~[~str]], role: &str, kind: &str,
+                name: &str) -> ~[~str] {
+        match rows.find(|r| str::eq_slice(r[0], role) &&
+                            str::eq_slice(r[1], kind) &&
+                            str::eq_slice(r[2], name)) {
+          Some(move r) => r,
+          None => die!(fmt!("no %s row for %s %s", role, kind, name))
+        }
+    }
+
+    // Whether a `ref` row's def_id is that of a `def` row
+    fn joins(rows: &[~[~str]], r: &[~str]) -> bool {
+        rows.any(|d| d[0] == ~"def" && d[8] == r[8] && d[9] == r[9])
+    }
+
+    #[test]
+    fn should_join_refs_to_their_defs() {
+        let rows = analyse(~"
+            struct Point { x: int, y: int }
+
+            impl Point {
+                fn norm(&self) -> int { self.x * self.x + self.y * self.y }
+            }
+
+            fn double(n: int) -> int { n * 2 }
+
+            fn main() {
+                let p = Point { x: 1, y: 2 };
+                let v = ~[1, 2, 3];
+                let n = double(p.x) + p.norm() + core::vec::len(v) as int;
+            }");
+
+        for [("fn", "double"), ("field", "x"), ("method", "norm"),
+             ("local", "p")].each |&(kind, name)| {
+            let r = find_row(rows, "ref", kind, name);
+            assert r[8] == ~"0";
+            assert joins(rows, r);
+        }
+    }
+
+    #[test]
+    fn should_read_the_paths_of_other_crates() {
+        let rows = analyse(~"
+            fn main() {
+                let v = ~[1, 2, 3];
+                let n = core::vec::len(v);
+            }");
+
+        let r = find_row(rows, "ref", "fn", "len");
+        assert r[8] != ~"0";
+        assert str::ends_with(r[10], "vec::len");
+        assert !joins(rows, r);
+    }
+}
@@ -58,6 +58,7 @@ use middle::trans::inline;
 use middle::trans::machine;
 use middle::trans::meth;
 use middle::trans::monomorphize;
+use middle::trans::partition;
 use middle::trans::reachable;
 use middle::trans::shape::*;
 use middle::trans::tvec;
@@ -1691,7 +1692,7 @@ pub fn copy_args_to_allocas(fcx: fn_ctxt,
         fcx.llargs.insert(arg_id, local_mem(llarg));
 
         if fcx.ccx.sess.opts.extra_debuginfo {
-            debuginfo::create_arg(bcx, args[arg_n], args[arg_n].ty.span);
+            debuginfo::create_arg(bcx, args[arg_n]);
         }
     }
 
@@ -2103,7 +2104,13 @@ pub fn trans_struct_def(ccx: @crate_ctxt, struct_def: @ast::struct_def,
 pub fn trans_mod(ccx: @crate_ctxt, m: ast::_mod) {
     let _icx = ccx.insn_ctxt("trans_mod");
     for vec::each(m.items) |item| {
-        trans_item(ccx, **item);
+        // Items owned by other codegen units are only declared here, when
+        // something in this unit refers to them.
+        match item.node {
+          ast::item_mod(_) => trans_item(ccx, **item),
+          _ if ccx.codegen_unit.owns_item(item.id) => trans_item(ccx, **item),
+          _ => ()
+        }
     }
 }
 
@@ -2162,7 +2169,9 @@ pub fn register_fn_fuller(ccx: @crate_ctxt,
                      (!ccx.sess.building_library ||
                       (ccx.sess.building_library &&
                        ccx.sess.targ_cfg.os == session::os_android));
-    if is_main { create_main_wrapper(ccx, sp, llfn); }
+    if is_main && ccx.codegen_unit.owns_item(node_id) {
+        create_main_wrapper(ccx, sp, llfn);
+    }
     llfn
 }
 
@@ -2471,7 +2480,10 @@ pub fn get_item_val(ccx: @crate_ctxt, id: ast::node_id) -> ValueRef {
             ccx.sess.bug(~"get_item_val(): unexpected variant")
           }
         };
-        if !(exprt || ccx.reachable.contains_key_ref(&id)) {
+        // With several codegen units, an item may be defined in one unit
+        // and used from another, so it has to stay visible to the linker.
+        if !(exprt || ccx.reachable.contains_key_ref(&id)) &&
+                ccx.codegen_unit.count == 1u {
             lib::llvm::SetLinkage(val, lib::llvm::InternalLinkage);
         }
         ccx.item_vals.insert(id, val);
@@ -2516,9 +2528,13 @@ pub fn trans_constant(ccx: @crate_ctxt, it: @ast::item) {
                     llvm::LLVMAddGlobal(ccx.llmod, ccx.int_type, buf)
                 }
             });
-            unsafe {
-                llvm::LLVMSetInitializer(discrim_gvar, C_int(ccx, disr_val));
-                llvm::LLVMSetGlobalConstant(discrim_gvar, True);
+            // Only the primary codegen unit defines the discriminants.
+            if ccx.codegen_unit.is_primary() {
+                unsafe {
+                    llvm::LLVMSetInitializer(discrim_gvar,
+                                             C_int(ccx, disr_val));
+                    llvm::LLVMSetGlobalConstant(discrim_gvar, True);
+                }
             }
             ccx.discrims.insert(
                 local_def(variant.node.id), discrim_gvar);
@@ -2928,14 +2944,62 @@ pub fn trans_crate(sess: session::Session,
                    tcx: ty::ctxt,
                    output: &Path,
                    emap2: resolve::ExportMap2,
-                   maps: astencode::Maps) -> (ModuleRef, link_meta) {
+                   maps: astencode::Maps) -> (~[ModuleRef], link_meta) {
+    let units = partition::partition_crate(crate, sess.codegen_units());
+    trans_units(sess, crate, tcx, output, emap2, maps, units)
+}
 
+/**
+ * Translates `units`, some of the codegen units of the crate, into a module
+ * each. The metadata goes into the primary unit, which needs the symbols of
+ * every item, so a library's primary unit is only translated with all the
+ * others.
+ */
+pub fn trans_units(sess: session::Session,
+                   crate: @ast::crate,
+                   tcx: ty::ctxt,
+                   output: &Path,
+                   emap2: resolve::ExportMap2,
+                   maps: astencode::Maps,
+                   units: &[@partition::CodegenUnit])
+                -> (~[ModuleRef], link_meta) {
     let symbol_hasher = @hash::default_state();
     let link_meta =
         link::build_link_meta(sess, crate, output, symbol_hasher);
     let reachable = reachable::find_reachable(crate.node.module, emap2, tcx,
                                               maps.method_map);
 
+    // Symbol tables are shared between codegen units, so that the metadata
+    // written into the primary unit can name items defined in any of them.
+    let item_symbols = HashMap();
+    let discrim_symbols = HashMap();
+
+    let ccxs = do units.map |unit| {
+        trans_codegen_unit(sess, crate, tcx, *unit, symbol_hasher,
+                           /*bad*/copy link_meta, emap2, reachable,
+                           item_symbols, discrim_symbols, maps)
+    };
+
+    // Translate the metadata.
+    for ccxs.each |ccx| {
+        if ccx.codegen_unit.is_primary() { write_metadata(*ccx, crate); }
+    }
+
+    return (ccxs.map(|ccx| ccx.llmod), link_meta);
+}
+
+// Translates the items owned by `unit` into a fresh LLVM module.
+pub fn trans_codegen_unit(sess: session::Session,
+                          crate: @ast::crate,
+                          tcx: ty::ctxt,
+                          unit: @partition::CodegenUnit,
+                          symbol_hasher: @hash::State,
+                          +link_meta: link_meta,
+                          emap2: resolve::ExportMap2,
+                          reachable: reachable::map,
+                          item_symbols: HashMap<ast::node_id, ~str>,
+                          discrim_symbols: HashMap<ast::node_id, ~str>,
+                          maps: astencode::Maps) -> @crate_ctxt {
     // Append ".rc" to crate name as LLVM module identifier.
     //
     // LLVM code generator emits a ".file filename" directive
@@ -2944,7 +3008,11 @@ pub fn trans_crate(sess: session::Session,
     // crashes if the module identifer is same as other symbols
     // such as a function name in the module.
     // 1. http://llvm.org/bugs/show_bug.cgi?id=11479
-    let llmod_id = link_meta.name.to_owned() + ~".rc";
+    let llmod_id = if unit.is_primary() {
+        link_meta.name.to_owned() + ~".rc"
+    } else {
+        fmt!("%s.%u.rc", link_meta.name, unit.index)
+    };
 
     unsafe {
         let llmod = str::as_c_str(llmod_id, |buf| {
@@ -2991,11 +3059,11 @@ pub fn trans_crate(sess: session::Session,
               item_vals: HashMap(),
               exp_map2: emap2,
               reachable: reachable,
-              item_symbols: HashMap(),
+              item_symbols: item_symbols,
               link_meta: link_meta,
               enum_sizes: ty::new_ty_hash(),
               discrims: HashMap(),
-              discrim_symbols: HashMap(),
+              discrim_symbols: discrim_symbols,
               tydescs: ty::new_ty_hash(),
               mut finished_tydescs: false,
               external: HashMap(),
@@ -3039,7 +3107,8 @@ pub fn trans_crate(sess: session::Session,
               crate_map: crate_map,
               mut uses_gc: false,
               dbg_cx: dbg_cx,
-              mut do_not_commit_warning_issued: false
+              mut do_not_commit_warning_issued: false,
+              codegen_unit: unit
         };
 
         {
@@ -3053,14 +3122,22 @@ pub fn trans_crate(sess: session::Session,
         }
 
         decl_gc_metadata(ccx, llmod_id);
-        fill_crate_map(ccx, crate_map);
+        if unit.is_primary() {
+            fill_crate_map(ccx, crate_map);
+        }
         glue::emit_tydescs(ccx);
-        write_abi_version(ccx);
+        // The ABI version is an external symbol, so only one unit defines it
+        if unit.is_primary() {
+            write_abi_version(ccx);
+        }
 
-        // Translate the metadata.
-        write_metadata(ccx, crate);
         if ccx.sess.trans_stats() {
-            io::println(~"--- trans stats ---");
+            if unit.count > 1u {
+                io::println(fmt!("--- trans stats (codegen unit %u) ---",
+                                 unit.index));
+            } else {
+                io::println(~"--- trans stats ---");
+            }
             io::println(fmt!("n_static_tydescs: %u",
                              ccx.stats.n_static_tydescs));
             io::println(fmt!("n_glues_created: %u",
@@ -3074,12 +3151,24 @@ pub fn trans_crate(sess: session::Session,
             io::println(fmt!("n_closures: %u", ccx.stats.n_closures));
         }
 
+        if ccx.sess.profiling() {
+            let p = ccx.sess.profile;
+            p.add("trans.n_static_tydescs", ccx.stats.n_static_tydescs);
+            p.add("trans.n_glues_created", ccx.stats.n_glues_created);
+            p.add("trans.n_null_glues", ccx.stats.n_null_glues);
+            p.add("trans.n_real_glues", ccx.stats.n_real_glues);
+            p.add("trans.n_fns", ccx.stats.n_fns);
+            p.add("trans.n_monos", ccx.stats.n_monos);
+            p.add("trans.n_inlines", ccx.stats.n_inlines);
+            p.add("trans.n_closures", ccx.stats.n_closures);
+        }
+
         if ccx.sess.count_llvm_insns() {
             for ccx.stats.llvm_insns.each_ref |&k, &v| {
                 io::println(fmt!("%-7u %s", v, k));
             }
         }
-        return (llmod, link_meta);
+        return ccx;
     }
 }
 //
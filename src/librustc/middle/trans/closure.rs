@@ -20,6 +20,7 @@ use middle::trans::base::*;
 use middle::trans::build::*;
 use middle::trans::callee;
 use middle::trans::common::*;
+use middle::trans::debuginfo;
 use middle::trans::datum::{Datum, INIT, ByRef, ByValue, ZeroMem};
 use middle::trans::expr;
 use middle::trans::glue;
@@ -354,6 +355,9 @@ pub fn load_environment(fcx: fn_ctxt,
         }
         let def_id = ast_util::def_id_of_def(cap_var.def);
         fcx.llupvars.insert(def_id.node, upvarptr);
+        if fcx.ccx.sess.opts.extra_debuginfo {
+            debuginfo::create_captured_var(bcx, cap_var, upvarptr);
+        }
         i += 1u;
     }
     if load_ret_handle {
@@ -35,6 +35,7 @@ use middle::trans::debuginfo;
 use middle::trans::expr;
 use middle::trans::glue;
 use middle::trans::meth;
+use middle::trans::partition;
 use middle::trans::reachable;
 use middle::trans::shape;
 use middle::trans::type_of;
@@ -223,7 +224,9 @@ pub struct crate_ctxt {
      // is not emitted by LLVM's GC pass when no functions use GC.
      mut uses_gc: bool,
      dbg_cx: Option<debuginfo::debug_ctxt>,
-     mut do_not_commit_warning_issued: bool
+     mut do_not_commit_warning_issued: bool,
+     // The part of the crate this context translates
+     codegen_unit: @partition::CodegenUnit
 }
 
 // Types used for llself.
@@ -1171,6 +1174,21 @@ pub fn C_estr_slice(cx: @crate_ctxt, +s: ~str) -> ValueRef {
     }
 }
 
+// A global holding the bytes of a byte string literal, which are not
+// null-terminated.
+pub fn C_binary(cx: @crate_ctxt, +bytes: ~[u8]) -> ValueRef {
+    unsafe {
+        let sc = C_bytes(bytes);
+        let g =
+            str::as_c_str(fmt!("binary%u", (cx.names)(~"binary").repr),
+                        |buf| llvm::LLVMAddGlobal(cx.llmod, val_ty(sc), buf));
+        llvm::LLVMSetInitializer(g, sc);
+        llvm::LLVMSetGlobalConstant(g, True);
+        lib::llvm::SetLinkage(g, lib::llvm::InternalLinkage);
+        return g;
+    }
+}
+
 // Returns a Plain Old LLVM String:
 pub fn C_postr(s: ~str) -> ValueRef {
     unsafe {
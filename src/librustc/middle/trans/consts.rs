@@ -55,7 +55,8 @@ pub fn const_lit(cx: @crate_ctxt, e: @ast::expr, lit: ast::lit)
       }
       ast::lit_bool(b) => C_bool(b),
       ast::lit_nil => C_nil(),
-      ast::lit_str(s) => C_estr_slice(cx, /*bad*/copy *s)
+      ast::lit_str(s) => C_estr_slice(cx, /*bad*/copy *s),
+      ast::lit_binary(bytes) => C_bytes(/*bad*/copy *bytes)
     }
 }
 
@@ -392,6 +393,11 @@ pub fn const_expr(cx: @crate_ctxt, e: @ast::expr) -> ValueRef {
               ast::expr_lit(lit) => {
                 match lit.node {
                   ast::lit_str(*) => { const_expr(cx, sub) }
+                  ast::lit_binary(bytes) => {
+                    let gv = C_binary(cx, /*bad*/copy *bytes);
+                    let p = const_ptrcast(cx, gv, T_i8());
+                    C_struct(~[p, C_uint(cx, bytes.len())])
+                  }
                   _ => { cx.sess.span_bug(e.span,
                                           ~"bad const-slice lit") }
                 }
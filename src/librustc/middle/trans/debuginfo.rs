@@ -11,8 +11,9 @@
 use core::prelude::*;
 
 use driver::session;
-use lib::llvm::ValueRef;
+use lib::llvm::{TypeRef, ValueRef};
 use lib::llvm::llvm;
+use middle::moves;
 use middle::pat_util::*;
 use middle::trans::base;
 use middle::trans::build::B;
@@ -25,10 +26,8 @@ use util::ppaux::ty_to_str;
 
 use core::libc;
 use core::option;
-use core::sys;
 use std::oldmap::HashMap;
 use std::oldmap;
-use syntax::ast::Ty;
 use syntax::codemap::{span, CharPos};
 use syntax::parse::token::ident_interner;
 use syntax::{ast, codemap, ast_util, ast_map};
@@ -52,6 +51,9 @@ const StructureTypeTag: int = 19;
 const MemberTag: int = 13;
 const ArrayTypeTag: int = 1;
 const SubrangeTag: int = 33;
+const UnionTypeTag: int = 23;
+const EnumerationTypeTag: int = 4;
+const EnumeratorTag: int = 40;
 
 const DW_ATE_boolean: int = 0x02;
 const DW_ATE_float: int = 0x04;
@@ -60,6 +62,8 @@ const DW_ATE_signed_char: int = 0x06;
 const DW_ATE_unsigned: int = 0x07;
 const DW_ATE_unsigned_char: int = 0x08;
 
+const DIFlagFwdDecl: int = 4;
+
 fn llstr(s: ~str) -> ValueRef {
     str::as_c_str(s, |sbuf| {
         unsafe {
@@ -107,13 +111,19 @@ fn add_named_metadata(cx: @crate_ctxt, name: ~str, val: ValueRef) {
 pub type debug_ctxt = {
     llmetadata: metadata_cache,
     names: namegen,
-    crate_file: ~str
+    crate_file: ~str,
+    // Type descriptors by type id
+    types: HashMap<uint, @metadata<tydesc_md>>,
+    // Types whose descriptors are being built, to stop at recursive types
+    pending: HashMap<uint, ()>
 };
 
 pub fn mk_ctxt(+crate: ~str, intr: @ident_interner) -> debug_ctxt {
     {llmetadata: oldmap::HashMap(),
      names: new_namegen(intr),
-     crate_file: crate}
+     crate_file: crate,
+     types: oldmap::HashMap(),
+     pending: oldmap::HashMap()}
 }
 
 fn update_cache(cache: metadata_cache, mdtag: int, val: debug_metadata) {
@@ -129,11 +139,9 @@ type metadata<T> = {node: ValueRef, data: T};
 
 type file_md = {path: ~str};
 type compile_unit_md = {name: ~str};
-type subprogram_md = {id: ast::node_id};
-type local_var_md = {id: ast::node_id};
+type subprogram_md = {id: ast::node_id, llfn: ValueRef};
 type tydesc_md = {hash: uint};
-type block_md = {start: codemap::Loc, end: codemap::Loc};
-type argument_md = {id: ast::node_id};
+type block_md = {id: ast::node_id, llfn: ValueRef};
 type retval_md = {id: ast::node_id};
 
 type metadata_cache = HashMap<int, ~[debug_metadata]>;
@@ -142,10 +150,8 @@ enum debug_metadata {
     file_metadata(@metadata<file_md>),
     compile_unit_metadata(@metadata<compile_unit_md>),
     subprogram_metadata(@metadata<subprogram_md>),
-    local_var_metadata(@metadata<local_var_md>),
     tydesc_metadata(@metadata<tydesc_md>),
     block_metadata(@metadata<block_md>),
-    argument_metadata(@metadata<argument_md>),
     retval_metadata(@metadata<retval_md>),
 }
 
@@ -162,10 +168,8 @@ fn md_from_metadata<T>(val: debug_metadata) -> T {
           file_metadata(md) => cast_safely(md),
           compile_unit_metadata(md) => cast_safely(md),
           subprogram_metadata(md) => cast_safely(md),
-          local_var_metadata(md) => cast_safely(md),
           tydesc_metadata(md) => cast_safely(md),
           block_metadata(md) => cast_safely(md),
-          argument_metadata(md) => cast_safely(md),
           retval_metadata(md) => cast_safely(md)
         }
     }
@@ -258,35 +262,33 @@ fn create_file(cx: @crate_ctxt, +full_path: ~str) -> @metadata<file_md> {
     return mdval;
 }
 
-fn line_from_span(cm: @codemap::CodeMap, sp: span) -> uint {
-    cm.lookup_char_pos(sp.lo).line
-}
-
+// The lexical block of `cx`, which must have source information. Blocks
+// translated for the same AST node share one lexical block.
 fn create_block(cx: block) -> @metadata<block_md> {
     let cache = get_cache(cx.ccx());
+    let info = cx.node_info.get();
     let mut cx = cx;
-    while cx.node_info.is_none() {
+    loop {
         match cx.parent {
-          Some(b) => cx = b,
-          None => die!()
+          Some(b) if b.node_info.is_some() &&
+                     b.node_info.get().id == info.id => cx = b,
+          _ => break
         }
     }
-    let sp = cx.node_info.get().span;
 
-    let start = cx.sess().codemap.lookup_char_pos(sp.lo);
-    let fname = /*bad*/copy start.file.name;
-    let end = cx.sess().codemap.lookup_char_pos(sp.hi);
+    let llfn = cx.fcx.llfn;
     let tg = LexicalBlockTag;
-    /*match cached_metadata::<@metadata<block_md>>(
-        cache, tg,
-        {|md| start == md.data.start && end == md.data.end}) {
-      option::Some(md) { return md; }
-      option::None {}
-    }*/
+    match cached_metadata::<@metadata<block_md>>(
+        cache, tg, |md| md.data.id == info.id && md.data.llfn == llfn) {
+      option::Some(md) => return md,
+      option::None => ()
+    }
 
+    let start = cx.sess().codemap.lookup_char_pos(info.span.lo);
+    let fname = /*bad*/copy start.file.name;
     let parent = match cx.parent {
         None => create_function(cx.fcx).node,
-        Some(bcx) => create_block(bcx).node
+        Some(bcx) => scope_of(bcx)
     };
     let file_node = create_file(cx.ccx(), fname);
     let unique_id = match cache.find(&LexicalBlockTag) {
@@ -301,15 +303,31 @@ fn create_block(cx: block) -> @metadata<block_md> {
                   lli32(unique_id)
                  ];
     let val = llmdnode(lldata);
-    let mdval = @{node: val, data: {start: start, end: end}};
-    //update_cache(cache, tg, block_metadata(mdval));
+    let mdval = @{node: val, data: {id: info.id, llfn: llfn}};
+    update_cache(cache, tg, block_metadata(mdval));
     return mdval;
 }
 
+// The innermost scope around `cx`: the lexical block of the nearest block
+// with source information, or else the function.
+fn scope_of(cx: block) -> ValueRef {
+    let mut cx = cx;
+    loop {
+        if cx.node_info.is_some() {
+            return create_block(cx).node;
+        }
+        match cx.parent {
+          Some(b) => cx = b,
+          None => return create_function(cx.fcx).node
+        }
+    }
+}
+
+// Sizes and alignments as used for the fields of structures
 fn size_and_align_of(cx: @crate_ctxt, t: ty::t) -> (int, int) {
     let llty = type_of::type_of(cx, t);
     (machine::llsize_of_real(cx, llty) as int,
-     machine::llalign_of_pref(cx, llty) as int)
+     machine::llalign_of_min(cx, llty) as int)
 }
 
 fn create_basic_type(cx: @crate_ctxt, t: ty::t, span: span)
@@ -322,7 +340,15 @@ fn create_basic_type(cx: @crate_ctxt, t: ty::t, span: span)
       option::None => ()
     }
 
-    let (name, encoding) = (~"uint", DW_ATE_unsigned);
+    let (name, encoding) = match ty::get(t).sty {
+      ty::ty_nil | ty::ty_bot => (~"()", DW_ATE_unsigned),
+      ty::ty_bool => (~"bool", DW_ATE_boolean),
+      ty::ty_int(ast::ty_char) => (~"char", DW_ATE_unsigned_char),
+      ty::ty_int(_) => (ty_to_str(cx.tcx, t), DW_ATE_signed),
+      ty::ty_uint(_) => (ty_to_str(cx.tcx, t), DW_ATE_unsigned),
+      ty::ty_float(_) => (ty_to_str(cx.tcx, t), DW_ATE_float),
+      _ => cx.sess.bug(~"create_basic_type: not a basic type")
+    };
 
     let fname = filename_from_span(cx, span);
     let file_node = create_file(cx, fname);
@@ -349,20 +375,12 @@ fn create_pointer_type(cx: @crate_ctxt, t: ty::t, span: span,
                        pointee: @metadata<tydesc_md>)
     -> @metadata<tydesc_md> {
     let tg = PointerTypeTag;
-    /*let cache = cx.llmetadata;
-    match cached_metadata::<@metadata<tydesc_md>>(
-        cache, tg, {|md| ty::hash_ty(t) == ty::hash_ty(md.data.hash)}) {
-      option::Some(md) { return md; }
-      option::None {}
-    }*/
     let (size, align) = size_and_align_of(cx, t);
     let fname = filename_from_span(cx, span);
     let file_node = create_file(cx, fname);
-    //let cu_node = create_compile_unit(cx, fname);
     let llnode = create_derived_type(tg, file_node.node, ~"", 0, size * 8,
                                      align * 8, 0, pointee.node);
     let mdval = @{node: llnode, data: {hash: ty::type_id(t)}};
-    //update_cache(cache, tg, tydesc_metadata(mdval));
     add_named_metadata(cx, ~"llvm.dbg.ty", llnode);
     return mdval;
 }
@@ -384,6 +402,7 @@ fn finish_structure(cx: @struct_ctxt) -> ValueRef {
                                  cx.total_size,
                                  cx.align,
                                  0,
+                                 0,
                                  option::None,
                                  option::Some(/*bad*/copy cx.members));
 }
@@ -416,67 +435,66 @@ fn create_derived_type(type_tag: int, file: ValueRef, +name: ~str, line: int,
     return llmdnode(lldata);
 }
 
+// Sizes, alignments and offsets are in bytes
 fn add_member(cx: @struct_ctxt, +name: ~str, line: int, size: int, align: int,
-              ty: ValueRef) {
+              offset: int, ty: ValueRef) {
     cx.members.push(create_derived_type(MemberTag, cx.file, name, line,
-                                       size * 8, align * 8, cx.total_size,
+                                       size * 8, align * 8, offset * 8,
                                        ty));
-    cx.total_size += size * 8;
+    if cx.total_size < (offset + size) * 8 {
+        cx.total_size = (offset + size) * 8;
+    }
 }
 
-fn create_record(cx: @crate_ctxt, t: ty::t, fields: ~[ast::ty_field],
-                 span: span) -> @metadata<tydesc_md> {
+// Describes a structure laid out like the LLVM structure type `llty`, with
+// one member for each of its elements.
+fn create_struct(cx: @crate_ctxt, +name: ~str, llty: TypeRef,
+                 members: &[(~str, ValueRef)], span: span) -> ValueRef {
     let fname = filename_from_span(cx, span);
     let file_node = create_file(cx, fname);
-    let scx = create_structure(file_node,
-                               cx.sess.str_of(
-                                   ((/*bad*/copy cx.dbg_cx).get().names)
-                                   (~"rec")),
-                               line_from_span(cx.sess.codemap,
-                                              span) as int);
-    for fields.each |field| {
-        let field_t = ty::get_field(cx.tcx, t, field.node.ident).mt.ty;
-        let ty_md = create_ty(cx, field_t, field.node.mt.ty);
-        let (size, align) = size_and_align_of(cx, field_t);
-        add_member(scx, cx.sess.str_of(field.node.ident),
-                   line_from_span(cx.sess.codemap, field.span) as int,
-                   size as int, align as int, ty_md.node);
+    let scx = create_structure(file_node, name, 0);
+    for members.eachi |i, member| {
+        let (member_name, node) = /*bad*/copy *member;
+        let llelt = struct_elt(llty, i);
+        add_member(scx, member_name, 0,
+                   machine::llsize_of_real(cx, llelt) as int,
+                   machine::llalign_of_min(cx, llelt) as int,
+                   machine::llelement_offset(cx, llty, i) as int,
+                   node);
     }
-    let mdval = @{node: finish_structure(scx), data:{hash: ty::type_id(t)}};
-    return mdval;
+    scx.total_size = machine::llsize_of_alloc(cx, llty) as int * 8;
+    return finish_structure(scx);
 }
 
-fn create_boxed_type(cx: @crate_ctxt, outer: ty::t, _inner: ty::t,
+// The members of a tuple, or of an enum variant, are named `__0`, `__1`...
+fn positional_members(cx: @crate_ctxt, tys: &[ty::t], span: span)
+    -> ~[(~str, ValueRef)] {
+    do vec::mapi(tys) |i, t| {
+        (fmt!("__%u", i), create_ty(cx, *t, span).node)
+    }
+}
+
+fn create_boxed_type(cx: @crate_ctxt, outer: ty::t, inner: ty::t,
                      span: span, boxed: @metadata<tydesc_md>)
     -> @metadata<tydesc_md> {
-    //let tg = StructureTypeTag;
-    /*let cache = cx.llmetadata;
-    match cached_metadata::<@metadata<tydesc_md>>(
-        cache, tg, {|md| ty::hash_ty(outer) == ty::hash_ty(md.data.hash)}) {
-      option::Some(md) { return md; }
-      option::None {}
-    }*/
-    let fname = filename_from_span(cx, span);
-    let file_node = create_file(cx, fname);
-    //let cu_node = create_compile_unit_metadata(cx, fname);
-    let uint_t = ty::mk_uint(cx.tcx);
-    let refcount_type = create_basic_type(cx, uint_t, span);
-    let scx = create_structure(file_node, ty_to_str(cx.tcx, outer), 0);
-    add_member(scx, ~"refcnt", 0, sys::size_of::<uint>() as int,
-               sys::min_align_of::<uint>() as int, refcount_type.node);
-    add_member(scx, ~"boxed", 0, 8, //XXX member_size_and_align(??)
-               8, //XXX just a guess
-               boxed.node);
-    let llnode = finish_structure(scx);
+    let llty = T_box(cx, type_of::type_of(cx, inner));
+    let refcount_type = create_ty(cx, ty::mk_uint(cx.tcx), span);
+    let ptr_type = create_ty(cx, ty::mk_nil_ptr(cx.tcx), span);
+    let llnode = create_struct(cx, ty_to_str(cx.tcx, outer), llty,
+                               ~[(~"refcnt", refcount_type.node),
+                                 (~"tydesc", ptr_type.node),
+                                 (~"prev", ptr_type.node),
+                                 (~"next", ptr_type.node),
+                                 (~"boxed", boxed.node)],
+                               span);
     let mdval = @{node: llnode, data: {hash: ty::type_id(outer)}};
-    //update_cache(cache, tg, tydesc_metadata(mdval));
     add_named_metadata(cx, ~"llvm.dbg.ty", llnode);
     return mdval;
 }
 
 fn create_composite_type(type_tag: int, +name: ~str, file: ValueRef,
                          line: int, size: int, align: int, offset: int,
-                         derived: Option<ValueRef>,
+                         flags: int, derived: Option<ValueRef>,
                          +members: Option<~[ValueRef]>)
     -> ValueRef {
     let lldata = ~[lltag(type_tag),
@@ -487,7 +505,7 @@ fn create_composite_type(type_tag: int, +name: ~str, file: ValueRef,
                   lli64(size), // size of members
                   lli64(align), // align
                   lli32/*64*/(offset), // offset
-                  lli32(0), // flags
+                  lli32(flags), // flags
                   if derived.is_none() {
                       llnull()
                   } else { // derived from
@@ -504,132 +522,249 @@ fn create_composite_type(type_tag: int, +name: ~str, file: ValueRef,
     return llmdnode(lldata);
 }
 
-fn create_vec(cx: @crate_ctxt, vec_t: ty::t, elem_t: ty::t,
-              vec_ty_span: codemap::span, elem_ty: @ast::Ty)
-    -> @metadata<tydesc_md> {
-    let fname = filename_from_span(cx, vec_ty_span);
+fn create_array(cx: @crate_ctxt, elem_t: ty::t, len: uint, span: span)
+    -> ValueRef {
+    let fname = filename_from_span(cx, span);
     let file_node = create_file(cx, fname);
-    let elem_ty_md = create_ty(cx, elem_t, elem_ty);
-    let scx = create_structure(file_node, ty_to_str(cx.tcx, vec_t), 0);
-    let size_t_type = create_basic_type(cx, ty::mk_uint(cx.tcx), vec_ty_span);
-    add_member(scx, ~"fill", 0, sys::size_of::<libc::size_t>() as int,
-               sys::min_align_of::<libc::size_t>() as int, size_t_type.node);
-    add_member(scx, ~"alloc", 0, sys::size_of::<libc::size_t>() as int,
-               sys::min_align_of::<libc::size_t>() as int, size_t_type.node);
-    let subrange = llmdnode(~[lltag(SubrangeTag), lli64(0), lli64(0)]);
-    let (arr_size, arr_align) = size_and_align_of(cx, elem_t);
-    let data_ptr = create_composite_type(ArrayTypeTag, ~"", file_node.node, 0,
-                                         arr_size, arr_align, 0,
-                                         option::Some(elem_ty_md.node),
-                                         option::Some(~[subrange]));
-    add_member(scx, ~"data", 0, 0, // clang says the size should be 0
-               sys::min_align_of::<u8>() as int, data_ptr);
-    let llnode = finish_structure(scx);
+    let elem_ty_md = create_ty(cx, elem_t, span);
+    let (elem_size, elem_align) = size_and_align_of(cx, elem_t);
+    // An upper bound of 0 also stands for the unknown length of a
+    // heap-allocated vector; gdb does not check the bounds anyway.
+    let upper = if len == 0u { 0 } else { len as int - 1 };
+    let subrange = llmdnode(~[lltag(SubrangeTag), lli64(0), lli64(upper)]);
+    return create_composite_type(ArrayTypeTag, ~"", file_node.node, 0,
+                                 elem_size * (len as int) * 8,
+                                 elem_align * 8, 0, 0,
+                                 option::Some(elem_ty_md.node),
+                                 option::Some(~[subrange]));
+}
+
+// The contents of a `~[T]`, `@[T]` or string box
+fn create_vec(cx: @crate_ctxt, vec_t: ty::t, elem_t: ty::t, span: span)
+    -> @metadata<tydesc_md> {
+    let size_t_type = create_ty(cx, ty::mk_uint(cx.tcx), span);
+    let data = create_array(cx, elem_t, 0u, span);
+    let llty = T_vec(cx, type_of::type_of(cx, elem_t));
+    let llnode = create_struct(cx, ty_to_str(cx.tcx, vec_t), llty,
+                               ~[(~"fill", size_t_type.node),
+                                 (~"alloc", size_t_type.node),
+                                 (~"data", data)],
+                               span);
     return @{node: llnode, data: {hash: ty::type_id(vec_t)}};
 }
 
-fn create_ty(_cx: @crate_ctxt, _t: ty::t, _ty: @ast::Ty)
+fn create_slice(cx: @crate_ctxt, slice_t: ty::t, elem_t: ty::t, span: span)
     -> @metadata<tydesc_md> {
-    /*let cache = get_cache(cx);
-    match cached_metadata::<@metadata<tydesc_md>>(
-        cache, tg, {|md| t == md.data.hash}) {
-      option::Some(md) { return md; }
-      option::None {}
-    }*/
-
-    /* FIXME (#2012): disabled this code as part of the patch that moves
-     * recognition of named builtin types into resolve. I tried to fix
-     * it, but it seems to already be broken -- it's only called when
-     * --xg is given, and compiling with --xg fails on trivial programs.
-     *
-     * Generating an ast::ty from a ty::t seems like it should not be
-     * needed. It is only done to track spans, but you will not get the
-     * right spans anyway -- types tend to refer to stuff defined
-     * elsewhere, not be self-contained.
-     */
-
-    die!();
-    /*
-    fn t_to_ty(cx: crate_ctxt, t: ty::t, span: span) -> @ast::ty {
-        let ty = match ty::get(t).struct {
-          ty::ty_nil { ast::ty_nil }
-          ty::ty_bot { ast::ty_bot }
-          ty::ty_bool { ast::ty_bool }
-          ty::ty_int(t) { ast::ty_int(t) }
-          ty::ty_float(t) { ast::ty_float(t) }
-          ty::ty_uint(t) { ast::ty_uint(t) }
-          ty::ty_box(mt) { ast::ty_box({ty: t_to_ty(cx, mt.ty, span),
-                                        mutbl: mt.mutbl}) }
-          ty::ty_uniq(mt) { ast::ty_uniq({ty: t_to_ty(cx, mt.ty, span),
-                                          mutbl: mt.mutbl}) }
-          ty::ty_rec(fields) {
-            let fs = ~[];
-            for field in fields {
-                fs.push({node: {ident: field.ident,
-                               mt: {ty: t_to_ty(cx, field.mt.ty, span),
-                                    mutbl: field.mt.mutbl}},
-                        span: span});
-            }
-            ast::ty_rec(fs)
-          }
-          ty::ty_vec(mt) { ast::ty_vec({ty: t_to_ty(cx, mt.ty, span),
-                                        mutbl: mt.mutbl}) }
-          _ {
-            cx.sess.span_bug(span, "t_to_ty: Can't handle this type");
-          }
+    let elem_ty_md = create_ty(cx, elem_t, span);
+    let data_ptr = create_pointer_type(cx, ty::mk_imm_ptr(cx.tcx, elem_t),
+                                       span, elem_ty_md);
+    let len_type = create_ty(cx, ty::mk_uint(cx.tcx), span);
+    let llnode = create_struct(cx, ty_to_str(cx.tcx, slice_t),
+                               type_of::type_of(cx, slice_t),
+                               ~[(~"data", data_ptr.node),
+                                 (~"len", len_type.node)],
+                               span);
+    return @{node: llnode, data: {hash: ty::type_id(slice_t)}};
+}
+
+/*
+ * Enums are described according to their representation (see
+ * type_of::fill_type_of_enum). The discriminant is an enumeration type
+ * whose enumerators are the variant names, so a C-like enum is just that.
+ * Otherwise the enum is a structure of the discriminant followed by a
+ * union of one structure per variant. An enum with a single variant has
+ * no discriminant and is described as the structure of that variant.
+ */
+fn create_enum(cx: @crate_ctxt, t: ty::t, did: ast::def_id,
+               substs: &ty::substs, span: span) -> @metadata<tydesc_md> {
+    let fname = filename_from_span(cx, span);
+    let file_node = create_file(cx, fname).node;
+    let name = ty_to_str(cx.tcx, t);
+    let variants = ty::substd_enum_variants(cx.tcx, did, substs);
+    let llty = type_of::type_of(cx, t);
+    let univariant = ty::enum_is_univariant(cx.tcx, did);
+    let payload_size = machine::static_size_of_enum(cx, t);
+
+    let llnode = if univariant && payload_size != 0u {
+        let args = /*bad*/copy variants[0].args;
+        let llargs = T_struct(args.map(|a| type_of::type_of(cx, *a)));
+        create_struct(cx, name, llargs,
+                      positional_members(cx, args, span), span)
+    } else {
+        let lldiscrim = T_enum_discrim(cx);
+        let discrim_size = machine::llsize_of_real(cx, lldiscrim) as int;
+        let discrim_align = machine::llalign_of_min(cx, lldiscrim) as int;
+        let enumerators = do variants.map |v| {
+            llmdnode(~[lltag(EnumeratorTag),
+                       llstr(cx.sess.str_of(v.name)),
+                       lli64(v.disr_val)])
         };
-        return @{node: ty, span: span};
+        let discrim = create_composite_type(EnumerationTypeTag,
+                                            copy name, file_node, 0,
+                                            discrim_size * 8,
+                                            discrim_align * 8, 0, 0,
+                                            option::None,
+                                            option::Some(enumerators));
+        if payload_size == 0u {
+            discrim
+        } else {
+            let cases = do variants.map |v| {
+                let llargs = T_struct(v.args.map(|a| {
+                    type_of::type_of(cx, *a)
+                }));
+                let case = create_struct(cx, cx.sess.str_of(v.name), llargs,
+                                         positional_members(cx, v.args,
+                                                            span),
+                                         span);
+                create_derived_type(MemberTag, file_node,
+                                    cx.sess.str_of(v.name), 0,
+                                    machine::llsize_of_real(cx, llargs)
+                                        as int * 8,
+                                    machine::llalign_of_min(cx, llargs)
+                                        as int * 8,
+                                    0, case)
+            };
+            let union = create_composite_type(UnionTypeTag, ~"", file_node,
+                                              0, payload_size as int * 8,
+                                              8, 0, 0, option::None,
+                                              option::Some(cases));
+            create_struct(cx, name, llty,
+                          ~[(~"discr", discrim), (~"variants", union)],
+                          span)
+        }
+    };
+    add_named_metadata(cx, ~"llvm.dbg.ty", llnode);
+    return @{node: llnode, data: {hash: ty::type_id(t)}};
+}
+
+// A closure is a pair of the code pointer and the environment box. What
+// the environment holds depends on the closure, so it is left opaque here;
+// the captured variables are described inside the closure itself (see
+// create_captured_var).
+fn create_fn_pair(cx: @crate_ctxt, t: ty::t, span: span)
+    -> @metadata<tydesc_md> {
+    let ptr_type = create_ty(cx, ty::mk_nil_ptr(cx.tcx), span);
+    let llnode = create_struct(cx, ty_to_str(cx.tcx, t),
+                               type_of::type_of(cx, t),
+                               ~[(~"code", ptr_type.node),
+                                 (~"env", ptr_type.node)],
+                               span);
+    return @{node: llnode, data: {hash: ty::type_id(t)}};
+}
+
+// Describes `t` as a structure of the right size without any members
+fn create_opaque_type(cx: @crate_ctxt, t: ty::t, span: span)
+    -> @metadata<tydesc_md> {
+    let llnode = create_struct(cx, ty_to_str(cx.tcx, t),
+                               type_of::type_of(cx, t), ~[], span);
+    return @{node: llnode, data: {hash: ty::type_id(t)}};
+}
+
+// A declaration of `t`, for when a recursive type refers to itself. The
+// debugger finds the full description by name.
+fn create_forward_decl(cx: @crate_ctxt, t: ty::t, span: span)
+    -> @metadata<tydesc_md> {
+    let fname = filename_from_span(cx, span);
+    let file_node = create_file(cx, fname);
+    let llnode = create_composite_type(StructureTypeTag,
+                                       ty_to_str(cx.tcx, t),
+                                       file_node.node, 0, 0, 0, 0,
+                                       DIFlagFwdDecl, option::None,
+                                       option::None);
+    return @{node: llnode, data: {hash: ty::type_id(t)}};
+}
+
+fn create_ty(cx: @crate_ctxt, t: ty::t, span: span)
+    -> @metadata<tydesc_md> {
+    let dbg_cx = (/*bad*/copy cx.dbg_cx).get();
+    let t_id = ty::type_id(t);
+    match dbg_cx.types.find(&t_id) {
+      option::Some(md) => return md,
+      option::None => ()
     }
+    if dbg_cx.pending.contains_key_ref(&t_id) {
+        return create_forward_decl(cx, t, span);
+    }
+    dbg_cx.pending.insert(t_id, ());
 
-    match ty.node {
-      ast::ty_box(mt) {
-        let inner_t = match ty::get(t).struct {
-          ty::ty_box(boxed) { boxed.ty }
-          _ { cx.sess.span_bug(ty.span, "t_to_ty was incoherent"); }
-        };
-        let md = create_ty(cx, inner_t, mt.ty);
-        let box = create_boxed_type(cx, t, inner_t, ty.span, md);
-        return create_pointer_type(cx, t, ty.span, box);
+    let md = match /*bad*/copy ty::get(t).sty {
+      ty::ty_nil | ty::ty_bot | ty::ty_bool | ty::ty_int(_) |
+      ty::ty_uint(_) | ty::ty_float(_) => {
+        create_basic_type(cx, t, span)
       }
-
-      ast::ty_uniq(mt) {
-        let inner_t = match ty::get(t).struct {
-          ty::ty_uniq(boxed) { boxed.ty }
-          // Hoping we'll have a way to eliminate this check soon.
-          _ { cx.sess.span_bug(ty.span, "t_to_ty was incoherent"); }
-        };
-        let md = create_ty(cx, inner_t, mt.ty);
-        return create_pointer_type(cx, t, ty.span, md);
+      ty::ty_estr(ty::vstore_uniq) | ty::ty_estr(ty::vstore_box) => {
+        let mt = ty::mt {ty: ty::mk_u8(cx.tcx), mutbl: ast::m_imm};
+        let contents = ty::mk_unboxed_vec(cx.tcx, mt);
+        let box = create_boxed_type(cx, t, contents, span,
+                                    create_ty(cx, contents, span));
+        create_pointer_type(cx, t, span, box)
       }
-
-      ast::ty_infer {
-        let inferred = t_to_ty(cx, t, ty.span);
-        return create_ty(cx, t, inferred);
+      ty::ty_evec(mt, ty::vstore_uniq) | ty::ty_evec(mt, ty::vstore_box) => {
+        let contents = ty::mk_unboxed_vec(cx.tcx, mt);
+        let box = create_boxed_type(cx, t, contents, span,
+                                    create_ty(cx, contents, span));
+        create_pointer_type(cx, t, span, box)
       }
-
-      ast::ty_rec(fields) {
-        return create_record(cx, t, fields, ty.span);
+      ty::ty_unboxed_vec(mt) => create_vec(cx, t, mt.ty, span),
+      ty::ty_estr(ty::vstore_slice(_)) => {
+        create_slice(cx, t, ty::mk_u8(cx.tcx), span)
       }
-
-      ast::ty_vec(mt) {
-        let inner_t = ty::sequence_element_type(cx.tcx, t);
-        let inner_ast_t = t_to_ty(cx, inner_t, mt.ty.span);
-        let v = create_vec(cx, t, inner_t, ty.span, inner_ast_t);
-        return create_pointer_type(cx, t, ty.span, v);
+      ty::ty_evec(mt, ty::vstore_slice(_)) => {
+        create_slice(cx, t, mt.ty, span)
       }
-
-      ast::ty_path(_, id) {
-        match cx.tcx.def_map.get(id) {
-          ast::def_prim_ty(pty) {
-            return create_basic_type(cx, t, pty, ty.span);
-          }
-          _ {}
-        }
+      ty::ty_estr(ty::vstore_fixed(n)) => {
+        // +1 for the trailing null
+        let node = create_array(cx, ty::mk_u8(cx.tcx), n + 1u, span);
+        @{node: node, data: {hash: t_id}}
       }
-
-      _ {}
+      ty::ty_evec(mt, ty::vstore_fixed(n)) => {
+        @{node: create_array(cx, mt.ty, n, span), data: {hash: t_id}}
+      }
+      ty::ty_box(mt) | ty::ty_uniq(mt) => {
+        let box = create_boxed_type(cx, t, mt.ty, span,
+                                    create_ty(cx, mt.ty, span));
+        create_pointer_type(cx, t, span, box)
+      }
+      ty::ty_ptr(mt) | ty::ty_rptr(_, mt) => {
+        create_pointer_type(cx, t, span, create_ty(cx, mt.ty, span))
+      }
+      ty::ty_rec(fields) => {
+        // Records are wrapped in an extra structure, like structs
+        let llty = struct_elt(type_of::type_of(cx, t), 0u);
+        let members = do fields.map |f| {
+            (cx.sess.str_of(f.ident), create_ty(cx, f.mt.ty, span).node)
+        };
+        let llnode = create_struct(cx, ty_to_str(cx.tcx, t), llty,
+                                   members, span);
+        @{node: llnode, data: {hash: t_id}}
+      }
+      ty::ty_struct(did, ref substs) => {
+        let llty = struct_elt(type_of::type_of(cx, t), 0u);
+        let fields = ty::lookup_struct_fields(cx.tcx, did);
+        let members = do fields.map |f| {
+            let field_t = ty::lookup_field_type(cx.tcx, did, f.id, substs);
+            (cx.sess.str_of(f.ident), create_ty(cx, field_t, span).node)
+        };
+        let llnode = create_struct(cx, ty_to_str(cx.tcx, t), llty,
+                                   members, span);
+        add_named_metadata(cx, ~"llvm.dbg.ty", llnode);
+        @{node: llnode, data: {hash: t_id}}
+      }
+      ty::ty_tup(elts) => {
+        let llnode = create_struct(cx, ty_to_str(cx.tcx, t),
+                                   type_of::type_of(cx, t),
+                                   positional_members(cx, elts, span),
+                                   span);
+        @{node: llnode, data: {hash: t_id}}
+      }
+      ty::ty_enum(did, ref substs) => create_enum(cx, t, did, substs, span),
+      ty::ty_bare_fn(_) | ty::ty_closure(_) => create_fn_pair(cx, t, span),
+      _ => create_opaque_type(cx, t, span)
     };
-    */
+
+    dbg_cx.pending.remove(&t_id);
+    dbg_cx.types.insert(t_id, md);
+    return md;
 }
 
 fn filename_from_span(cx: @crate_ctxt, sp: codemap::span) -> ~str {
@@ -649,114 +784,83 @@ fn create_var(type_tag: int, context: ValueRef, +name: ~str, file: ValueRef,
     return llmdnode(lldata);
 }
 
-pub fn create_local_var(bcx: block, local: @ast::local)
-    -> @metadata<local_var_md> {
+// Declares the variable `name` of type `t`, stored at `llptr`
+fn declare_var(bcx: block, tag: int, +name: ~str, t: ty::t, sp: span,
+               llptr: ValueRef) {
     unsafe {
         let cx = bcx.ccx();
-        let cache = get_cache(cx);
-        let tg = AutoVariableTag;
-        match cached_metadata::<@metadata<local_var_md>>(
-            cache, tg, |md| md.data.id == local.node.id) {
-          option::Some(md) => return md,
-          option::None => ()
-        }
-
-        let name = match local.node.pat.node {
-          ast::pat_ident(_, pth, _) => ast_util::path_to_ident(pth),
-          // FIXME this should be handled (#2533)
-          _ => die!(~"no single variable name for local")
-        };
-        let loc = cx.sess.codemap.lookup_char_pos(local.span.lo);
-        let ty = node_id_type(bcx, local.node.id);
-        let tymd = create_ty(cx, ty, local.node.ty);
+        let loc = cx.sess.codemap.lookup_char_pos(sp.lo);
+        let tymd = create_ty(cx, t, sp);
         let filemd = create_file(cx, /*bad*/copy loc.file.name);
-        let context = match bcx.parent {
-            None => create_function(bcx.fcx).node,
-            Some(_) => create_block(bcx).node
-        };
-        let mdnode = create_var(tg, context, cx.sess.str_of(name),
-                                filemd.node, loc.line as int, tymd.node);
-        let mdval = @{node: mdnode, data: {id: local.node.id}};
-        update_cache(cache, AutoVariableTag, local_var_metadata(mdval));
-
-        let llptr = match bcx.fcx.lllocals.find(&local.node.id) {
-          option::Some(local_mem(v)) => v,
-          option::Some(_) => {
-            bcx.tcx().sess.span_bug(local.span, ~"local is bound to \
-                    something weird");
-          }
-          option::None => {
-            match bcx.fcx.lllocals.get(&local.node.pat.id) {
-              local_imm(v) => v,
-              _ => bcx.tcx().sess.span_bug(local.span, ~"local is bound to \
-                                                         something weird")
-            }
-          }
-        };
+        let mdnode = create_var(tag, scope_of(bcx), name, filemd.node,
+                                loc.line as int, tymd.node);
         let declargs = ~[llmdnode(~[llptr]), mdnode];
         trans::build::Call(bcx, cx.intrinsics.get(&~"llvm.dbg.declare"),
                            declargs);
-        return mdval;
     }
 }
 
-pub fn create_arg(bcx: block, arg: ast::arg, sp: span)
-    -> Option<@metadata<argument_md>> {
-    unsafe {
-        let fcx = bcx.fcx, cx = fcx.ccx;
-        let cache = get_cache(cx);
-        let tg = ArgVariableTag;
-        match cached_metadata::<@metadata<argument_md>>(
-            cache, ArgVariableTag, |md| md.data.id == arg.id) {
-          option::Some(md) => return Some(md),
-          option::None => ()
+pub fn create_local_var(bcx: block, local: @ast::local) {
+    let cx = bcx.ccx();
+    do pat_bindings(cx.tcx.def_map, local.node.pat) |_, id, sp, path| {
+        // A local bound by a plain identifier lives under the local's id
+        let llptr = match bcx.fcx.lllocals.find(&id) {
+          option::Some(v) => option::Some(v),
+          option::None => bcx.fcx.lllocals.find(&local.node.id)
+        };
+        match llptr {
+          option::Some(local_mem(v)) | option::Some(local_imm(v)) => {
+            let name = cx.sess.str_of(path.idents.last());
+            let t = node_id_type(bcx, id);
+            declare_var(bcx, AutoVariableTag, name, t, sp, v);
+          }
+          option::None => {
+            bcx.tcx().sess.span_bug(sp, ~"local is not bound to anything")
+          }
         }
+    }
+}
 
-        let loc = cx.sess.codemap.lookup_char_pos(sp.lo);
-        let ty = node_id_type(bcx, arg.id);
-        let tymd = create_ty(cx, ty, arg.ty);
-        let filemd = create_file(cx, /*bad*/copy loc.file.name);
-        let context = create_function(bcx.fcx);
-
-        match arg.pat.node {
-            ast::pat_ident(_, path, _) => {
-                // XXX: This is wrong; it should work for multiple bindings.
-                let mdnode = create_var(tg,
-                                        context.node,
-                                        cx.sess.str_of(path.idents.last()),
-                                        filemd.node,
-                                        loc.line as int,
-                                        tymd.node);
-
-                let mdval = @{node: mdnode, data: {id: arg.id}};
-                update_cache(cache, tg, argument_metadata(mdval));
-
-                let llptr = match fcx.llargs.get(&arg.id) {
-                  local_mem(v) | local_imm(v) => v,
-                };
-                let declargs = ~[llmdnode(~[llptr]), mdnode];
-                trans::build::Call(bcx,
-                                   cx.intrinsics.get(&~"llvm.dbg.declare"),
-                                   declargs);
-                return Some(mdval);
-            }
-            _ => {
-                return None;
-            }
+pub fn create_arg(bcx: block, arg: ast::arg) {
+    let fcx = bcx.fcx, cx = fcx.ccx;
+    do pat_bindings(cx.tcx.def_map, arg.pat) |_, id, sp, path| {
+        let llptr = match fcx.llargs.find(&id) {
+          option::Some(v) => option::Some(v),
+          option::None => fcx.llargs.find(&arg.id)
+        };
+        match llptr {
+          option::Some(local_mem(v)) | option::Some(local_imm(v)) => {
+            let name = cx.sess.str_of(path.idents.last());
+            let t = node_id_type(bcx, id);
+            declare_var(bcx, ArgVariableTag, name, t, sp, v);
+          }
+          option::None => {
+            bcx.tcx().sess.span_bug(sp, ~"argument is not bound to anything")
+          }
         }
     }
 }
 
+// Declares a variable captured by a closure, as found at `llptr` in the
+// closure's environment, under the name it was bound with.
+pub fn create_captured_var(bcx: block, cap_var: &moves::CaptureVar,
+                           llptr: ValueRef) {
+    let cx = bcx.ccx();
+    let def_id = ast_util::def_id_of_def(cap_var.def);
+    let name = cx.sess.str_of(cap_var.ident);
+    declare_var(bcx, AutoVariableTag, name, node_id_type(bcx, def_id.node),
+                cap_var.span, llptr);
+}
+
 pub fn update_source_pos(cx: block, s: span) {
     if !cx.sess().opts.debuginfo {
         return;
     }
     let cm = cx.sess().codemap;
-    let blockmd = create_block(cx);
     let loc = cm.lookup_char_pos(s.lo);
     let scopedata = ~[lli32(loc.line.to_int()),
                      lli32(loc.col.to_int()),
-                     blockmd.node,
+                     scope_of(cx),
                      llnull()];
     let dbgscope = llmdnode(scopedata);
     unsafe {
@@ -810,9 +914,10 @@ pub fn create_function(fcx: fn_ctxt) -> @metadata<subprogram_md> {
     log(debug, ident);
     log(debug, id);
 
+    // Every instance of a generic function gets its own subprogram
     let cache = get_cache(cx);
     match cached_metadata::<@metadata<subprogram_md>>(
-        cache, SubprogramTag, |md| md.data.id == id) {
+        cache, SubprogramTag, |md| md.data.llfn == fcx.llfn) {
       option::Some(md) => return md,
       option::None => ()
     }
@@ -822,13 +927,23 @@ pub fn create_function(fcx: fn_ctxt) -> @metadata<subprogram_md> {
     let ty_node = if cx.sess.opts.extra_debuginfo {
         match ret_ty.node {
           ast::ty_nil => llnull(),
-          _ => create_ty(cx, ty::node_id_to_type(cx.tcx, id), ret_ty).node
+          _ => {
+            let fn_ty = ty::node_id_to_type(cx.tcx, id);
+            let ret_t = match fcx.param_substs {
+              Some(ref substs) => {
+                ty::subst_tps(cx.tcx, substs.tys, substs.self_ty,
+                              ty::ty_fn_ret(fn_ty))
+              }
+              None => ty::ty_fn_ret(fn_ty)
+            };
+            create_ty(cx, ret_t, ret_ty.span).node
+          }
         }
     } else {
         llnull()
     };
     let sub_node = create_composite_type(SubroutineTag, ~"", file_node, 0, 0,
-                                         0, 0, option::None,
+                                         0, 0, 0, option::None,
                                          option::Some(~[ty_node]));
 
     let fn_metadata = ~[lltag(SubprogramTag),
@@ -855,7 +970,7 @@ pub fn create_function(fcx: fn_ctxt) -> @metadata<subprogram_md> {
                       ];
     let val = llmdnode(fn_metadata);
     add_named_metadata(cx, ~"llvm.dbg.sp", val);
-    let mdval = @{node: val, data: {id: id}};
+    let mdval = @{node: val, data: {id: id, llfn: fcx.llfn}};
     update_cache(cache, SubprogramTag, subprogram_metadata(mdval));
 
     return mdval;
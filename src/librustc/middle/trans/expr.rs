@@ -559,7 +559,8 @@ fn trans_rvalue_dps_unadjusted(bcx: block, expr: @ast::expr,
         ast::expr_vstore(contents, ast::expr_vstore_fixed(_)) => {
             return tvec::trans_fixed_vstore(bcx, expr, contents, dest);
         }
-        ast::expr_vec(*) | ast::expr_repeat(*) => {
+        ast::expr_vec(*) | ast::expr_repeat(*) |
+        ast::expr_lit(@codemap::spanned {node: ast::lit_binary(_), _}) => {
             return tvec::trans_fixed_vstore(bcx, expr, expr, dest);
         }
         ast::expr_fn(_, ref decl, ref body, _) |
@@ -169,6 +169,16 @@ pub fn llalign_of_min(cx: @crate_ctxt, t: TypeRef) -> uint {
     }
 }
 
+// Returns the offset in bytes of element `n` of the LLVM structure type
+// `llstructty`, taking the padding required by the target into account.
+pub fn llelement_offset(cx: @crate_ctxt, llstructty: TypeRef,
+                        n: uint) -> uint {
+    unsafe {
+        return llvm::LLVMOffsetOfElement(cx.td.lltd, llstructty,
+                                         n as c_uint) as uint;
+    }
+}
+
 // Returns the "default" alignment of t, which is calculated by casting
 // null to a record containing a single-bit followed by a t value, then
 // doing gep(0,1) to get at the trailing (and presumably padded) t cell.
@@ -0,0 +1,94 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Splits a crate into codegen units for `-Z codegen-units=N`.
+//
+// Each unit is translated into its own LLVM module, which is then optimised
+// and emitted as an object file on its own thread. The partition is by
+// module: the items directly in the crate root form one group and every
+// top-level module (with everything nested in it) forms another. Groups are
+// handed out, largest first, to whichever unit has the fewest items so far.
+//
+// Unit 0 is the primary unit. It is the only one that defines the crate map,
+// the enum discriminant globals and the metadata; the others just declare
+// them. Items are only defined in the unit that owns them and referenced
+// from the others through external declarations, which is why trans stops
+// giving items internal linkage as soon as there is more than one unit.
+
+use core::prelude::*;
+
+use core::uint;
+use core::vec;
+use std::oldmap::HashMap;
+use std::sort;
+use syntax::ast;
+
+pub struct CodegenUnit {
+    index: uint,
+    // How many units the crate was split into
+    count: uint,
+    // Module-level items defined in this unit
+    items: HashMap<ast::node_id, ()>,
+}
+
+pub impl CodegenUnit {
+    fn is_primary(&self) -> bool { self.index == 0u }
+
+    fn owns_item(&self, id: ast::node_id) -> bool {
+        self.count == 1u || self.items.contains_key_ref(&id)
+    }
+}
+
+// Collects the ids of all non-module items in `m`, recursively
+fn collect_items(m: &ast::_mod, ids: &mut ~[ast::node_id]) {
+    for m.items.each |item| {
+        match item.node {
+          ast::item_mod(ref sub) => collect_items(sub, ids),
+          _ => ids.push(item.id)
+        }
+    }
+}
+
+pub fn partition_crate(crate: &ast::crate, n: uint) -> ~[@CodegenUnit] {
+    let module = &crate.node.module;
+
+    // One group for the crate root, and one for each top-level module
+    let mut groups = ~[~[]];
+    for module.items.each |item| {
+        match item.node {
+          ast::item_mod(ref sub) => {
+            let mut ids = ~[];
+            collect_items(sub, &mut ids);
+            groups.push(move ids);
+          }
+          _ => groups[0].push(item.id)
+        }
+    }
+
+    let count = uint::max(1u, uint::min(n, groups.len()));
+    let units = do vec::from_fn(count) |i| {
+        @CodegenUnit { index: i, count: count, items: HashMap() }
+    };
+    let mut sizes = vec::from_elem(count, 0u);
+
+    let by_size = |a: &uint, b: &uint| groups[*a].len() >= groups[*b].len();
+    let order = sort::merge_sort(vec::from_fn(groups.len(), |i| i), by_size);
+    for order.each |g| {
+        let mut smallest = 0u;
+        for uint::range(1u, count) |i| {
+            if sizes[i] < sizes[smallest] { smallest = i; }
+        }
+        for groups[*g].each |id| {
+            units[smallest].items.insert(*id, ());
+        }
+        sizes[smallest] += groups[*g].len();
+    }
+    units
+}
@@ -368,6 +368,21 @@ pub fn write_content(bcx: block,
                 }
             }
         }
+        ast::expr_lit(@codemap::spanned {
+            node: ast::lit_binary(bytes), _
+        }) => {
+            match dest {
+                Ignore => {
+                    return bcx;
+                }
+                SaveIn(lldest) => {
+                    let llbytes = C_uint(bcx.ccx(), bytes.len());
+                    let llconst = C_binary(bcx.ccx(), /*bad*/copy *bytes);
+                    base::call_memcpy(bcx, lldest, llconst, llbytes);
+                    return bcx;
+                }
+            }
+        }
         ast::expr_vec(elements, _) => {
             match dest {
                 Ignore => {
@@ -464,6 +479,9 @@ pub fn elements_required(bcx: block, content_expr: @ast::expr) -> uint {
         ast::expr_lit(@codemap::spanned { node: ast::lit_str(s), _ }) => {
             s.len() + 1
         },
+        ast::expr_lit(@codemap::spanned {
+            node: ast::lit_binary(bytes), _
+        }) => bytes.len(),
         ast::expr_vec(es, _) => es.len(),
         ast::expr_repeat(_, count_expr, _) => {
             ty::eval_repeat_count(bcx.tcx(), count_expr, content_expr.span)
@@ -3152,6 +3152,7 @@ pub fn expr_kind(tcx: ctxt,
         ast::expr_copy(*) |
         ast::expr_repeat(*) |
         ast::expr_lit(@codemap::spanned {node: lit_str(_), _}) |
+        ast::expr_lit(@codemap::spanned {node: ast::lit_binary(_), _}) |
         ast::expr_vstore(_, ast::expr_vstore_slice) |
         ast::expr_vstore(_, ast::expr_vstore_mut_slice) |
         ast::expr_vstore(_, ast::expr_vstore_fixed(_)) |
@@ -3215,6 +3216,12 @@ pub fn expr_kind(tcx: ctxt,
                 expr.span,
                 ~"macro expression remains after expansion");
         }
+
+        ast::expr_err => {
+            tcx.sess.span_bug(
+                expr.span,
+                ~"syntax error remains after parsing");
+        }
     }
 }
 
@@ -4288,6 +4295,12 @@ pub fn eval_repeat_count(tcx: ctxt,
                                 repeat count but found boolean");
             return 0;
         }
+        const_eval::const_binary(_) => {
+            tcx.sess.span_err(span,
+                              ~"expected signed or unsigned integer for \
+                                repeat count but found byte string");
+            return 0;
+        }
 
     }
 }
@@ -25,6 +25,7 @@ use syntax::ast;
 use syntax::ast_util::walk_pat;
 use syntax::ast_util;
 use syntax::codemap::span;
+use syntax::codemap;
 use syntax::print::pprust;
 
 pub fn check_match(fcx: @fn_ctxt,
@@ -334,6 +335,12 @@ pub fn check_pat(pcx: pat_ctxt, pat: @ast::pat, expected: ty::t) {
         fcx.write_ty(pat.id, expected);
       }
       ast::pat_lit(lt) => {
+        match lt.node {
+          ast::expr_lit(@codemap::spanned {node: ast::lit_binary(_), _}) => {
+            tcx.sess.span_err(lt.span, ~"byte strings cannot be matched on");
+          }
+          _ => ()
+        }
         check_expr_has_type(fcx, lt, expected);
         fcx.write_ty(pat.id, fcx.expr_ty(lt));
       }
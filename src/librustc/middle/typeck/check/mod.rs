@@ -933,6 +933,10 @@ pub fn check_lit(fcx: @fn_ctxt, lit: @ast::lit) -> ty::t {
 
     match lit.node {
       ast::lit_str(*) => ty::mk_estr(tcx, ty::vstore_slice(ty::re_static)),
+      ast::lit_binary(bytes) => {
+        ty::mk_evec(tcx, ty::mt {ty: ty::mk_u8(tcx), mutbl: ast::m_imm},
+                    ty::vstore_fixed(bytes.len()))
+      }
       ast::lit_int(_, t) => ty::mk_mach_int(tcx, t),
       ast::lit_uint(_, t) => ty::mk_mach_uint(tcx, t),
       ast::lit_int_unsuffixed(_) => {
@@ -2032,6 +2036,13 @@ pub fn check_expr_with_unifier(fcx: @fn_ctxt,
             let tt = ast_expr_vstore_to_vstore(fcx, ev, str::len(*s), vst);
             ty::mk_estr(tcx, tt)
           }
+          ast::expr_lit(@codemap::spanned {
+            node: ast::lit_binary(bytes), _
+          }) => {
+            let tt = ast_expr_vstore_to_vstore(fcx, ev, bytes.len(), vst);
+            ty::mk_evec(tcx, ty::mt {ty: ty::mk_u8(tcx), mutbl: ast::m_imm},
+                        tt)
+          }
           ast::expr_vec(args, mutbl) => {
             let tt = ast_expr_vstore_to_vstore(fcx, ev, args.len(), vst);
             let mutability;
@@ -2196,6 +2207,7 @@ pub fn check_expr_with_unifier(fcx: @fn_ctxt,
         instantiate_path(fcx, pth, tpt, expr.span, expr.id, region_lb);
       }
       ast::expr_mac(_) => tcx.sess.bug(~"unexpanded macro"),
+      ast::expr_err => tcx.sess.span_bug(expr.span, ~"syntax error"),
       ast::expr_break(_) => { fcx.write_bot(id); bot = true; }
       ast::expr_again(_) => { fcx.write_bot(id); bot = true; }
       ast::expr_ret(expr_opt) => {
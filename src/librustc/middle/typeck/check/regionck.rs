@@ -677,6 +677,7 @@ pub mod guarantor {
             // All of these expressions are rvalues and hence their
             // value is not guaranteed by a region pointer.
             ast::expr_mac(*) |
+            ast::expr_err |
             ast::expr_lit(_) |
             ast::expr_unary(*) |
             ast::expr_addr_of(*) |
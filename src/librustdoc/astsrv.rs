@@ -32,6 +32,7 @@ use rustc::driver::session::Session;
 use rustc::driver::session::{basic_options, options};
 use rustc::driver::session;
 use rustc::front;
+use rustc::metadata::creader;
 use rustc::metadata::filesearch;
 use std::oldmap::HashMap;
 use syntax::ast;
@@ -43,7 +44,10 @@ use syntax;
 
 pub struct Ctxt {
     ast: @ast::crate,
-    ast_map: ast_map::map
+    ast_map: ast_map::map,
+    // The crate store of this session holds the crates named by `extern
+    // mod`, when they could all be found
+    sess: Session
 }
 
 type SrvOwner<T> = fn(srv: Srv) -> T;
@@ -133,14 +137,38 @@ fn build_ctxt(sess: Session,
     use rustc::front::config;
 
     let ast = config::strip_unconfigured_items(ast);
-    let ast = syntax::ext::expand::expand_crate(sess.parse_sess,
-                                                copy sess.opts.cfg, ast);
+    let os = session::sess_os_to_meta_os(sess.targ_cfg.os);
+    let imported_macros = creader::read_exported_macros(
+        sess.diagnostic(), *ast, sess.filesearch, os, false,
+        sess.parse_sess.interner);
+    let (ast, _) = syntax::ext::expand::expand_crate_with_macros(
+        sess.parse_sess, copy sess.opts.cfg, imported_macros, ast);
     let ast = front::test::modify_for_testing(sess, ast);
     let ast_map = ast_map::map_crate(sess.diagnostic(), *ast);
+    read_extern_crates(sess, ast);
 
     Ctxt {
         ast: ast,
         ast_map: ast_map,
+        sess: sess
+    }
+}
+
+/// Loads the crates named by `extern mod` into the session's crate store.
+/// Documentation doesn't need them, so if any is missing none are loaded.
+fn read_extern_crates(sess: Session, ast: @ast::crate) {
+    let os = session::sess_os_to_meta_os(sess.targ_cfg.os);
+    let missing = creader::find_missing_crates(
+        sess.diagnostic(), *ast, sess.filesearch, os, false,
+        sess.parse_sess.interner);
+    if missing.is_empty() {
+        creader::read_crates(sess.diagnostic(), *ast, sess.cstore,
+                             sess.filesearch, os, false,
+                             sess.parse_sess.interner);
+    } else {
+        for missing.each |name| {
+            warn!("can't find crate for `%s`; not linking to it", *name);
+        }
     }
 }
 
@@ -23,8 +23,12 @@ use std::cell::Cell;
 pub enum OutputFormat {
     /// Markdown
     pub Markdown,
+    /// HTML, written directly by rustdoc
+    pub Html,
     /// HTML, via markdown and pandoc
-    pub PandocHtml
+    pub PandocHtml,
+    /// JSON describing the document tree, for other tools
+    pub Json
 }
 
 impl OutputFormat : cmp::Eq {
@@ -55,7 +59,11 @@ pub struct Config {
     output_dir: Path,
     output_format: OutputFormat,
     output_style: OutputStyle,
-    pandoc_cmd: Option<~str>
+    pandoc_cmd: Option<~str>,
+    /// Run the code examples instead of writing documents
+    test: bool,
+    /// Where to look for the documented crate and its dependencies
+    lib_paths: ~[Path]
 }
 
 pub impl Config: Clone {
@@ -66,6 +74,8 @@ fn opt_output_dir() -> ~str { ~"output-dir" }
 fn opt_output_format() -> ~str { ~"output-format" }
 fn opt_output_style() -> ~str { ~"output-style" }
 fn opt_pandoc_cmd() -> ~str { ~"pandoc-cmd" }
+fn opt_test() -> ~str { ~"test" }
+fn opt_lib_path() -> ~str { ~"L" }
 fn opt_help() -> ~str { ~"h" }
 
 fn opts() -> ~[(getopts::Opt, ~str)] {
@@ -73,11 +83,15 @@ fn opts() -> ~[(getopts::Opt, ~str)] {
         (getopts::optopt(opt_output_dir()),
          ~"--output-dir <val>     put documents here"),
         (getopts::optopt(opt_output_format()),
-         ~"--output-format <val>  either 'markdown' or 'html'"),
+         ~"--output-format <val>  markdown, html, native-html or json"),
         (getopts::optopt(opt_output_style()),
          ~"--output-style <val>   either 'doc-per-crate' or 'doc-per-mod'"),
         (getopts::optopt(opt_pandoc_cmd()),
          ~"--pandoc-cmd <val>     the command for running pandoc"),
+        (getopts::optflag(opt_test()),
+         ~"--test                 compile and run the code examples"),
+        (getopts::optmulti(opt_lib_path()),
+         ~"-L <path>              look for libraries here"),
         (getopts::optflag(opt_help()),
          ~"-h                     print help")
     ]
@@ -92,6 +106,12 @@ pub fn usage() {
         println(fmt!("    %s", opt.second()));
     }
     println(~"");
+    println(~"Output formats:\n");
+    println(~"    markdown     markdown, one file per document");
+    println(~"    html         HTML converted from markdown by pandoc");
+    println(~"    native-html  HTML written by rustdoc itself");
+    println(~"    json         the document tree as JSON");
+    println(~"");
 }
 
 pub fn default_config(input_crate: &Path) -> Config {
@@ -100,7 +120,9 @@ pub fn default_config(input_crate: &Path) -> Config {
         output_dir: Path("."),
         output_format: PandocHtml,
         output_style: DocPerMod,
-        pandoc_cmd: None
+        pandoc_cmd: None,
+        test: false,
+        lib_paths: ~[]
     }
 }
 
@@ -201,6 +223,14 @@ fn config_from_opts(
             }
         }
     };
+    let result = do result::chain(result) |config| {
+        let lib_paths = getopts::opt_strs(matches, opt_lib_path());
+        result::Ok(Config {
+            test: getopts::opt_present(matches, opt_test()),
+            lib_paths: lib_paths.map(|s| Path(*s)),
+            .. config
+        })
+    };
     let program_output = Cell(move program_output);
     let result = do result::chain(result) |config| {
         let pandoc_cmd = getopts::opt_maybe_str(matches, opt_pandoc_cmd());
@@ -220,6 +250,8 @@ fn parse_output_format(output_format: &str) -> Result<OutputFormat, ~str> {
     match output_format.to_str() {
       ~"markdown" => result::Ok(Markdown),
       ~"html" => result::Ok(PandocHtml),
+      ~"native-html" => result::Ok(Html),
+      ~"json" => result::Ok(Json),
       _ => result::Err(fmt!("unknown output format '%s'", output_format))
     }
 }
@@ -356,6 +388,22 @@ fn should_set_output_format_to_pandoc_html_if_requested() {
     assert config.get().output_format == PandocHtml;
 }
 
+#[test]
+fn should_set_output_format_to_native_html_if_requested() {
+    let config = test::parse_config(~[
+        ~"rustdoc", ~"crate.rc", ~"--output-format", ~"native-html"
+    ]);
+    assert config.get().output_format == Html;
+}
+
+#[test]
+fn should_set_output_format_to_json_if_requested() {
+    let config = test::parse_config(~[
+        ~"rustdoc", ~"crate.rc", ~"--output-format", ~"json"
+    ]);
+    assert config.get().output_format == Json;
+}
+
 #[test]
 fn should_error_on_bogus_format() {
     let config = test::parse_config(~[
@@ -407,3 +455,31 @@ fn should_set_pandoc_command_when_using_pandoc() {
     let config = test::parse_config(~[~"rustdoc", ~"crate.rc"]);
     assert config.get().pandoc_cmd == Some(~"pandoc");
 }
+
+#[test]
+fn should_not_need_pandoc_for_native_html() {
+    let config = test::parse_config(~[
+        ~"rustdoc", ~"crate.rc", ~"--output-format", ~"native-html"
+    ]);
+    assert config.get().pandoc_cmd == None;
+}
+
+#[test]
+fn should_not_test_by_default() {
+    let config = test::parse_config(~[~"rustdoc", ~"crate.rc"]);
+    assert !config.get().test;
+}
+
+#[test]
+fn should_set_test_if_requested() {
+    let config = test::parse_config(~[~"rustdoc", ~"crate.rc", ~"--test"]);
+    assert config.get().test;
+}
+
+#[test]
+fn should_set_lib_paths_if_provided() {
+    let config = test::parse_config(~[
+        ~"rustdoc", ~"crate.rc", ~"-L", ~"a", ~"-L", ~"b"
+    ]);
+    assert config.get().lib_paths == ~[Path("a"), Path("b")];
+}
@@ -108,7 +108,9 @@ pub type FnDoc = SimpleItemDoc;
 #[deriving_eq]
 pub struct EnumDoc {
     item: ItemDoc,
-    variants: ~[VariantDoc]
+    variants: ~[VariantDoc],
+    // The impls of traits for this type
+    impls: ~[ImplRef]
 }
 
 #[deriving_eq]
@@ -121,7 +123,9 @@ pub struct VariantDoc {
 #[deriving_eq]
 pub struct TraitDoc {
     item: ItemDoc,
-    methods: ~[MethodDoc]
+    methods: ~[MethodDoc],
+    // The impls of this trait, here and in other crates
+    implementors: ~[ImplRef]
 }
 
 #[deriving_eq]
@@ -148,7 +152,16 @@ pub type TyDoc = SimpleItemDoc;
 pub struct StructDoc {
     item: ItemDoc,
     fields: ~[~str],
-    sig: Option<~str>
+    sig: Option<~str>,
+    // The impls of traits for this type
+    impls: ~[ImplRef]
+}
+
+/// An impl of a trait, as listed with the trait and with the type
+#[deriving_eq]
+pub struct ImplRef {
+    trait_ty: ~str,
+    self_ty: ~str
 }
 
 #[deriving_eq]
@@ -195,7 +195,8 @@ fn enumdoc_from_enum(
 ) -> doc::EnumDoc {
     doc::EnumDoc {
         item: itemdoc,
-        variants: variantdocs_from_variants(variants)
+        variants: variantdocs_from_variants(variants),
+        impls: ~[]
     }
 }
 
@@ -255,7 +256,8 @@ fn traitdoc_from_trait(
                 }
               }
             }
-        }
+        },
+        implementors: ~[]
     }
 }
 
@@ -326,7 +328,8 @@ fn structdoc_from_struct(
                     ~"what is an unnamed struct field?")
             }
         },
-        sig: None
+        sig: None,
+        impls: ~[]
     }
 }
 
@@ -0,0 +1,350 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Converts the markdown of doc comments to HTML.
+
+This covers the subset of pandoc's markdown that doc comments use:
+headers, paragraphs, bulleted and numbered lists, indented and fenced
+code blocks, inline code, emphasis and links.  Anything else is passed
+through as text.
+*/
+
+use core::prelude::*;
+
+use core::char;
+use core::str;
+use core::uint;
+use core::vec;
+
+/// Escapes the characters that are special in HTML text and attributes
+pub fn escape(s: &str) -> ~str {
+    let mut out = ~"";
+    for str::each_char(s) |c| {
+        match c {
+          '&' => out += ~"&amp;",
+          '<' => out += ~"&lt;",
+          '>' => out += ~"&gt;",
+          '"' => out += ~"&quot;",
+          c => str::push_char(&mut out, c)
+        }
+    }
+    out
+}
+
+/// Renders a block of markdown
+pub fn render(md: &str) -> ~str {
+    let lines = str::lines_any(md);
+    let mut out = ~"";
+    let mut para = ~[];
+    let mut i = 0u;
+
+    while i < lines.len() {
+        let line = copy lines[i];
+        let trimmed = str::trim(line);
+
+        if trimmed.is_empty() {
+            flush_para(&mut out, &mut para);
+            i += 1;
+        } else if is_fence(trimmed) {
+            flush_para(&mut out, &mut para);
+            let mut code = ~[];
+            i += 1;
+            while i < lines.len() && !is_fence(str::trim(lines[i])) {
+                code.push(copy lines[i]);
+                i += 1;
+            }
+            // Skip the closing fence
+            i += 1;
+            write_code_block(&mut out, code);
+        } else if para.is_empty() && is_indented(line) {
+            let mut code = ~[];
+            while i < lines.len() &&
+                (is_indented(lines[i]) || is_blank_in_code(lines, i)) {
+                code.push(unindent(lines[i]));
+                i += 1;
+            }
+            write_code_block(&mut out, code);
+        } else if str::starts_with(trimmed, "#") {
+            flush_para(&mut out, &mut para);
+            let level = uint::min(count_leading(trimmed, '#'), 6u);
+            let text = str::trim(str::slice(trimmed, level, trimmed.len()));
+            let text = str::trim_right_chars(text, ['#', ' ']);
+            out += fmt!("<h%u>%s</h%u>\n", level, inline(text), level);
+            i += 1;
+        } else if list_marker(trimmed).is_some() {
+            flush_para(&mut out, &mut para);
+            let ordered = char::is_digit(str::char_at(trimmed, 0u));
+            out += if ordered { ~"<ol>\n" } else { ~"<ul>\n" };
+            while i < lines.len() {
+                let trimmed = str::trim(lines[i]);
+                let start = match list_marker(trimmed) {
+                  Some(start) => start,
+                  None => break
+                };
+                let mut item = str::slice(trimmed, start, trimmed.len());
+                i += 1;
+                // Continuation lines are indented under the marker
+                while i < lines.len() && !str::trim(lines[i]).is_empty() &&
+                    list_marker(str::trim(lines[i])).is_none() &&
+                    char::is_whitespace(str::char_at(lines[i], 0u)) {
+                    item += ~" " + str::trim(lines[i]);
+                    i += 1;
+                }
+                out += fmt!("<li>%s</li>\n", inline(item));
+            }
+            out += if ordered { ~"</ol>\n" } else { ~"</ul>\n" };
+        } else {
+            para.push(trimmed);
+            i += 1;
+        }
+    }
+    flush_para(&mut out, &mut para);
+    out
+}
+
+fn flush_para(out: &mut ~str, para: &mut ~[~str]) {
+    if !para.is_empty() {
+        *out += fmt!("<p>%s</p>\n", inline(str::connect(*para, ~"\n")));
+        *para = ~[];
+    }
+}
+
+fn write_code_block(out: &mut ~str, lines: &[~str]) {
+    // Trailing blank lines belong to the text after the block
+    let mut end = lines.len();
+    while end > 0u && str::trim(lines[end - 1u]).is_empty() {
+        end -= 1u;
+    }
+    let code = str::connect(vec::slice(lines, 0u, end), ~"\n");
+    *out += fmt!("<pre><code>%s</code></pre>\n", escape(code));
+}
+
+fn is_fence(line: &str) -> bool {
+    str::starts_with(line, "~~~") || str::starts_with(line, "```")
+}
+
+fn is_indented(line: &str) -> bool {
+    str::starts_with(line, "    ") || str::starts_with(line, "\t")
+}
+
+// A blank line inside an indented code block, i.e. one that is followed by
+// more indented lines
+fn is_blank_in_code(lines: &[~str], i: uint) -> bool {
+    let mut j = i;
+    while j < lines.len() && str::trim(lines[j]).is_empty() {
+        j += 1;
+    }
+    j > i && j < lines.len() && is_indented(lines[j])
+}
+
+fn unindent(line: &str) -> ~str {
+    if str::starts_with(line, "\t") {
+        str::slice(line, 1u, line.len())
+    } else if str::starts_with(line, "    ") {
+        str::slice(line, 4u, line.len())
+    } else {
+        str::from_slice(line)
+    }
+}
+
+fn count_leading(s: &str, c: char) -> uint {
+    let mut n = 0u;
+    for str::each_char(s) |d| {
+        if d != c { break; }
+        n += 1u;
+    }
+    n
+}
+
+// Returns the offset of the text of a list item, if `line` starts one
+fn list_marker(line: &str) -> Option<uint> {
+    if str::starts_with(line, "* ") || str::starts_with(line, "- ") ||
+        str::starts_with(line, "+ ") {
+        return Some(2u);
+    }
+    let mut n = 0u;
+    while n < line.len() && char::is_digit(str::char_at(line, n)) {
+        n += 1u;
+    }
+    if n > 0u && str::starts_with(str::slice(line, n, line.len()), ". ") {
+        Some(n + 2u)
+    } else {
+        None
+    }
+}
+
+/// Renders the inline markup of a span of text
+pub fn inline(s: &str) -> ~str {
+    let cs = str::chars(s);
+    let mut out = ~"";
+    let mut in_em = false;
+    let mut in_strong = false;
+    let mut i = 0u;
+
+    while i < cs.len() {
+        let c = cs[i];
+        if c == '\\' && i + 1u < cs.len() && char::is_ascii(cs[i + 1u]) &&
+            !char::is_alphanumeric(cs[i + 1u]) {
+            out += escape(str::from_char(cs[i + 1u]));
+            i += 2u;
+        } else if c == '`' {
+            match find_char(cs, '`', i + 1u) {
+              Some(end) => {
+                let code = str::from_chars(vec::slice(cs, i + 1u, end));
+                out += fmt!("<code>%s</code>", escape(code));
+                i = end + 1u;
+              }
+              None => {
+                out += ~"`";
+                i += 1u;
+              }
+            }
+        } else if c == '*' && i + 1u < cs.len() && cs[i + 1u] == '*' {
+            out += if in_strong { ~"</strong>" } else { ~"<strong>" };
+            in_strong = !in_strong;
+            i += 2u;
+        } else if (c == '*' || c == '_') && is_em_delim(cs, i, in_em) {
+            out += if in_em { ~"</em>" } else { ~"<em>" };
+            in_em = !in_em;
+            i += 1u;
+        } else if c == '[' {
+            match parse_link(cs, i) {
+              Some((text, url, end)) => {
+                out += fmt!("<a href=\"%s\">%s</a>", escape(url),
+                            inline(text));
+                i = end;
+              }
+              None => {
+                out += ~"[";
+                i += 1u;
+              }
+            }
+        } else if c == '<' && is_autolink(cs, i) {
+            let end = find_char(cs, '>', i).get();
+            let url = str::from_chars(vec::slice(cs, i + 1u, end));
+            out += fmt!("<a href=\"%s\">%s</a>", escape(url), escape(url));
+            i = end + 1u;
+        } else {
+            out += escape(str::from_char(c));
+            i += 1u;
+        }
+    }
+
+    if in_em { out += ~"</em>"; }
+    if in_strong { out += ~"</strong>"; }
+    out
+}
+
+fn find_char(cs: &[char], c: char, start: uint) -> Option<uint> {
+    let mut i = start;
+    while i < cs.len() {
+        if cs[i] == c { return Some(i); }
+        i += 1u;
+    }
+    None
+}
+
+// Emphasis opens before a word and closes after one, so that the
+// underscores in identifiers like `foo_bar` are left alone
+fn is_em_delim(cs: &[char], i: uint, in_em: bool) -> bool {
+    let before = i > 0u && char::is_alphanumeric(cs[i - 1u]);
+    let after = i + 1u < cs.len() && char::is_alphanumeric(cs[i + 1u]);
+    if in_em { before && !after } else { !before && after }
+}
+
+// Parses `[text](url)` starting at `i`, returning the text, the url and the
+// offset just past the link
+fn parse_link(cs: &[char], i: uint) -> Option<(~str, ~str, uint)> {
+    let close = match find_char(cs, ']', i + 1u) {
+      Some(close) => close,
+      None => return None
+    };
+    if close + 1u >= cs.len() || cs[close + 1u] != '(' {
+        return None;
+    }
+    let end = match find_char(cs, ')', close + 2u) {
+      Some(end) => end,
+      None => return None
+    };
+    Some((str::from_chars(vec::slice(cs, i + 1u, close)),
+          str::from_chars(vec::slice(cs, close + 2u, end)),
+          end + 1u))
+}
+
+fn is_autolink(cs: &[char], i: uint) -> bool {
+    match find_char(cs, '>', i) {
+      Some(end) => {
+        let text = str::from_chars(vec::slice(cs, i + 1u, end));
+        (str::starts_with(text, "http://") ||
+         str::starts_with(text, "https://")) &&
+            !str::contains_char(text, ' ')
+      }
+      None => false
+    }
+}
+
+#[test]
+fn should_escape_html() {
+    assert escape("<a href=\"x\">&</a>") ==
+        ~"&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;";
+}
+
+#[test]
+fn should_render_paragraphs() {
+    assert render("one\ntwo\n\nthree") ==
+        ~"<p>one\ntwo</p>\n<p>three</p>\n";
+}
+
+#[test]
+fn should_render_headers() {
+    assert render("# Arguments\n\ntext") ==
+        ~"<h1>Arguments</h1>\n<p>text</p>\n";
+}
+
+#[test]
+fn should_render_indented_code_blocks() {
+    assert render("Example:\n\n    let x = 1;\n\n    x < 2\n\nafter") ==
+        ~"<p>Example:</p>\n\
+          <pre><code>let x = 1;\n\nx &lt; 2</code></pre>\n\
+          <p>after</p>\n";
+}
+
+#[test]
+fn should_render_fenced_code_blocks() {
+    assert render("~~~\nfn main() { }\n~~~") ==
+        ~"<pre><code>fn main() { }</code></pre>\n";
+}
+
+#[test]
+fn should_render_lists() {
+    assert render("* a\n* b\n  continued") ==
+        ~"<ul>\n<li>a</li>\n<li>b continued</li>\n</ul>\n";
+    assert render("1. a\n2. b") ==
+        ~"<ol>\n<li>a</li>\n<li>b</li>\n</ol>\n";
+}
+
+#[test]
+fn should_render_inline_markup() {
+    assert inline("`a<b>` *em* **strong** [link](http://x)") ==
+        ~"<code>a&lt;b&gt;</code> <em>em</em> <strong>strong</strong> \
+          <a href=\"http://x\">link</a>";
+}
+
+#[test]
+fn should_leave_underscores_in_identifiers_alone() {
+    assert inline("call foo_bar_baz") == ~"call foo_bar_baz";
+}
+
+#[test]
+fn should_unescape_backslashes() {
+    // escape_pass doubles backslashes for pandoc
+    assert inline("a\\\\b \\*") == ~"a\\b *";
+}
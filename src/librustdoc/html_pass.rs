@@ -0,0 +1,912 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Generate HTML from a document tree, without going through pandoc.
+
+Every module gets a page (or, with `doc-per-crate`, a section of the crate
+page) with a sidebar listing its contents.  Type names in signatures link
+to the documentation of the type, which for types of other crates is
+assumed to have been generated into a sibling directory named after that
+crate.  A search index over all items is written to `search-index.js`.
+*/
+
+use core::prelude::*;
+
+use astsrv;
+use config;
+use doc::{Item, ItemUtils};
+use doc;
+use html_markdown::{escape, inline, render};
+use markdown_pass::{header_kind, header_text, mods_last};
+use markdown_writer::{WriterFactory, WriterUtils};
+use markdown_writer;
+use pass::Pass;
+use sort_pass;
+
+use core::char;
+use core::hashmap::linear::LinearMap;
+use core::str;
+use core::vec;
+use rustc::metadata::csearch;
+use rustc::metadata::cstore;
+use rustc::metadata::decoder;
+use std::cell::Cell;
+use std::json;
+use std::oldmap::HashMap;
+use syntax::ast;
+
+pub fn mk_pass(writer_factory: WriterFactory, config: config::Config)
+            -> Pass {
+    let writer_factory = Cell(writer_factory);
+    Pass {
+        name: ~"html",
+        f: |srv, doc| run(srv, doc, writer_factory.take(), copy config)
+    }
+}
+
+fn run(
+    srv: astsrv::Srv,
+    doc: doc::Doc,
+    writer_factory: WriterFactory,
+    config: config::Config
+) -> doc::Doc {
+    // With doc-per-crate, modules are written as nested sections after the
+    // other items of their parent
+    let sorted_doc = (sort_pass::mk_pass(
+        ~"mods last", mods_last
+    ).f)(srv.clone(), copy doc);
+
+    let externs = extern_types(srv);
+    let search_index = write_html(sorted_doc, externs, writer_factory,
+                                  copy config);
+    write_assets(&config, search_index);
+    doc
+}
+
+/// A type defined by another crate
+pub struct ExternType {
+    crate: ~str,
+    /// The path of the type within its crate, e.g. `option::Option`
+    path: ~str
+}
+
+fn extern_types(srv: astsrv::Srv) -> ~[ExternType] {
+    do astsrv::exec(srv) |ctxt| {
+        let cstore = ctxt.sess.cstore;
+        let mut crates = ~[];
+        for cstore::iter_crate_data(cstore) |cnum, meta| {
+            crates.push((cnum, copy meta.name));
+        }
+
+        let mut types = ~[];
+        for crates.each |&(cnum, name)| {
+            for csearch::each_path(cstore, cnum) |path, def_like| {
+                match def_like {
+                  decoder::dl_def(ast::def_ty(_)) |
+                  decoder::dl_def(ast::def_struct(_)) => {
+                    types.push(ExternType {
+                        crate: copy name,
+                        path: str::from_slice(path)
+                    });
+                  }
+                  _ => ()
+                }
+            }
+        }
+        types
+    }
+}
+
+/// Where the documentation of the types that signatures can mention lives
+struct Links {
+    /// Link targets, keyed by the path of the type from the crate root, or
+    /// for types of other crates, by the path including the crate name
+    targets: HashMap<~str, ~str>,
+    /// The keys of `targets` for each type name, for resolving names that
+    /// are imported or used unqualified.  Local types take precedence.
+    local_names: HashMap<~str, @mut ~[~str]>,
+    extern_names: HashMap<~str, @mut ~[~str]>
+}
+
+struct Ctxt {
+    config: config::Config,
+    crate_name: ~str,
+    /// The file holding the crate page
+    crate_page: ~str,
+    links: Links,
+    search_index: @mut ~[json::Json]
+}
+
+/**
+ * Writes a page for every module of the crate, or with `doc-per-crate`,
+ * a single page for all of it, and returns the entries of the search
+ * index.
+ */
+pub fn write_html(
+    doc: doc::Doc,
+    externs: &[ExternType],
+    writer_factory: WriterFactory,
+    config: config::Config
+) -> ~[json::Json] {
+    let topmod = doc.cratemod();
+    let crate_page = markdown_writer::make_filename(
+        copy config, doc::CratePage(doc.CrateDoc())).to_str();
+    let ctxt = Ctxt {
+        config: config,
+        crate_name: topmod.name(),
+        crate_page: crate_page,
+        links: Links {
+            targets: HashMap(),
+            local_names: HashMap(),
+            extern_names: HashMap()
+        },
+        search_index: @mut ~[]
+    };
+
+    for externs.each |ext| {
+        add_extern_link(&ctxt, ext);
+    }
+    index_mod(&ctxt, &topmod);
+
+    write_mod_page(&ctxt, &writer_factory, &topmod,
+                   doc::CratePage(doc.CrateDoc()));
+    copy *ctxt.search_index
+}
+
+fn add_name(names: &HashMap<~str, @mut ~[~str]>, name: ~str, key: ~str) {
+    match names.find(&name) {
+      Some(keys) => keys.push(key),
+      None => { names.insert(name, @mut ~[key]); }
+    }
+}
+
+fn add_extern_link(ctxt: &Ctxt, ext: &ExternType) {
+    let segs = str::split_str(ext.path, ~"::");
+    let page = if segs.len() > 1u {
+        str::connect(vec::init(segs), ~"_") + ~".html"
+    } else {
+        ~"index.html"
+    };
+    let key = ext.crate + ~"::" + ext.path;
+    let url = fmt!("../%s/%s#type.%s", ext.crate, page,
+                   str::connect(segs, ~"."));
+    ctxt.links.targets.insert(copy key, url);
+    add_name(&ctxt.links.extern_names, vec::last(segs), key);
+}
+
+fn is_mod_page(ctxt: &Ctxt, doc: &doc::ModDoc) -> bool {
+    doc.id() == ast::crate_node_id ||
+        ctxt.config.output_style == config::DocPerMod
+}
+
+/// The file documenting the items of a module
+fn mod_page(ctxt: &Ctxt, doc: &doc::ModDoc) -> ~str {
+    if doc.id() == ast::crate_node_id ||
+        ctxt.config.output_style == config::DocPerCrate {
+        copy ctxt.crate_page
+    } else {
+        markdown_writer::make_filename(
+            copy ctxt.config, doc::ItemPage(doc::ModTag(copy *doc))).to_str()
+    }
+}
+
+fn nmod_page(ctxt: &Ctxt, parent_page: &str, doc: &doc::NmodDoc) -> ~str {
+    if ctxt.config.output_style == config::DocPerCrate {
+        str::from_slice(parent_page)
+    } else {
+        markdown_writer::make_filename(
+            copy ctxt.config, doc::ItemPage(doc::NmodTag(copy *doc))).to_str()
+    }
+}
+
+fn item_anchor(doc: &doc::ItemTag) -> ~str {
+    let path = str::connect(doc.path() + ~[doc.name()], ~".");
+    match *doc {
+      doc::ModTag(_) | doc::NmodTag(_) => ~"mod." + path,
+      doc::FnTag(_) => ~"fn." + path,
+      doc::ConstTag(_) => ~"const." + path,
+      doc::EnumTag(_) | doc::TraitTag(_) | doc::TyTag(_)
+      | doc::StructTag(_) => ~"type." + path,
+      doc::ImplTag(_) => fmt!("impl.%d", doc.id())
+    }
+}
+
+/// The link to an item of the module documented on `page`
+fn item_link(ctxt: &Ctxt, page: &str, doc: &doc::ItemTag) -> ~str {
+    match *doc {
+      doc::ModTag(ref m) if is_mod_page(ctxt, m) => mod_page(ctxt, m),
+      doc::NmodTag(ref n)
+      if ctxt.config.output_style == config::DocPerMod => {
+        nmod_page(ctxt, page, n)
+      }
+      _ => fmt!("%s#%s", page, item_anchor(doc))
+    }
+}
+
+fn impl_label(doc: &doc::ImplDoc) -> ~str {
+    let self_ty = doc.self_ty.get_or_default(~"");
+    if doc.trait_types.is_empty() {
+        self_ty
+    } else {
+        fmt!("%s for %s", str::connect(doc.trait_types, ~", "), self_ty)
+    }
+}
+
+fn item_label(doc: &doc::ItemTag) -> ~str {
+    match *doc {
+      doc::ImplTag(ref i) => impl_label(i),
+      _ => doc.name()
+    }
+}
+
+// Records the link targets of the types of a module and its submodules,
+// and their search index entries
+fn index_mod(ctxt: &Ctxt, doc: &doc::ModDoc) {
+    let page = mod_page(ctxt, doc);
+    for doc.items.each |item| {
+        let link = item_link(ctxt, page, item);
+        match *item {
+          doc::EnumTag(_) | doc::TraitTag(_) | doc::TyTag(_)
+          | doc::StructTag(_) => {
+            let key = str::connect(item.path() + ~[item.name()], ~"::");
+            ctxt.links.targets.insert(copy key, copy link);
+            // Names used unqualified refer to the original definition
+            if !item.item().reexport {
+                add_name(&ctxt.links.local_names, item.name(), key);
+            }
+          }
+          _ => ()
+        }
+
+        match *item {
+          doc::ImplTag(_) => (),
+          _ => {
+            let mut entry = ~LinearMap::new();
+            let path = ~[copy ctxt.crate_name] + item.path() +
+                ~[item.name()];
+            entry.insert(~"name", json::String(item.name()));
+            entry.insert(~"path", json::String(str::connect(path, ~"::")));
+            entry.insert(~"kind", json::String(header_kind(copy *item)));
+            entry.insert(~"brief", match item.brief() {
+                Some(brief) => json::String(brief),
+                None => json::Null
+            });
+            entry.insert(~"link", json::String(copy link));
+            ctxt.search_index.push(json::Object(move entry));
+          }
+        }
+
+        match *item {
+          doc::ModTag(ref m) => index_mod(ctxt, m),
+          _ => ()
+        }
+    }
+}
+
+fn write_mod_page(
+    ctxt: &Ctxt,
+    writer_factory: &WriterFactory,
+    doc: &doc::ModDoc,
+    page: doc::Page
+) {
+    let title = str::replace(header_text(doc::ModTag(copy *doc)),
+                             ~"`", ~"");
+    let html = page_start(title) + sidebar(ctxt, doc) +
+        ~"<div class=\"content\">\n" + render_mod(ctxt, doc) +
+        ~"</div>\n" + page_end();
+    let writer = (*writer_factory)(page);
+    writer.write_str(html);
+    writer.write_done();
+
+    if ctxt.config.output_style == config::DocPerMod {
+        let page_name = mod_page(ctxt, doc);
+        for doc.items.each |item| {
+            match *item {
+              doc::ModTag(ref m) => {
+                write_mod_page(ctxt, writer_factory, m,
+                               doc::ItemPage(copy *item));
+              }
+              doc::NmodTag(ref n) => {
+                write_nmod_page(ctxt, writer_factory, page_name, n);
+              }
+              _ => ()
+            }
+        }
+    }
+}
+
+fn write_nmod_page(
+    ctxt: &Ctxt,
+    writer_factory: &WriterFactory,
+    parent_page: &str,
+    doc: &doc::NmodDoc
+) {
+    let title = str::replace(header_text(doc::NmodTag(copy *doc)),
+                             ~"`", ~"");
+    let html = page_start(title) + nmod_sidebar(ctxt, parent_page, doc) +
+        ~"<div class=\"content\">\n" +
+        render_nmod(ctxt, parent_page, doc) + ~"</div>\n" + page_end();
+    let writer = (*writer_factory)(doc::ItemPage(doc::NmodTag(copy *doc)));
+    writer.write_str(html);
+    writer.write_done();
+}
+
+fn page_start(title: &str) -> ~str {
+    fmt!("<!DOCTYPE html>\n\
+          <html>\n\
+          <head>\n\
+          <meta charset=\"utf-8\">\n\
+          <title>%s</title>\n\
+          <link rel=\"stylesheet\" href=\"rust.css\">\n\
+          <link rel=\"stylesheet\" href=\"rustdoc.css\">\n\
+          </head>\n\
+          <body>\n", escape(title))
+}
+
+fn page_end() -> ~str {
+    ~"<script src=\"search-index.js\"></script>\n\
+      <script src=\"search.js\"></script>\n\
+      </body>\n\
+      </html>\n"
+}
+
+// The kinds of item listed in the sidebar, in order
+const sidebar_kinds: &static/[(&static/str, &static/str)] = &[
+    ("Module", "Modules"),
+    ("Foreign module", "Foreign modules"),
+    ("Struct", "Structs"),
+    ("Enum", "Enums"),
+    ("Trait", "Traits"),
+    ("Type", "Types"),
+    ("Implementation", "Implementations"),
+    ("Function", "Functions"),
+    ("Const", "Constants")
+];
+
+fn sidebar_start(ctxt: &Ctxt, path: &[~str]) -> ~str {
+    let mut out = fmt!("<nav class=\"sidebar\">\n\
+                        <p class=\"crate\"><a href=\"%s\">%s</a></p>\n",
+                       escape(ctxt.crate_page), escape(ctxt.crate_name));
+    // Links to each enclosing module
+    if ctxt.config.output_style == config::DocPerMod && !path.is_empty() {
+        out += ~"<p class=\"location\">";
+        for path.eachi |i, name| {
+            if i != 0u { out += ~"::"; }
+            let page = str::connect(vec::slice(path, 0u, i + 1u), ~"_");
+            out += fmt!("<a href=\"%s.html\">%s</a>",
+                        escape(page), escape(*name));
+        }
+        out += ~"</p>\n";
+    }
+    out += ~"<input id=\"search\" type=\"search\" \
+             placeholder=\"Search\">\n\
+             <ul id=\"search-results\"></ul>\n";
+    out
+}
+
+fn sidebar(ctxt: &Ctxt, doc: &doc::ModDoc) -> ~str {
+    let path = if doc.id() == ast::crate_node_id {
+        ~[]
+    } else {
+        doc.path() + ~[doc.name()]
+    };
+    let page = mod_page(ctxt, doc);
+    let mut out = sidebar_start(ctxt, path);
+
+    for sidebar_kinds.each |&(kind, title)| {
+        let items = do doc.items.filtered |item| {
+            header_kind(copy *item) == str::from_slice(kind)
+        };
+        if items.is_empty() { loop; }
+        out += fmt!("<h3>%s</h3>\n<ul>\n", title);
+        for items.each |item| {
+            out += fmt!("<li><a href=\"%s\">%s</a></li>\n",
+                        escape(item_link(ctxt, page, item)),
+                        escape(item_label(item)));
+        }
+        out += ~"</ul>\n";
+    }
+    out + ~"</nav>\n"
+}
+
+fn nmod_sidebar(ctxt: &Ctxt, parent_page: &str, doc: &doc::NmodDoc)
+             -> ~str {
+    let page = nmod_page(ctxt, parent_page, doc);
+    let mut out = sidebar_start(ctxt, doc.path());
+    if !doc.fns.is_empty() {
+        out += ~"<h3>Functions</h3>\n<ul>\n";
+        for doc.fns.each |f| {
+            let item = doc::FnTag(copy *f);
+            out += fmt!("<li><a href=\"%s\">%s</a></li>\n",
+                        escape(item_link(ctxt, page, &item)),
+                        escape(f.name()));
+        }
+        out += ~"</ul>\n";
+    }
+    out + ~"</nav>\n"
+}
+
+fn render_common(desc: Option<~str>, sections: &[doc::Section]) -> ~str {
+    let mut out = ~"";
+    for desc.each |desc| {
+        out += render(*desc);
+    }
+    for sections.each |section| {
+        out += fmt!("<h4>%s</h4>\n", inline(section.header));
+        out += render(section.body);
+    }
+    out
+}
+
+fn render_summary(ctxt: &Ctxt, page: &str, items: &[doc::ItemTag]) -> ~str {
+    if items.is_empty() {
+        return ~"";
+    }
+    let mut out = ~"<table class=\"index\">\n";
+    for items.each |item| {
+        out += fmt!("<tr><td>%s</td><td><a href=\"%s\">%s</a></td>\
+                     <td>%s</td></tr>\n",
+                    escape(header_kind(copy *item)),
+                    escape(item_link(ctxt, page, item)),
+                    escape(item_label(item)),
+                    item.brief().map_default(~"", |b| inline(*b)));
+    }
+    out + ~"</table>\n"
+}
+
+fn render_mod(ctxt: &Ctxt, doc: &doc::ModDoc) -> ~str {
+    let page = mod_page(ctxt, doc);
+    let modpath = if doc.id() == ast::crate_node_id {
+        ~[]
+    } else {
+        doc.path() + ~[doc.name()]
+    };
+
+    let mut out = fmt!("<h1>%s</h1>\n",
+                       inline(header_text(doc::ModTag(copy *doc))));
+    out += render_common(doc.desc(), doc.sections());
+    out += render_summary(ctxt, page, doc.items);
+
+    for doc.items.each |item| {
+        match *item {
+          doc::ModTag(ref m) => {
+            if !is_mod_page(ctxt, m) {
+                out += fmt!("<section class=\"mod\" id=\"%s\">\n%s\
+                             </section>\n",
+                            item_anchor(item), render_mod(ctxt, m));
+            }
+          }
+          doc::NmodTag(ref n) => {
+            if ctxt.config.output_style == config::DocPerCrate {
+                out += fmt!("<section class=\"mod\" id=\"%s\">\n%s\
+                             </section>\n",
+                            item_anchor(item), render_nmod(ctxt, page, n));
+            }
+          }
+          _ => out += render_item(ctxt, modpath, item)
+        }
+    }
+    out
+}
+
+fn render_nmod(ctxt: &Ctxt, parent_page: &str, doc: &doc::NmodDoc)
+            -> ~str {
+    let page = nmod_page(ctxt, parent_page, doc);
+    let fns = doc.fns.map(|f| doc::FnTag(copy *f));
+    let mut out = fmt!("<h1>%s</h1>\n",
+                       inline(header_text(doc::NmodTag(copy *doc))));
+    out += render_common(doc.desc(), doc.sections());
+    out += render_summary(ctxt, page, fns);
+    for fns.each |f| {
+        out += render_item(ctxt, doc.path(), f);
+    }
+    out
+}
+
+fn render_item(ctxt: &Ctxt, modpath: &[~str], doc: &doc::ItemTag) -> ~str {
+    let mut out = fmt!("<section class=\"item\" id=\"%s\">\n<h2>%s</h2>\n",
+                       item_anchor(doc), inline(header_text(copy *doc)));
+    match *doc {
+      doc::FnTag(ref d) | doc::ConstTag(ref d) | doc::TyTag(ref d) => {
+        out += render_sig(ctxt, modpath, &d.sig);
+        out += render_common(d.desc(), d.sections());
+      }
+      doc::StructTag(ref d) => {
+        out += render_sig(ctxt, modpath, &d.sig);
+        out += render_common(d.desc(), d.sections());
+        out += render_impl_refs(ctxt, modpath, ~"Trait implementations",
+                                d.impls, |i| copy i.trait_ty);
+      }
+      doc::EnumTag(ref d) => {
+        out += render_common(d.desc(), d.sections());
+        out += render_variants(ctxt, modpath, d.variants);
+        out += render_impl_refs(ctxt, modpath, ~"Trait implementations",
+                                d.impls, |i| copy i.trait_ty);
+      }
+      doc::TraitTag(ref d) => {
+        out += render_common(d.desc(), d.sections());
+        out += render_methods(ctxt, modpath, d.methods);
+        out += render_impl_refs(ctxt, modpath, ~"Implementors",
+                                d.implementors, |i| copy i.self_ty);
+      }
+      doc::ImplTag(ref d) => {
+        out += render_common(d.desc(), d.sections());
+        out += render_methods(ctxt, modpath, d.methods);
+      }
+      doc::ModTag(_) | doc::NmodTag(_) => die!(~"modules have own pages")
+    }
+    out + ~"</section>\n"
+}
+
+fn render_sig(ctxt: &Ctxt, modpath: &[~str], sig: &Option<~str>) -> ~str {
+    match *sig {
+      Some(ref sig) => {
+        fmt!("<pre class=\"sig\">%s</pre>\n", link_sig(ctxt, modpath, *sig))
+      }
+      None => ~""
+    }
+}
+
+fn render_variants(ctxt: &Ctxt, modpath: &[~str],
+                   docs: &[doc::VariantDoc]) -> ~str {
+    if docs.is_empty() {
+        return ~"";
+    }
+    let mut out = ~"<h4>Variants</h4>\n<ul class=\"variants\">\n";
+    for docs.each |variant| {
+        let sig = variant.sig.get_or_default(copy variant.name);
+        out += fmt!("<li><code>%s</code>", link_sig(ctxt, modpath, sig));
+        for variant.desc.each |desc| {
+            out += ~" - " + inline(*desc);
+        }
+        out += ~"</li>\n";
+    }
+    out + ~"</ul>\n"
+}
+
+// Lists impls of a trait or of a type, by what `label` says of them
+fn render_impl_refs(ctxt: &Ctxt, modpath: &[~str], title: ~str,
+                    docs: &[doc::ImplRef],
+                    label: fn(&doc::ImplRef) -> ~str) -> ~str {
+    if docs.is_empty() {
+        return ~"";
+    }
+    let mut out = fmt!("<h4>%s</h4>\n<ul class=\"impls\">\n", title);
+    for docs.each |doc| {
+        out += fmt!("<li><code>%s</code></li>\n",
+                    link_sig(ctxt, modpath, label(doc)));
+    }
+    out + ~"</ul>\n"
+}
+
+fn render_methods(ctxt: &Ctxt, modpath: &[~str],
+                  docs: &[doc::MethodDoc]) -> ~str {
+    let mut out = ~"";
+    for docs.each |method| {
+        out += fmt!("<h3>Method <code>%s</code></h3>\n",
+                    escape(method.name));
+        out += render_sig(ctxt, modpath, &method.sig);
+        out += render_common(copy method.desc, method.sections);
+    }
+    out
+}
+
+pure fn is_ident_start(c: char) -> bool {
+    char::is_alphabetic(c) || c == '_'
+}
+
+pure fn is_ident_char(c: char) -> bool {
+    char::is_alphanumeric(c) || c == '_'
+}
+
+/// Escapes a signature, linking the names of types to their documentation
+fn link_sig(ctxt: &Ctxt, modpath: &[~str], sig: &str) -> ~str {
+    let params = declared_ty_params(sig);
+    let cs = str::chars(sig);
+    let mut out = ~"";
+    let mut i = 0u;
+
+    while i < cs.len() {
+        let starts_path = is_ident_start(cs[i]) &&
+            (i == 0u || !(is_ident_char(cs[i - 1u]) || cs[i - 1u] == '\''));
+        if !starts_path {
+            out += escape(str::from_char(cs[i]));
+            i += 1u;
+            loop;
+        }
+
+        // Read a whole path, such as `io::Reader`
+        let start = i;
+        let mut segs = ~[];
+        loop {
+            let seg_start = i;
+            while i < cs.len() && is_ident_char(cs[i]) { i += 1u; }
+            segs.push(str::from_chars(vec::slice(cs, seg_start, i)));
+            if i + 2u < cs.len() && cs[i] == ':' && cs[i + 1u] == ':' &&
+                is_ident_start(cs[i + 2u]) {
+                i += 2u;
+            } else {
+                break;
+            }
+        }
+        let text = escape(str::from_chars(vec::slice(cs, start, i)));
+
+        let target = if segs.len() == 1u && params.contains(&segs[0]) {
+            None
+        } else {
+            resolve(&ctxt.links, modpath, segs)
+        };
+        match target {
+          Some(url) => {
+            out += fmt!("<a href=\"%s\">%s</a>", escape(url), text);
+          }
+          None => out += text
+        }
+    }
+    out
+}
+
+/**
+ * The names of the type parameters that a signature declares, such as `T`
+ * and `U` in `fn f<T: Copy, U>(t: T, u: U)`.  These are never linked, even
+ * when some type happens to have the same name.
+ */
+fn declared_ty_params(sig: &str) -> ~[~str] {
+    let cs = str::chars(sig);
+    let mut params = ~[];
+    let mut i = 0u;
+    // The parameters come before any arguments, fields or definition
+    while i < cs.len() && cs[i] != '<' {
+        if cs[i] == '(' || cs[i] == '{' || cs[i] == '=' { return params; }
+        i += 1u;
+    }
+
+    let mut depth = 0u;
+    let mut expect_param = false;
+    while i < cs.len() {
+        let c = cs[i];
+        if c == '<' {
+            depth += 1u;
+            expect_param = depth == 1u;
+        } else if c == '>' {
+            depth -= 1u;
+            if depth == 0u { break; }
+        } else if c == ',' && depth == 1u {
+            expect_param = true;
+        } else if expect_param && is_ident_start(c) {
+            let start = i;
+            while i < cs.len() && is_ident_char(cs[i]) { i += 1u; }
+            params.push(str::from_chars(vec::slice(cs, start, i)));
+            expect_param = false;
+            loop;
+        } else if !char::is_whitespace(c) {
+            expect_param = false;
+        }
+        i += 1u;
+    }
+    params
+}
+
+fn resolve(links: &Links, modpath: &[~str], segs: &[~str]) -> Option<~str> {
+    let path = str::connect(segs, ~"::");
+
+    // A path relative to the current module, then to the crate root or
+    // starting with the name of another crate
+    let relative = str::connect(vec::append(vec::from_slice(modpath), segs),
+                                ~"::");
+    for [relative, copy path].each |key| {
+        match links.targets.find(key) {
+          Some(url) => return Some(url),
+          None => ()
+        }
+    }
+
+    // Otherwise the name of the type must have been imported, or the path
+    // start with an imported module; go by the name if that is unambiguous
+    let name = vec::last(segs);
+    for [&links.local_names, &links.extern_names].each |names| {
+        match names.find(&name) {
+          Some(keys) => {
+            let candidates = do keys.filtered |key| {
+                *key == path || str::ends_with(*key, ~"::" + path)
+            };
+            if candidates.len() == 1u {
+                return links.targets.find(&candidates[0]);
+            }
+          }
+          None => ()
+        }
+    }
+    None
+}
+
+fn write_assets(config: &config::Config, search_index: ~[json::Json]) {
+    let dir = &config.output_dir;
+    markdown_writer::write_file(&dir.push("rustdoc.css"),
+                                str::from_slice(stylesheet));
+    markdown_writer::write_file(&dir.push("search.js"),
+                                str::from_slice(search_script));
+    markdown_writer::write_file(
+        &dir.push("search-index.js"),
+        fmt!("var searchIndex = %s;\n",
+             json::to_str(&json::List(move search_index))));
+}
+
+const stylesheet: &static/str = "\
+body { margin: 0; font-family: sans-serif; }
+.sidebar { position: fixed; top: 0; bottom: 0; left: 0; width: 220px;
+           overflow: auto; padding: 10px; background: #f5f5f5;
+           border-right: 1px solid #ddd; }
+.sidebar ul { list-style: none; padding-left: 10px; }
+.sidebar .crate { font-size: 1.4em; font-weight: bold; }
+#search { width: 100%; }
+.content { margin-left: 250px; padding: 10px 20px; max-width: 800px; }
+pre.sig { background: #f8f8f8; padding: 5px; }
+table.index td { padding: 2px 10px 2px 0; vertical-align: top; }
+";
+
+const search_script: &static/str = "\
+(function () {
+    var input = document.getElementById('search');
+    var results = document.getElementById('search-results');
+    if (!input || typeof searchIndex == 'undefined') { return; }
+    input.onkeyup = function () {
+        var query = input.value.toLowerCase();
+        results.innerHTML = '';
+        if (query.length == 0) { return; }
+        for (var i = 0; i < searchIndex.length; i++) {
+            var entry = searchIndex[i];
+            if (entry.path.toLowerCase().indexOf(query) == -1) { continue; }
+            var link = document.createElement('a');
+            link.href = entry.link;
+            link.appendChild(document.createTextNode(entry.path));
+            var li = document.createElement('li');
+            li.appendChild(link);
+            results.appendChild(li);
+            if (results.childNodes.length == 50) { break; }
+        }
+    };
+})();
+";
+
+#[test]
+fn should_write_crate_header_and_title() {
+    let html = test::render(~"mod a { }");
+    assert str::contains(html, ~"<title>Crate test</title>");
+    assert str::contains(html, ~"<h1>Crate <code>test</code></h1>");
+}
+
+#[test]
+fn should_render_descriptions_as_html() {
+    let html = test::render(~"#[doc = \"does `a`\"] fn f() { }");
+    assert str::contains(html, ~"<p>does <code>a</code></p>");
+}
+
+#[test]
+fn should_write_sidebar() {
+    let html = test::render(~"fn f() { } struct S { x: int }");
+    assert str::contains(html, ~"<h3>Structs</h3>\n<ul>\n\
+                                 <li><a href=\"test.html#type.S\">S</a>");
+    assert str::contains(html, ~"<h3>Functions</h3>");
+}
+
+#[test]
+fn should_link_types_in_signatures() {
+    let html = test::render(~"struct S { x: int } fn f(s: S) { }");
+    assert str::contains(
+        html, ~"fn f(s: <a href=\"test.html#type.S\">S</a>)");
+}
+
+#[test]
+fn should_link_qualified_types() {
+    let html = test::render(
+        ~"mod a { pub struct S { x: int } } fn f(s: a::S) { }");
+    assert str::contains(
+        html, ~"fn f(s: <a href=\"test.html#type.a.S\">a::S</a>)");
+}
+
+#[test]
+fn should_not_link_type_parameters() {
+    let html = test::render(~"struct T { x: int } fn f<T>(t: T) { }");
+    assert str::contains(html, ~"fn f&lt;T&gt;(t: T)");
+}
+
+#[test]
+fn should_link_extern_types() {
+    let html = test::render_with_externs(
+        ~"fn f(o: Option<int>) { }",
+        ~[ExternType { crate: ~"core", path: ~"option::Option" }]);
+    assert str::contains(
+        html, ~"<a href=\"../core/option.html#type.option.Option\">");
+}
+
+#[test]
+fn should_list_implementors() {
+    let html = test::render(
+        ~"struct S { x: int } trait T { fn f(); } impl S: T { fn f() { } }");
+    assert str::contains(
+        html, ~"<h4>Implementors</h4>\n<ul class=\"impls\">\n\
+                <li><code><a href=\"test.html#type.S\">S</a></code></li>");
+    assert str::contains(
+        html, ~"<h4>Trait implementations</h4>\n<ul class=\"impls\">\n\
+                <li><code><a href=\"test.html#type.T\">T</a></code></li>");
+}
+
+#[test]
+fn should_index_items_for_search() {
+    let (_, doc) = test::create_doc_srv(~"mod a { fn b() { } }");
+    let (writer_factory, _po) = markdown_writer::future_writer_factory();
+    let index = write_html(doc, ~[], writer_factory, test::config());
+    let paths = do index.map |entry| {
+        match *entry {
+          json::Object(ref obj) => copy *obj.get(&~"path"),
+          _ => die!()
+        }
+    };
+    assert paths.contains(&json::String(~"test::a"));
+    assert paths.contains(&json::String(~"test::a::b"));
+}
+
+#[cfg(test)]
+mod test {
+    use astsrv;
+    use attr_pass;
+    use config;
+    use desc_to_brief_pass;
+    use doc;
+    use extract;
+    use html_pass::{ExternType, write_html};
+    use implementors_pass;
+    use markdown_writer;
+    use path_pass;
+    use sectionalize_pass;
+    use trim_pass;
+    use tystr_pass;
+    use unindent_pass;
+
+    use core::path::Path;
+
+    pub fn config() -> config::Config {
+        config::Config {
+            output_style: config::DocPerCrate,
+            .. config::default_config(&Path("whatever"))
+        }
+    }
+
+    pub fn create_doc_srv(source: ~str) -> (astsrv::Srv, doc::Doc) {
+        do astsrv::from_str(source) |srv| {
+            let doc = extract::from_srv(srv.clone(), ~"test");
+            let doc = (tystr_pass::mk_pass().f)(srv.clone(), doc);
+            let doc = (path_pass::mk_pass().f)(srv.clone(), doc);
+            let doc = (attr_pass::mk_pass().f)(srv.clone(), doc);
+            let doc = (implementors_pass::mk_pass().f)(srv.clone(), doc);
+            let doc = (desc_to_brief_pass::mk_pass().f)(srv.clone(), doc);
+            let doc = (unindent_pass::mk_pass().f)(srv.clone(), doc);
+            let doc = (sectionalize_pass::mk_pass().f)(srv.clone(), doc);
+            let doc = (trim_pass::mk_pass().f)(srv.clone(), doc);
+            (srv.clone(), doc)
+        }
+    }
+
+    pub fn render_with_externs(source: ~str,
+                               externs: ~[ExternType]) -> ~str {
+        let (_, doc) = create_doc_srv(source);
+        let (writer_factory, po) = markdown_writer::future_writer_factory();
+        write_html(doc, externs, writer_factory, config());
+        let html = po.recv().second();
+        debug!("html: %s", html);
+        html
+    }
+
+    pub fn render(source: ~str) -> ~str {
+        render_with_externs(source, ~[])
+    }
+}
@@ -0,0 +1,292 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Records the impls of each trait and of each type.
+
+Impls of the crate are matched to traits and types by the paths written in
+them, since rustdoc doesn't resolve names; a path is looked up relative to
+the module of the impl, then from the crate root, and finally by its last
+components if only one item matches.
+
+Only impls of the documented crate are listed.  The crates it depends on
+can't name its traits or types, so none of their impls belong on its pages,
+and its own impls of their traits aren't added to the documentation of
+those crates.
+*/
+
+use core::prelude::*;
+
+use astsrv;
+use doc::ItemUtils;
+use doc;
+use fold::Fold;
+use fold;
+use pass::Pass;
+
+use core::char;
+use core::str;
+use core::vec;
+use std::oldmap::HashMap;
+
+pub fn mk_pass() -> Pass {
+    Pass {
+        name: ~"implementors",
+        f: run
+    }
+}
+
+struct Ctxt {
+    // Impls by the path of their trait, and by the path of their self type
+    implementors: HashMap<~str, @mut ~[doc::ImplRef]>,
+    impls: HashMap<~str, @mut ~[doc::ImplRef]>
+}
+
+impl Ctxt: Clone {
+    fn clone(&self) -> Ctxt {
+        Ctxt {
+            implementors: self.implementors,
+            impls: self.impls
+        }
+    }
+}
+
+/// A trait impl of the documented crate
+struct LocalImpl {
+    modpath: ~[~str],
+    trait_ty: ~str,
+    self_ty: ~str
+}
+
+fn run(_srv: astsrv::Srv, doc: doc::Doc) -> doc::Doc {
+    let ctxt = Ctxt {
+        implementors: HashMap(),
+        impls: HashMap()
+    };
+    let mut traits = ~[];
+    let mut types = ~[];
+    let mut local_impls = ~[];
+    collect(&doc.cratemod(), &mut traits, &mut types, &mut local_impls);
+
+    for local_impls.each |i| {
+        let impl_ref = doc::ImplRef {
+            trait_ty: copy i.trait_ty,
+            self_ty: copy i.self_ty
+        };
+        for resolve(traits, i.modpath, ty_path(i.trait_ty)).each |key| {
+            add(ctxt.implementors, copy *key, copy impl_ref);
+        }
+        for resolve(types, i.modpath, ty_path(i.self_ty)).each |key| {
+            add(ctxt.impls, copy *key, copy impl_ref);
+        }
+    }
+
+    let fold = Fold {
+        ctxt: ctxt.clone(),
+        fold_enum: fold_enum,
+        fold_trait: fold_trait,
+        fold_struct: fold_struct,
+        .. fold::default_any_fold(ctxt)
+    };
+    (fold.fold_doc)(&fold, doc)
+}
+
+fn add(map: HashMap<~str, @mut ~[doc::ImplRef]>, key: ~str,
+       impl_ref: doc::ImplRef) {
+    match map.find(&key) {
+      Some(refs) => refs.push(impl_ref),
+      None => { map.insert(key, @mut ~[impl_ref]); }
+    }
+}
+
+fn item_key(doc: &doc::ItemTag) -> ~str {
+    str::connect(doc.path() + ~[doc.name()], ~"::")
+}
+
+fn collect(
+    doc: &doc::ModDoc,
+    traits: &mut ~[~str],
+    types: &mut ~[~str],
+    impls: &mut ~[LocalImpl]
+) {
+    for doc.items.each |item| {
+        match *item {
+          doc::TraitTag(_) => traits.push(item_key(item)),
+          doc::EnumTag(_) | doc::StructTag(_) => types.push(item_key(item)),
+          doc::ImplTag(ref i) => {
+            for i.trait_types.each |trait_ty| {
+                impls.push(LocalImpl {
+                    modpath: i.path(),
+                    trait_ty: copy *trait_ty,
+                    self_ty: i.self_ty.get_or_default(~"")
+                });
+            }
+          }
+          doc::ModTag(ref m) => collect(m, traits, types, impls),
+          _ => ()
+        }
+    }
+}
+
+/**
+ * The path naming the type `ty`, such as `a::T` for `@a::T<int>`, or
+ * nothing if the type isn't a path
+ */
+fn ty_path(ty: &str) -> ~[~str] {
+    let cs = str::chars(ty);
+    let mut i = 0u;
+    loop {
+        while i < cs.len() && str::contains_char("@~&* ", cs[i]) {
+            i += 1u;
+        }
+        let is_lifetime = i < cs.len() && cs[i] == '\'';
+        if is_lifetime { i += 1u; }
+
+        let start = i;
+        while i < cs.len() &&
+            (char::is_alphanumeric(cs[i]) || cs[i] == '_' || cs[i] == ':') {
+            i += 1u;
+        }
+        let word = str::from_chars(vec::slice(cs, start, i));
+
+        // Skip regions, as in `&r/T`, and mutability
+        if is_lifetime || (i < cs.len() && cs[i] == '/') {
+            if i < cs.len() && cs[i] == '/' { i += 1u; }
+            loop;
+        }
+        if word == ~"mut" || word == ~"const" {
+            loop;
+        }
+        return do str::split_str(word, "::").filtered |seg| {
+            !seg.is_empty()
+        };
+    }
+}
+
+fn resolve(keys: &[~str], modpath: &[~str], path: &[~str]) -> Option<~str> {
+    if path.is_empty() {
+        return None;
+    }
+    let path = str::connect(path, ~"::");
+    let relative = str::connect(vec::from_slice(modpath) + ~[copy path],
+                                ~"::");
+    if keys.contains(&relative) {
+        return Some(relative);
+    }
+    if keys.contains(&path) {
+        return Some(path);
+    }
+    // The trait or type may have been imported
+    let candidates = do keys.filtered |key| {
+        str::ends_with(*key, ~"::" + path)
+    };
+    if candidates.len() == 1u {
+        Some(copy candidates[0])
+    } else {
+        None
+    }
+}
+
+fn impls_of(map: HashMap<~str, @mut ~[doc::ImplRef]>,
+            doc: &doc::ItemTag) -> ~[doc::ImplRef] {
+    match map.find(&item_key(doc)) {
+      Some(refs) => copy *refs,
+      None => ~[]
+    }
+}
+
+fn fold_trait(fold: &fold::Fold<Ctxt>, doc: doc::TraitDoc) -> doc::TraitDoc {
+    let doc = fold::default_seq_fold_trait(fold, doc);
+    doc::TraitDoc {
+        implementors: impls_of(fold.ctxt.implementors,
+                               &doc::TraitTag(copy doc)),
+        .. doc
+    }
+}
+
+fn fold_enum(fold: &fold::Fold<Ctxt>, doc: doc::EnumDoc) -> doc::EnumDoc {
+    let doc = fold::default_seq_fold_enum(fold, doc);
+    doc::EnumDoc {
+        impls: impls_of(fold.ctxt.impls, &doc::EnumTag(copy doc)),
+        .. doc
+    }
+}
+
+fn fold_struct(fold: &fold::Fold<Ctxt>,
+               doc: doc::StructDoc) -> doc::StructDoc {
+    let doc = fold::default_seq_fold_struct(fold, doc);
+    doc::StructDoc {
+        impls: impls_of(fold.ctxt.impls, &doc::StructTag(copy doc)),
+        .. doc
+    }
+}
+
+#[test]
+fn should_list_implementors_of_traits() {
+    let doc = test::mk_doc(
+        ~"trait T { fn f(); } struct S { x: int } \
+          impl S: T { fn f() { } } impl int: T { fn f() { } }");
+    assert doc.cratemod().traits()[0].implementors == ~[
+        doc::ImplRef { trait_ty: ~"T", self_ty: ~"S" },
+        doc::ImplRef { trait_ty: ~"T", self_ty: ~"int" }
+    ];
+}
+
+#[test]
+fn should_list_trait_impls_of_types() {
+    let doc = test::mk_doc(
+        ~"mod a { pub enum E { V } } \
+          mod b { impl a::E: ToStr { fn to_str() -> ~str { ~\"\" } } }");
+    assert doc.cratemod().mods()[0].enums()[0].impls == ~[
+        doc::ImplRef { trait_ty: ~"ToStr", self_ty: ~"a::E" }
+    ];
+}
+
+#[test]
+fn should_resolve_paths_relative_to_the_impl() {
+    let doc = test::mk_doc(
+        ~"mod a { pub trait T { fn f(); } impl int: T { fn f() { } } } \
+          trait T { fn f(); }");
+    assert doc.cratemod().mods()[0].traits()[0].implementors.len() == 1u;
+    assert doc.cratemod().traits()[0].implementors.is_empty();
+}
+
+#[test]
+fn should_not_list_inherent_impls() {
+    let doc = test::mk_doc(~"struct S { x: int } impl S { fn f() { } }");
+    assert doc.cratemod().structs()[0].impls.is_empty();
+}
+
+#[test]
+fn should_find_type_paths() {
+    assert ty_path("@a::b::T<int>") == ~[~"a", ~"b", ~"T"];
+    assert ty_path("&r/mut T") == ~[~"T"];
+    assert ty_path("&'r T") == ~[~"T"];
+    assert ty_path("(int, uint)").is_empty();
+}
+
+#[cfg(test)]
+mod test {
+    use astsrv;
+    use doc;
+    use extract;
+    use implementors_pass::run;
+    use path_pass;
+    use tystr_pass;
+
+    pub fn mk_doc(source: ~str) -> doc::Doc {
+        do astsrv::from_str(copy source) |srv| {
+            let doc = extract::from_srv(srv.clone(), ~"");
+            let doc = (tystr_pass::mk_pass().f)(srv.clone(), doc);
+            let doc = (path_pass::mk_pass().f)(srv.clone(), doc);
+            run(srv.clone(), doc)
+        }
+    }
+}
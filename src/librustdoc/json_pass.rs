@@ -0,0 +1,283 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Writes the document tree as JSON, for tools that render or compare
+documentation themselves.
+
+The output is a single object:
+
+~~~
+{ "format_version": 1, "crate": <item> }
+~~~
+
+where `crate` is the module item for the crate itself.  Every item has the
+fields
+
+* `kind` - one of `mod`, `foreign_mod`, `const`, `fn`, `enum`, `trait`,
+  `impl`, `type` or `struct`
+* `id` - the AST node id, which is only unique within one run
+* `name`
+* `path` - the names of the enclosing modules, not including the crate
+* `brief` and `desc` - markdown, or null
+* `sections` - a list of `{"header", "body"}` objects
+* `reexport` - whether the item is a copy made for a `pub use`
+
+and by kind
+
+* `mod` - `items`, a list of items
+* `foreign_mod` - `fns`, a list of `fn` items
+* `const`, `fn` and `type` - `sig`, the declaration, or null
+* `enum` - `variants`, a list of `{"name", "desc", "sig"}` objects, and
+  `impls`
+* `trait` - `methods` and `implementors`
+* `impl` - `trait_types`, a list of strings, `self_ty` and `methods`
+* `struct` - `fields`, a list of names, `sig` and `impls`
+
+Methods are objects with `name`, `brief`, `desc`, `sections`, `sig` and
+`implementation`, either `required` or `provided`.  `impls` and
+`implementors` are lists of `{"trait_ty", "self_ty"}` objects, for impls in
+the documented crate.
+
+`format_version` is incremented whenever a field is removed or changes
+meaning; fields may be added without changing it.
+*/
+
+use core::prelude::*;
+
+use doc::Item;
+use doc;
+use markdown_writer::{WriterFactory, WriterUtils};
+use pass::Pass;
+
+use core::hashmap::linear::LinearMap;
+use core::str;
+use std::cell::Cell;
+use std::json;
+
+/// The version of the layout described above
+pub const json_format_version: uint = 1;
+
+pub fn mk_pass(writer_factory: WriterFactory) -> Pass {
+    let writer_factory = Cell(writer_factory);
+    Pass {
+        name: ~"json",
+        f: |_srv, doc| {
+            write_json(&doc, writer_factory.take());
+            doc
+        }
+    }
+}
+
+pub fn write_json(doc: &doc::Doc, writer_factory: WriterFactory) {
+    let writer = writer_factory(doc::CratePage(doc.CrateDoc()));
+    writer.write_line(json::to_pretty_str(&doc_to_json(doc)));
+    writer.write_done();
+}
+
+pub fn doc_to_json(doc: &doc::Doc) -> json::Json {
+    let mut obj = ~LinearMap::new();
+    obj.insert(~"format_version",
+               json::Number(json_format_version as float));
+    obj.insert(~"crate", item_to_json(&doc::ModTag(doc.cratemod())));
+    json::Object(move obj)
+}
+
+fn string(s: &str) -> json::Json {
+    json::String(str::from_slice(s))
+}
+
+fn opt_string(s: &Option<~str>) -> json::Json {
+    match *s {
+      Some(ref s) => string(*s),
+      None => json::Null
+    }
+}
+
+fn strings(v: &[~str]) -> json::Json {
+    json::List(v.map(|s| string(*s)))
+}
+
+fn sections_to_json(sections: &[doc::Section]) -> json::Json {
+    json::List(do sections.map |section| {
+        let mut obj = ~LinearMap::new();
+        obj.insert(~"header", string(section.header));
+        obj.insert(~"body", string(section.body));
+        json::Object(move obj)
+    })
+}
+
+fn impl_refs_to_json(refs: &[doc::ImplRef]) -> json::Json {
+    json::List(do refs.map |r| {
+        let mut obj = ~LinearMap::new();
+        obj.insert(~"trait_ty", string(r.trait_ty));
+        obj.insert(~"self_ty", string(r.self_ty));
+        json::Object(move obj)
+    })
+}
+
+fn methods_to_json(methods: &[doc::MethodDoc]) -> json::Json {
+    json::List(do methods.map |m| {
+        let mut obj = ~LinearMap::new();
+        obj.insert(~"name", string(m.name));
+        obj.insert(~"brief", opt_string(&m.brief));
+        obj.insert(~"desc", opt_string(&m.desc));
+        obj.insert(~"sections", sections_to_json(m.sections));
+        obj.insert(~"sig", opt_string(&m.sig));
+        obj.insert(~"implementation", string(match m.implementation {
+            doc::Required => "required",
+            doc::Provided => "provided"
+        }));
+        json::Object(move obj)
+    })
+}
+
+fn item_kind(doc: &doc::ItemTag) -> &static/str {
+    match *doc {
+      doc::ModTag(_) => "mod",
+      doc::NmodTag(_) => "foreign_mod",
+      doc::ConstTag(_) => "const",
+      doc::FnTag(_) => "fn",
+      doc::EnumTag(_) => "enum",
+      doc::TraitTag(_) => "trait",
+      doc::ImplTag(_) => "impl",
+      doc::TyTag(_) => "type",
+      doc::StructTag(_) => "struct"
+    }
+}
+
+fn item_to_json(doc: &doc::ItemTag) -> json::Json {
+    let item = doc.item();
+    let mut obj = ~LinearMap::new();
+    obj.insert(~"kind", string(item_kind(doc)));
+    obj.insert(~"id", json::Number(item.id as float));
+    obj.insert(~"name", string(item.name));
+    obj.insert(~"path", strings(item.path));
+    obj.insert(~"brief", opt_string(&item.brief));
+    obj.insert(~"desc", opt_string(&item.desc));
+    obj.insert(~"sections", sections_to_json(item.sections));
+    obj.insert(~"reexport", json::Boolean(item.reexport));
+
+    match *doc {
+      doc::ModTag(ref d) => {
+        obj.insert(~"items", json::List(d.items.map(|i| item_to_json(i))));
+      }
+      doc::NmodTag(ref d) => {
+        obj.insert(~"fns", json::List(do d.fns.map |f| {
+            item_to_json(&doc::FnTag(copy *f))
+        }));
+      }
+      doc::ConstTag(ref d) | doc::FnTag(ref d) | doc::TyTag(ref d) => {
+        obj.insert(~"sig", opt_string(&d.sig));
+      }
+      doc::EnumTag(ref d) => {
+        obj.insert(~"variants", json::List(do d.variants.map |v| {
+            let mut variant = ~LinearMap::new();
+            variant.insert(~"name", string(v.name));
+            variant.insert(~"desc", opt_string(&v.desc));
+            variant.insert(~"sig", opt_string(&v.sig));
+            json::Object(move variant)
+        }));
+        obj.insert(~"impls", impl_refs_to_json(d.impls));
+      }
+      doc::TraitTag(ref d) => {
+        obj.insert(~"methods", methods_to_json(d.methods));
+        obj.insert(~"implementors", impl_refs_to_json(d.implementors));
+      }
+      doc::ImplTag(ref d) => {
+        obj.insert(~"trait_types", strings(d.trait_types));
+        obj.insert(~"self_ty", opt_string(&d.self_ty));
+        obj.insert(~"methods", methods_to_json(d.methods));
+      }
+      doc::StructTag(ref d) => {
+        obj.insert(~"fields", strings(d.fields));
+        obj.insert(~"sig", opt_string(&d.sig));
+        obj.insert(~"impls", impl_refs_to_json(d.impls));
+      }
+    }
+    json::Object(move obj)
+}
+
+#[test]
+fn should_write_format_version() {
+    let json = test::render(~"");
+    assert test::field(&json, ~"format_version") == json::Number(1f);
+}
+
+#[test]
+fn should_write_crate_as_module() {
+    let json = test::render(~"#[link(name = \"c\")]; fn f() { }");
+    let crate = test::field(&json, ~"crate");
+    assert test::field(&crate, ~"kind") == json::String(~"mod");
+    assert test::field(&crate, ~"name") == json::String(~"c");
+}
+
+#[test]
+fn should_write_item_signatures() {
+    let json = test::render(~"mod a { fn f(x: int) { } }");
+    let crate = test::field(&json, ~"crate");
+    let a = test::items(&crate)[0];
+    let f = test::items(&a)[0];
+    assert test::field(&f, ~"kind") == json::String(~"fn");
+    assert test::field(&f, ~"path") == json::List(~[json::String(~"a")]);
+    assert test::field(&f, ~"sig") == json::String(~"fn f(x: int)");
+}
+
+#[test]
+fn should_write_implementors() {
+    let json = test::render(
+        ~"trait t { fn f(); } impl int: t { fn f() { } }");
+    let crate = test::field(&json, ~"crate");
+    let t = test::items(&crate)[0];
+    match test::field(&t, ~"implementors") {
+      json::List(ref refs) => {
+        assert test::field(&refs[0], ~"self_ty") == json::String(~"int");
+      }
+      _ => die!(~"implementors should be a list")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use astsrv;
+    use attr_pass;
+    use extract;
+    use implementors_pass;
+    use json_pass::doc_to_json;
+    use path_pass;
+    use tystr_pass;
+
+    use std::json;
+
+    pub fn render(source: ~str) -> json::Json {
+        do astsrv::from_str(source) |srv| {
+            let doc = extract::from_srv(srv.clone(), ~"");
+            let doc = (tystr_pass::mk_pass().f)(srv.clone(), doc);
+            let doc = (path_pass::mk_pass().f)(srv.clone(), doc);
+            let doc = (attr_pass::mk_pass().f)(srv.clone(), doc);
+            let doc = (implementors_pass::mk_pass().f)(srv.clone(), doc);
+            doc_to_json(&doc)
+        }
+    }
+
+    pub fn field(json: &json::Json, name: ~str) -> json::Json {
+        match *json {
+          json::Object(ref obj) => copy *obj.get(&name),
+          _ => die!(~"expected an object")
+        }
+    }
+
+    pub fn items(json: &json::Json) -> ~[json::Json] {
+        match field(json, ~"items") {
+          json::List(items) => items,
+          _ => die!(~"expected a list of items")
+        }
+    }
+}
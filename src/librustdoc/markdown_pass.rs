@@ -49,24 +49,24 @@ pub fn mk_pass(writer_factory: WriterFactory) -> Pass {
     }
 }
 
+pub pure fn mods_last(item1: &doc::ItemTag, item2: &doc::ItemTag) -> bool {
+    pure fn is_mod(item: &doc::ItemTag) -> bool {
+        match *item {
+          doc::ModTag(_) => true,
+          _ => false
+        }
+    }
+
+    let lteq = !is_mod(item1) || is_mod(item2);
+    lteq
+}
+
 fn run(
     srv: astsrv::Srv,
     doc: doc::Doc,
     writer_factory: WriterFactory
 ) -> doc::Doc {
 
-    pure fn mods_last(item1: &doc::ItemTag, item2: &doc::ItemTag) -> bool {
-        pure fn is_mod(item: &doc::ItemTag) -> bool {
-            match *item {
-              doc::ModTag(_) => true,
-              _ => false
-            }
-        }
-
-        let lteq = !is_mod(item1) || is_mod(item2);
-        lteq
-    }
-
     // Sort the items so mods come last. All mods will be
     // output at the same header level so sorting mods last
     // makes the headers come out nested correctly.
@@ -622,6 +622,7 @@ fn write_enum(
 ) {
     write_common(ctxt, doc.desc(), doc.sections());
     write_variants(ctxt, doc.variants);
+    write_trait_impls(ctxt, doc.impls);
 }
 
 #[test]
@@ -703,6 +704,31 @@ fn should_write_variant_list_with_signatures() {
 fn write_trait(ctxt: &Ctxt, doc: doc::TraitDoc) {
     write_common(ctxt, doc.desc(), doc.sections());
     write_methods(ctxt, doc.methods);
+    write_implementors(ctxt, doc.implementors);
+}
+
+fn write_implementors(ctxt: &Ctxt, docs: &[doc::ImplRef]) {
+    if docs.is_empty() {
+        return;
+    }
+
+    write_header_(ctxt, H4, ~"Implementors");
+    for docs.each |doc| {
+        ctxt.w.write_line(fmt!("* `%s`", doc.self_ty));
+    }
+    ctxt.w.write_line(~"");
+}
+
+fn write_trait_impls(ctxt: &Ctxt, docs: &[doc::ImplRef]) {
+    if docs.is_empty() {
+        return;
+    }
+
+    write_header_(ctxt, H4, ~"Trait implementations");
+    for docs.each |doc| {
+        ctxt.w.write_line(fmt!("* `%s`", doc.trait_ty));
+    }
+    ctxt.w.write_line(~"");
 }
 
 fn write_methods(ctxt: &Ctxt, docs: &[doc::MethodDoc]) {
@@ -753,6 +779,13 @@ fn write_impl(ctxt: &Ctxt, doc: doc::ImplDoc) {
     write_methods(ctxt, doc.methods);
 }
 
+#[test]
+fn should_write_trait_implementors() {
+    let markdown = test::render(
+        ~"trait i { fn a(); } impl int: i { fn a() { } }");
+    assert str::contains(markdown, ~"#### Implementors\n\n* `int`\n");
+}
+
 #[test]
 fn should_write_impl_header() {
     let markdown = test::render(~"impl int { fn a() { } }");
@@ -819,6 +852,7 @@ fn write_struct(
 ) {
     write_sig(ctxt, copy doc.sig);
     write_common(ctxt, doc.desc(), doc.sections());
+    write_trait_impls(ctxt, doc.impls);
 }
 
 #[test]
@@ -827,6 +861,14 @@ fn should_write_struct_header() {
     assert str::contains(markdown, ~"## Struct `S`\n\n");
 }
 
+#[test]
+fn should_write_struct_trait_impls() {
+    let markdown = test::render(
+        ~"struct S { field: () } impl S: ToStr { fn to_str() -> ~str { } }");
+    assert str::contains(markdown,
+                         ~"#### Trait implementations\n\n* `ToStr`\n");
+}
+
 #[cfg(test)]
 mod test {
     use astsrv;
@@ -835,6 +877,7 @@ mod test {
     use desc_to_brief_pass;
     use doc;
     use extract;
+    use implementors_pass;
     use markdown_index_pass;
     use markdown_pass::{mk_pass, write_markdown};
     use markdown_writer;
@@ -870,6 +913,8 @@ mod test {
             debug!("doc (path): %?", doc);
             let doc = (attr_pass::mk_pass().f)(srv.clone(), doc);
             debug!("doc (attr): %?", doc);
+            let doc = (implementors_pass::mk_pass().f)(srv.clone(), doc);
+            debug!("doc (implementors): %?", doc);
             let doc = (desc_to_brief_pass::mk_pass().f)(srv.clone(), doc);
             debug!("doc (desc_to_brief): %?", doc);
             let doc = (unindent_pass::mk_pass().f)(srv.clone(), doc);
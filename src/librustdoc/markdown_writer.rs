@@ -59,7 +59,9 @@ impl Writer: WriterUtils {
 
 pub fn make_writer_factory(config: config::Config) -> WriterFactory {
     match config.output_format {
-      config::Markdown => {
+      // The HTML and JSON passes produce the whole file themselves, so
+      // their output only needs writing out like markdown
+      config::Markdown | config::Html | config::Json => {
         markdown_writer_factory(config)
       }
       config::PandocHtml => {
@@ -200,7 +202,8 @@ pub fn make_filename(
     let filename = {
         match page {
           doc::CratePage(doc) => {
-            if config.output_format == config::PandocHtml &&
+            if (config.output_format == config::Html ||
+                config.output_format == config::PandocHtml) &&
                 config.output_style == config::DocPerMod {
                 ~"index"
             } else {
@@ -215,7 +218,8 @@ pub fn make_filename(
     };
     let ext = match config.output_format {
       config::Markdown => ~"md",
-      config::PandocHtml => ~"html"
+      config::Html | config::PandocHtml => ~"html",
+      config::Json => ~"json"
     };
 
     Path(filename).with_filetype(ext)
@@ -249,6 +253,20 @@ fn should_name_html_crate_file_name_index_html_when_doc_per_mod() {
     assert filename.to_str() == ~"output/dir/index.html";
 }
 
+#[test]
+fn should_name_json_file_after_crate() {
+    let config = Config {
+        output_dir: Path("output/dir"),
+        output_format: config::Json,
+        output_style: config::DocPerMod,
+        .. config::default_config(&Path("input/test.rc"))
+    };
+    let doc = test::mk_doc(~"test", ~"");
+    let page = doc::CratePage(doc.CrateDoc());
+    let filename = make_local_filename(config, page);
+    assert filename.to_str() == ~"output/dir/test.json";
+}
+
 #[test]
 fn should_name_mod_file_names_by_path() {
     let config = Config {
@@ -280,7 +298,7 @@ mod test {
     }
 }
 
-fn write_file(path: &Path, s: ~str) {
+pub fn write_file(path: &Path, s: ~str) {
     use io::WriterUtil;
 
     match io::file_writer(path, ~[io::Create, io::Truncate]) {
@@ -0,0 +1,337 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Copies re-exported items into the modules that re-export them.
+
+Every item named by a `pub use` is documented again, under its new name, in
+the module containing the `use`, and marked as a re-export.  Only items of
+the documented crate can be copied, since the metadata of other crates
+doesn't carry their documentation, and modules are left where they are.
+*/
+
+use core::prelude::*;
+
+use astsrv;
+use doc::ItemUtils;
+use doc;
+use extract::to_str;
+use fold::Fold;
+use fold;
+use pass::Pass;
+
+use core::vec;
+use syntax::ast;
+
+pub fn mk_pass() -> Pass {
+    Pass {
+        name: ~"reexport",
+        f: run
+    }
+}
+
+/// A `pub use` in a module
+struct Reexport {
+    mod_id: doc::AstId,
+    /// The name in the re-exporting module, unless all the items of the
+    /// module named by `path` are re-exported
+    name: Option<~str>,
+    path: ~[~str]
+}
+
+struct Ctxt {
+    // The items to add to each module
+    additions: @~[(doc::AstId, ~[doc::ItemTag])]
+}
+
+impl Ctxt: Clone {
+    fn clone(&self) -> Ctxt {
+        Ctxt {
+            additions: self.additions
+        }
+    }
+}
+
+fn run(srv: astsrv::Srv, doc: doc::Doc) -> doc::Doc {
+    let topmod = doc.cratemod();
+    let mut additions = ~[];
+    for find_reexports(srv).each |reexport| {
+        let modpath = match find_mod(&topmod, reexport.mod_id) {
+          Some(m) => mod_path(&m),
+          None => loop
+        };
+        let path = absolute_path(modpath, reexport.path);
+        let items = match reexport.name {
+          Some(ref name) => {
+            match find_item(&topmod, path) {
+              Some(item) => ~[reexported(&item, copy *name, copy modpath)],
+              None => ~[]
+            }
+          }
+          None => {
+            match find_item(&topmod, path) {
+              Some(doc::ModTag(m)) => do m.items.map |item| {
+                reexported(item, item.name(), copy modpath)
+              },
+              _ => ~[]
+            }
+          }
+        };
+        let items = do items.filtered |item| { is_copyable(item) };
+        if !items.is_empty() {
+            additions.push((reexport.mod_id, items));
+        }
+    }
+
+    let ctxt = Ctxt {
+        additions: @additions
+    };
+    let fold = Fold {
+        ctxt: ctxt.clone(),
+        fold_mod: fold_mod,
+        .. fold::default_any_fold(ctxt)
+    };
+    (fold.fold_doc)(&fold, doc)
+}
+
+fn find_reexports(srv: astsrv::Srv) -> ~[Reexport] {
+    do astsrv::exec(srv) |ctxt| {
+        let mut reexports = ~[];
+        find_mod_reexports(&mut reexports, ast::crate_node_id,
+                           &ctxt.ast.node.module);
+        reexports
+    }
+}
+
+fn find_mod_reexports(
+    reexports: &mut ~[Reexport],
+    mod_id: doc::AstId,
+    module: &ast::_mod
+) {
+    for module.view_items.each |view_item| {
+        if view_item.vis != ast::public { loop; }
+        match view_item.node {
+          ast::view_item_import(ref view_paths) => {
+            for view_paths.each |view_path| {
+                match view_path.node {
+                  ast::view_path_simple(ident, path, _, _) => {
+                    reexports.push(Reexport {
+                        mod_id: mod_id,
+                        name: Some(to_str(ident)),
+                        path: path.idents.map(|i| to_str(*i))
+                    });
+                  }
+                  ast::view_path_glob(path, _) => {
+                    reexports.push(Reexport {
+                        mod_id: mod_id,
+                        name: None,
+                        path: path.idents.map(|i| to_str(*i))
+                    });
+                  }
+                  ast::view_path_list(path, ref idents, _) => {
+                    for idents.each |ident| {
+                        let name = to_str(ident.node.name);
+                        reexports.push(Reexport {
+                            mod_id: mod_id,
+                            name: Some(copy name),
+                            path: path.idents.map(|i| to_str(*i)) + ~[name]
+                        });
+                    }
+                  }
+                }
+            }
+          }
+          ast::view_item_use(*) => ()
+        }
+    }
+
+    for module.items.each |item| {
+        match item.node {
+          ast::item_mod(ref m) => find_mod_reexports(reexports, item.id, m),
+          _ => ()
+        }
+    }
+}
+
+// `use` paths start at the crate root, unless they start with `self` or
+// `super`
+fn absolute_path(modpath: &[~str], path: &[~str]) -> ~[~str] {
+    if path.is_empty() {
+        return ~[];
+    }
+    if path[0] == ~"self" {
+        vec::from_slice(modpath) + vec::tail(path)
+    } else if path[0] == ~"super" && !modpath.is_empty() {
+        vec::from_slice(vec::init(modpath)) + vec::tail(path)
+    } else {
+        vec::from_slice(path)
+    }
+}
+
+fn mod_path(doc: &doc::ModDoc) -> ~[~str] {
+    if doc.id() == ast::crate_node_id {
+        ~[]
+    } else {
+        doc.path() + ~[doc.name()]
+    }
+}
+
+fn find_mod(doc: &doc::ModDoc, id: doc::AstId) -> Option<doc::ModDoc> {
+    if doc.id() == id {
+        return Some(copy *doc);
+    }
+    for doc.mods().each |m| {
+        match find_mod(m, id) {
+          Some(m) => return Some(m),
+          None => ()
+        }
+    }
+    None
+}
+
+fn find_item(doc: &doc::ModDoc, path: &[~str]) -> Option<doc::ItemTag> {
+    if path.is_empty() {
+        return None;
+    }
+    for doc.items.each |item| {
+        if item.name() != path[0] { loop; }
+        if path.len() == 1u {
+            match *item {
+              // Impls have no names to be re-exported by
+              doc::ImplTag(_) => (),
+              _ => return Some(copy *item)
+            }
+        } else {
+            match *item {
+              doc::ModTag(ref m) => return find_item(m, vec::tail(path)),
+              _ => ()
+            }
+        }
+    }
+    None
+}
+
+fn is_copyable(doc: &doc::ItemTag) -> bool {
+    match *doc {
+      doc::ModTag(_) | doc::NmodTag(_) | doc::ImplTag(_) => false,
+      _ => true
+    }
+}
+
+/// A copy of an item for the module at `path`, where it is called `name`
+fn reexported(doc: &doc::ItemTag, name: ~str, path: ~[~str]) -> doc::ItemTag {
+    fn moved(item: &doc::ItemDoc, name: ~str, path: ~[~str]) -> doc::ItemDoc {
+        doc::ItemDoc {
+            name: name,
+            path: path,
+            reexport: true,
+            .. copy *item
+        }
+    }
+
+    match *doc {
+      doc::ConstTag(ref d) => doc::ConstTag(doc::SimpleItemDoc {
+          item: moved(&d.item, name, path), .. copy *d
+      }),
+      doc::FnTag(ref d) => doc::FnTag(doc::SimpleItemDoc {
+          item: moved(&d.item, name, path), .. copy *d
+      }),
+      doc::TyTag(ref d) => doc::TyTag(doc::SimpleItemDoc {
+          item: moved(&d.item, name, path), .. copy *d
+      }),
+      doc::EnumTag(ref d) => doc::EnumTag(doc::EnumDoc {
+          item: moved(&d.item, name, path), .. copy *d
+      }),
+      doc::TraitTag(ref d) => doc::TraitTag(doc::TraitDoc {
+          item: moved(&d.item, name, path), .. copy *d
+      }),
+      doc::StructTag(ref d) => doc::StructTag(doc::StructDoc {
+          item: moved(&d.item, name, path), .. copy *d
+      }),
+      doc::ModTag(_) | doc::NmodTag(_) | doc::ImplTag(_) => copy *doc
+    }
+}
+
+fn fold_mod(fold: &fold::Fold<Ctxt>, doc: doc::ModDoc) -> doc::ModDoc {
+    let doc = fold::default_any_fold_mod(fold, doc);
+    let mut added = ~[];
+    for fold.ctxt.additions.each |&(id, ref items)| {
+        if id == doc.id() {
+            added += copy *items;
+        }
+    }
+    doc::ModDoc {
+        items: doc.items + added,
+        .. doc
+    }
+}
+
+#[test]
+fn should_copy_reexported_items() {
+    let doc = test::mk_doc(
+        ~"pub use a::b::f; mod a { pub mod b { pub fn f() { } } }");
+    let f = doc.cratemod().fns()[0];
+    assert f.name() == ~"f";
+    assert f.path().is_empty();
+    assert f.item.reexport;
+    assert !doc.cratemod().mods()[0].mods()[0].fns()[0].item.reexport;
+}
+
+#[test]
+fn should_rename_reexported_items() {
+    let doc = test::mk_doc(~"pub use g = a::f; mod a { pub fn f() { } }");
+    assert doc.cratemod().fns()[0].name() == ~"g";
+}
+
+#[test]
+fn should_copy_lists_of_reexported_items() {
+    let doc = test::mk_doc(
+        ~"mod a { pub use b::{S, T}; } \
+          mod b { pub struct S { x: int } pub trait T { fn f(); } }");
+    assert doc.cratemod().mods()[0].structs()[0].path() == ~[~"a"];
+    assert doc.cratemod().mods()[0].traits()[0].name() == ~"T";
+}
+
+#[test]
+fn should_copy_glob_reexports() {
+    let doc = test::mk_doc(
+        ~"pub use a::*; mod a { pub fn f() { } pub const c: int = 0; }");
+    assert doc.cratemod().fns()[0].name() == ~"f";
+    assert doc.cratemod().consts()[0].name() == ~"c";
+}
+
+#[test]
+fn should_not_copy_private_imports() {
+    let doc = test::mk_doc(~"use a::f; mod a { pub fn f() { } }");
+    assert doc.cratemod().fns().is_empty();
+}
+
+#[test]
+fn should_not_copy_modules() {
+    let doc = test::mk_doc(~"pub use a::b; mod a { pub mod b { } }");
+    assert doc.cratemod().mods().len() == 1u;
+}
+
+#[cfg(test)]
+mod test {
+    use astsrv;
+    use doc;
+    use extract;
+    use path_pass;
+    use reexport_pass::run;
+
+    pub fn mk_doc(source: ~str) -> doc::Doc {
+        do astsrv::from_str(copy source) |srv| {
+            let doc = extract::from_srv(srv.clone(), ~"");
+            let doc = (path_pass::mk_pass().f)(srv.clone(), doc);
+            run(srv.clone(), doc)
+        }
+    }
+}
@@ -0,0 +1,386 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Compiles and runs the code examples of a crate's documentation.
+
+Every fenced code block in a description or section is a test, unless its
+fence names a language other than Rust.  Unless it defines its own `main`,
+the example is wrapped in one, and it is linked against the documented
+crate, which must already have been built.  The words `ignore` and
+`should_fail` after the fence have their usual meaning for tests:
+
+~~~ {.rust .should_fail}
+let v: ~[int] = ~[];
+v[0];
+~~~
+*/
+
+use core::prelude::*;
+
+use config;
+use doc::ItemUtils;
+use doc;
+use pass::Pass;
+
+use core::char;
+use core::io;
+use core::os;
+use core::path::Path;
+use core::run;
+use core::str;
+use core::task;
+use rustc::driver::driver;
+use rustc::driver::session;
+use std::tempfile;
+use std::test::{TestDesc, TestDescAndFn, TestOpts, run_tests_console};
+use syntax::ast;
+use syntax::diagnostic;
+
+pub fn mk_pass(config: config::Config) -> Pass {
+    Pass {
+        name: ~"test",
+        f: |_srv, doc| run(doc, copy config)
+    }
+}
+
+/// A code example found in the documentation
+#[deriving_eq]
+pub struct Example {
+    /// The item documented by the example, and its number within the item
+    name: ~str,
+    code: ~str,
+    ignore: bool,
+    should_fail: bool
+}
+
+fn run(doc: doc::Doc, config: config::Config) -> doc::Doc {
+    let crate_name = crate_name(&doc);
+    let examples = extract_examples(&doc);
+    let dir = match tempfile::mkdtemp(&os::tmpdir(), "rustdoc-test") {
+      Some(dir) => dir,
+      None => die!(~"couldn't create a directory for the examples")
+    };
+
+    // The compiler can't run in several tasks at once, and the test runner
+    // runs tests in parallel, so the examples are all compiled here, one
+    // after another, and the tests only run them
+    let mut tests = ~[];
+    for examples.eachi |i, example| {
+        let exe = dir.push(fmt!("example-%u", i));
+        let compiled = example.ignore ||
+            compile(make_program(crate_name, example.code), &exe,
+                    copy config.lib_paths);
+        let should_fail = example.should_fail;
+        tests.push(TestDescAndFn {
+            desc: TestDesc {
+                name: copy example.name,
+                ignore: example.ignore,
+                should_fail: should_fail
+            },
+            testfn: fn~(move exe) {
+                run_example(&exe, compiled, should_fail)
+            }
+        });
+    }
+
+    let opts = TestOpts {
+        filter: None,
+        run_ignored: false,
+        logfile: None
+    };
+    let passed = run_tests_console(&opts, move tests);
+    os::remove_dir(&dir);
+    if !passed {
+        die!(~"some examples failed");
+    }
+    doc
+}
+
+/// The name that examples use to link to the documented crate
+fn crate_name(doc: &doc::Doc) -> ~str {
+    // Without a `link` attribute the crate is named after its file
+    let name = doc.cratemod().name();
+    Path(name).filestem().get_or_default(name)
+}
+
+fn run_example(exe: &Path, compiled: bool, should_fail: bool) {
+    if !compiled {
+        // An example that is expected to fail still has to compile, so
+        // don't let a compile error pass for the expected failure
+        if should_fail {
+            io::stderr().write_line(~"example failed to compile");
+            return;
+        }
+        die!(~"example failed to compile");
+    }
+
+    let output = run::program_output(exe.to_str(), ~[]);
+    os::remove_file(exe);
+    if output.status != 0 {
+        die!(fmt!("example exited with status %d\n%s%s",
+                  output.status, output.out, output.err));
+    }
+}
+
+// Compiles an example to `exe`, returning whether it compiled
+fn compile(program: ~str, exe: &Path, lib_paths: ~[Path]) -> bool {
+    let out = copy *exe;
+    task::try(fn~(move program, move out, move lib_paths) {
+        build(copy program, &out, copy lib_paths)
+    }).is_ok()
+}
+
+fn build(program: ~str, exe: &Path, lib_paths: ~[Path]) {
+    let options: @session::options = @{
+        crate_type: session::bin_crate,
+        binary: ~"rustdoc",
+        addl_lib_search_paths: lib_paths,
+        .. *session::basic_options()
+    };
+    let input = driver::str_input(program);
+    let sess = driver::build_session(options, diagnostic::emit);
+    let cfg = driver::build_configuration(sess, ~"rustdoc", input);
+    let outputs = driver::build_output_filenames(
+        input, &None, &Some(copy *exe), sess);
+    driver::compile_upto(sess, cfg, input, driver::cu_everything,
+                         Some(outputs));
+}
+
+/// Turns an example into a program using the documented crate
+pub fn make_program(crate_name: &str, code: &str) -> ~str {
+    let mut program = ~"";
+    if !str::contains(code, "extern mod") {
+        program += fmt!("extern mod %s;\n", crate_name);
+    }
+    if str::contains(code, "fn main") {
+        program + code + ~"\n"
+    } else {
+        program + ~"fn main() {\n" + code + ~"\n}\n"
+    }
+}
+
+/// Collects the examples of every item, in the order they are documented
+pub fn extract_examples(doc: &doc::Doc) -> ~[Example] {
+    let mut examples = ~[];
+    extract_from_mod(&mut examples, &doc.cratemod());
+    examples
+}
+
+fn item_path(item: &doc::ItemTag) -> ~str {
+    str::connect(item.path() + ~[item.name()], ~"::")
+}
+
+fn extract_from_mod(examples: &mut ~[Example], doc: &doc::ModDoc) {
+    let name = if doc.id() == ast::crate_node_id {
+        ~"crate"
+    } else {
+        item_path(&doc::ModTag(copy *doc))
+    };
+    extract_from_item(examples, name, doc.desc(), doc.sections());
+
+    for doc.items.each |item| {
+        let name = item_path(item);
+        extract_from_item(examples, copy name, item.desc(), item.sections());
+        match *item {
+          doc::ModTag(ref m) => extract_from_mod(examples, m),
+          doc::NmodTag(ref n) => {
+            for n.fns.each |f| {
+                extract_from_item(examples,
+                                  item_path(&doc::FnTag(copy *f)),
+                                  f.desc(), f.sections());
+            }
+          }
+          doc::TraitTag(ref t) => {
+            for t.methods.each |m| {
+                extract_from_item(examples, name + ~"::" + m.name,
+                                  copy m.desc, m.sections);
+            }
+          }
+          doc::ImplTag(ref i) => {
+            for i.methods.each |m| {
+                extract_from_item(examples, name + ~"::" + m.name,
+                                  copy m.desc, m.sections);
+            }
+          }
+          doc::EnumTag(ref e) => {
+            for e.variants.each |v| {
+                extract_from_item(examples, name + ~"::" + v.name,
+                                  copy v.desc, ~[]);
+            }
+          }
+          _ => ()
+        }
+    }
+}
+
+fn extract_from_item(
+    examples: &mut ~[Example],
+    name: ~str,
+    desc: Option<~str>,
+    sections: &[doc::Section]
+) {
+    let mut blocks = ~[];
+    for desc.each |desc| {
+        blocks += code_blocks(*desc);
+    }
+    for sections.each |section| {
+        blocks += code_blocks(section.body);
+    }
+
+    let numbered = blocks.len() > 1u;
+    for blocks.eachi |i, block| {
+        let (info, code) = copy *block;
+        let words = info_words(info);
+        let is_rust = do words.all |word| {
+            *word == ~"rust" || *word == ~"ignore" ||
+                *word == ~"should_fail"
+        };
+        if !is_rust { loop; }
+
+        examples.push(Example {
+            name: if numbered {
+                fmt!("%s (example %u)", name, i + 1u)
+            } else {
+                copy name
+            },
+            code: code,
+            ignore: words.contains(&~"ignore"),
+            should_fail: words.contains(&~"should_fail")
+        });
+    }
+}
+
+fn is_fence(line: &str) -> bool {
+    str::starts_with(line, "~~~") || str::starts_with(line, "```")
+}
+
+/// Returns the text after the opening fence and the code of each fenced
+/// block
+fn code_blocks(text: &str) -> ~[(~str, ~str)] {
+    let mut blocks = ~[];
+    let mut in_block = false;
+    let mut info = ~"";
+    let mut lines = ~[];
+    for str::lines_each(text) |line| {
+        let trimmed = str::trim(line);
+        if !in_block {
+            if is_fence(trimmed) {
+                in_block = true;
+                info = str::trim_left_chars(trimmed, ['~', '`']);
+                lines = ~[];
+            }
+        } else if is_fence(trimmed) {
+            blocks.push((copy info, str::connect(lines, ~"\n")));
+            in_block = false;
+        } else {
+            lines.push(str::from_slice(line));
+        }
+    }
+    blocks
+}
+
+/// Splits the text after a fence, such as `{.rust .ignore}` or `ignore`,
+/// into words
+fn info_words(info: &str) -> ~[~str] {
+    let mut words = ~[];
+    let mut word = ~"";
+    for str::each_char(info) |c| {
+        if char::is_alphanumeric(c) || c == '_' {
+            str::push_char(&mut word, c);
+        } else if !word.is_empty() {
+            words.push(word);
+            word = ~"";
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+#[test]
+fn should_wrap_examples_in_main() {
+    assert make_program("foo", "bar();") ==
+        ~"extern mod foo;\nfn main() {\nbar();\n}\n";
+}
+
+#[test]
+fn should_not_wrap_examples_with_main() {
+    assert make_program("foo", "extern mod foo;\nfn main() { }") ==
+        ~"extern mod foo;\nfn main() { }\n";
+}
+
+#[test]
+fn should_extract_fenced_blocks() {
+    let examples = test::extract(
+        ~"#[doc = \"f\n\n~~~\nf();\n~~~\n\nindented\n\n    x\"] fn f() { }");
+    assert examples == ~[Example {
+        name: ~"f",
+        code: ~"f();",
+        ignore: false,
+        should_fail: false
+    }];
+}
+
+#[test]
+fn should_extract_blocks_from_sections() {
+    let examples = test::extract(
+        ~"mod a { #[doc = \"# Example\n\n~~~\nb();\n~~~\"] fn b() { } }");
+    assert examples.map(|e| copy e.name) == ~[~"a::b"];
+}
+
+#[test]
+fn should_number_several_examples_of_an_item() {
+    let examples = test::extract(
+        ~"#[doc = \"~~~\na\n~~~\n\n~~~\nb\n~~~\"] fn f() { }");
+    assert examples.map(|e| copy e.name) ==
+        ~[~"f (example 1)", ~"f (example 2)"];
+}
+
+#[test]
+fn should_honor_ignore_and_should_fail() {
+    let examples = test::extract(
+        ~"#[doc = \"~~~ {.rust .ignore}\na\n~~~\n\n\
+          ```should_fail\nb\n```\"] fn f() { }");
+    assert examples[0].ignore && !examples[0].should_fail;
+    assert !examples[1].ignore && examples[1].should_fail;
+}
+
+#[test]
+fn should_skip_blocks_in_other_languages() {
+    let examples = test::extract(
+        ~"#[doc = \"~~~ {.sh}\nmake\n~~~\"] fn f() { }");
+    assert examples.is_empty();
+}
+
+#[cfg(test)]
+mod test {
+    use astsrv;
+    use attr_pass;
+    use extract;
+    use path_pass;
+    use sectionalize_pass;
+    use test_pass::{Example, extract_examples};
+    use trim_pass;
+    use unindent_pass;
+
+    pub fn extract(source: ~str) -> ~[Example] {
+        do astsrv::from_str(source) |srv| {
+            let doc = extract::from_srv(srv.clone(), ~"");
+            let doc = (path_pass::mk_pass().f)(srv.clone(), doc);
+            let doc = (attr_pass::mk_pass().f)(srv.clone(), doc);
+            let doc = (unindent_pass::mk_pass().f)(srv.clone(), doc);
+            let doc = (sectionalize_pass::mk_pass().f)(srv.clone(), doc);
+            let doc = (trim_pass::mk_pass().f)(srv.clone(), doc);
+            extract_examples(&doc)
+        }
+    }
+}
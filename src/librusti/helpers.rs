@@ -0,0 +1,44 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The functions that rusti's programs use, which follow the view items of
+// the session and precede the input's own code.
+
+fn print<T>(result: &T) {
+    io::println(fmt!("%?", *result));
+}
+
+// Moves a value kept by an earlier input out of its slot, leaving the slot
+// empty
+fn rusti_load<T: Owned>(slot: uint) -> T {
+    unsafe {
+        let cell: *mut uint = cast::transmute(slot);
+        let value: ~T = cast::transmute(*cell);
+        *cell = 0;
+        let ~value = move value;
+        move value
+    }
+}
+
+// Moves the value of a variable into a slot, for later inputs, if rusti
+// decided to keep it once it knew the variable's type and the moves of the
+// program: the word at `flag` is 1 to keep the variable, and 0 not to.
+fn rusti_keep<T>(slot: uint, var: *T, flag: uint) {
+    unsafe {
+        if *(flag as *uint) == 0 {
+            return;
+        }
+        let value: T = cast::reinterpret_cast(&*var);
+        // Zero the variable, as a move would, so it isn't dropped
+        ptr::set_memory(cast::transmute_mut_unsafe(var), 0, 1);
+        let cell: *mut uint = cast::transmute(slot);
+        *cell = cast::transmute(~value);
+    }
+}
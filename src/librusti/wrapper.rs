@@ -23,7 +23,3 @@
 #[allow(while_true)];
 
 extern mod std;
-
-fn print<T>(result: T) {
-    io::println(fmt!("%?", result));
-}
@@ -0,0 +1,140 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * An implementation of the SHA-256 cryptographic hash, as given in FIPS
+ * 180-2. Like `md4`, it hashes a whole message at once.
+ */
+
+use core::uint;
+use core::vec;
+
+// The round constants: the first 32 bits of the fractional parts of the
+// cube roots of the first 64 primes
+const k: [u32 * 64] = [
+    0x428a2f98u32, 0x71374491u32, 0xb5c0fbcfu32, 0xe9b5dba5u32,
+    0x3956c25bu32, 0x59f111f1u32, 0x923f82a4u32, 0xab1c5ed5u32,
+    0xd807aa98u32, 0x12835b01u32, 0x243185beu32, 0x550c7dc3u32,
+    0x72be5d74u32, 0x80deb1feu32, 0x9bdc06a7u32, 0xc19bf174u32,
+    0xe49b69c1u32, 0xefbe4786u32, 0x0fc19dc6u32, 0x240ca1ccu32,
+    0x2de92c6fu32, 0x4a7484aau32, 0x5cb0a9dcu32, 0x76f988dau32,
+    0x983e5152u32, 0xa831c66du32, 0xb00327c8u32, 0xbf597fc7u32,
+    0xc6e00bf3u32, 0xd5a79147u32, 0x06ca6351u32, 0x14292967u32,
+    0x27b70a85u32, 0x2e1b2138u32, 0x4d2c6dfcu32, 0x53380d13u32,
+    0x650a7354u32, 0x766a0abbu32, 0x81c2c92eu32, 0x92722c85u32,
+    0xa2bfe8a1u32, 0xa81a664bu32, 0xc24b8b70u32, 0xc76c51a3u32,
+    0xd192e819u32, 0xd6990624u32, 0xf40e3585u32, 0x106aa070u32,
+    0x19a4c116u32, 0x1e376c08u32, 0x2748774cu32, 0x34b0bcb5u32,
+    0x391c0cb3u32, 0x4ed8aa4au32, 0x5b9cca4fu32, 0x682e6ff3u32,
+    0x748f82eeu32, 0x78a5636fu32, 0x84c87814u32, 0x8cc70208u32,
+    0x90befffau32, 0xa4506cebu32, 0xbef9a3f7u32, 0xc67178f2u32
+];
+
+/// The SHA-256 digest of `msg`, as 32 bytes
+pub pure fn sha256(msg: &[u8]) -> ~[u8] {
+    let orig_len: u64 = (vec::len(msg) * 8u) as u64;
+
+    // pad the message to 56 bytes short of a whole block
+    let mut msg = vec::append(vec::from_slice(msg), ~[0x80u8]);
+    while vec::len(msg) % 64u != 56u {
+        unsafe { msg.push(0u8); }
+    }
+
+    // append the length, most significant byte first
+    let mut i = 8u64;
+    while i > 0u64 {
+        i -= 1u64;
+        unsafe { msg.push((orig_len >> (i * 8u64)) as u8); }
+    }
+
+    let h = vec::cast_to_mut(~[
+        0x6a09e667u32, 0xbb67ae85u32, 0x3c6ef372u32, 0xa54ff53au32,
+        0x510e527fu32, 0x9b05688cu32, 0x1f83d9abu32, 0x5be0cd19u32
+    ]);
+    let w = vec::cast_to_mut(vec::from_elem(64u, 0u32));
+
+    pure fn rotr(x: u32, n: u32) -> u32 {
+        (x >> n) | (x << (32u32 - n))
+    }
+
+    let mut base = 0u;
+    let len = vec::len(msg);
+    while base < len {
+        let mut t = 0u;
+        while t < 16u {
+            let i = base + t * 4u;
+            w[t] = (msg[i] as u32 << 24u32) | (msg[i + 1u] as u32 << 16u32) |
+                (msg[i + 2u] as u32 << 8u32) | (msg[i + 3u] as u32);
+            t += 1u;
+        }
+        while t < 64u {
+            let s0 = rotr(w[t - 15u], 7u32) ^ rotr(w[t - 15u], 18u32) ^
+                (w[t - 15u] >> 3u32);
+            let s1 = rotr(w[t - 2u], 17u32) ^ rotr(w[t - 2u], 19u32) ^
+                (w[t - 2u] >> 10u32);
+            w[t] = w[t - 16u] + s0 + w[t - 7u] + s1;
+            t += 1u;
+        }
+
+        let mut a = h[0], b = h[1], c = h[2], d = h[3];
+        let mut e = h[4], f = h[5], g = h[6], hh = h[7];
+        t = 0u;
+        while t < 64u {
+            let s1 = rotr(e, 6u32) ^ rotr(e, 11u32) ^ rotr(e, 25u32);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = hh + s1 + ch + k[t] + w[t];
+            let s0 = rotr(a, 2u32) ^ rotr(a, 13u32) ^ rotr(a, 22u32);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0 + maj;
+            hh = g; g = f; f = e; e = d + t1;
+            d = c; c = b; b = a; a = t1 + t2;
+            t += 1u;
+        }
+
+        h[0] += a; h[1] += b; h[2] += c; h[3] += d;
+        h[4] += e; h[5] += f; h[6] += g; h[7] += hh;
+        base += 64u;
+    }
+
+    let mut result = ~[];
+    let mut j = 0u;
+    while j < 8u {
+        let mut i = 4u32;
+        while i > 0u32 {
+            i -= 1u32;
+            unsafe { result.push((h[j] >> (i * 8u32)) as u8); }
+        }
+        j += 1u;
+    }
+    result
+}
+
+/// The SHA-256 digest of `msg`, as 64 lowercase hex digits
+pub pure fn sha256_str(msg: &[u8]) -> ~str {
+    let mut result = ~"";
+    for sha256(msg).each |byte| {
+        if *byte < 16u8 { result += ~"0"; }
+        result += uint::to_str_radix(*byte as uint, 16u);
+    }
+    result
+}
+
+#[test]
+fn test_sha256() {
+    // Examples from FIPS 180-2
+    assert sha256_str(core::str::to_bytes("abc")) ==
+        ~"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+    assert sha256_str(core::str::to_bytes(
+        "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")) ==
+        ~"248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1";
+    assert sha256_str([]) ==
+        ~"e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+    assert sha256([]).len() == 32u;
+}
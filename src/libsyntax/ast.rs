@@ -754,6 +754,10 @@ pub enum expr_ {
 
     expr_mac(mac),
 
+    // Stands in for an expression with a syntax error in it, so that the
+    // parser can carry on and report the errors after it.
+    expr_err,
+
     // A struct literal expression.
     expr_struct(@path, ~[field], Option<@expr>),
 
@@ -877,6 +881,7 @@ pub enum lit_ {
     lit_float_unsuffixed(@~str),
     lit_nil,
     lit_bool(bool),
+    lit_binary(@~[u8]),
 }
 
 pub impl lit_: cmp::Eq {
@@ -896,6 +901,7 @@ pub impl lit_: cmp::Eq {
             (lit_float_unsuffixed(a), lit_float_unsuffixed(b)) => a == b,
             (lit_nil, lit_nil) => true,
             (lit_bool(a), lit_bool(b)) => a == b,
+            (lit_binary(a), lit_binary(b)) => a == b,
             (lit_str(_), _) => false,
             (lit_int(*), _) => false,
             (lit_uint(*), _) => false,
@@ -903,7 +909,8 @@ pub impl lit_: cmp::Eq {
             (lit_float(*), _) => false,
             (lit_float_unsuffixed(*), _) => false,
             (lit_nil, _) => false,
-            (lit_bool(_), _) => false
+            (lit_bool(_), _) => false,
+            (lit_binary(_), _) => false
         }
     }
     pure fn ne(&self, other: &lit_) -> bool { !(*self).eq(other) }
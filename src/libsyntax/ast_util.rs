@@ -180,6 +180,27 @@ pub pure fn uint_ty_to_str(t: uint_ty) -> ~str {
     }
 }
 
+/// The source text of a byte string literal for `bytes`
+pub fn binary_lit_to_str(bytes: &[u8]) -> ~str {
+    let mut s = ~"b\"";
+    for bytes.each |b| {
+        let c = *b as char;
+        match c {
+          '\n' => str::push_str(&mut s, "\\n"),
+          '\r' => str::push_str(&mut s, "\\r"),
+          '\t' => str::push_str(&mut s, "\\t"),
+          '\\' | '"' => {
+            str::push_char(&mut s, '\\');
+            str::push_char(&mut s, c);
+          }
+          ' ' .. '~' => str::push_char(&mut s, c),
+          _ => str::push_str(&mut s, fmt!("\\x%02x", *b as uint))
+        }
+    }
+    str::push_char(&mut s, '"');
+    s
+}
+
 pub pure fn uint_ty_max(t: uint_ty) -> u64 {
     match t {
       ty_u8 => 0xffu64,
@@ -204,6 +204,29 @@ fn eq(a: @ast::meta_item, b: @ast::meta_item) -> bool {
         }
 }
 
+/**
+ * Determines whether a configuration predicate, one of the items of a
+ * #[cfg(...)] attribute or cfg!(...) invocation, holds for the crate
+ * configuration. Words and name/value pairs hold if they are in the
+ * configuration; `not(p)` holds if `p` does not (and `not(p, q, ...)` if
+ * none of them do), `all(p, q, ...)` if every one of them does, and
+ * `any(p, q, ...)` if at least one does.
+ */
+pub fn cfg_matches(cfg: &[@ast::meta_item], mi: @ast::meta_item) -> bool {
+    match mi.node {
+        ast::meta_list(ref name, ref mis) => {
+            match *name {
+                ~"not" => !mis.any(|mi| cfg_matches(cfg, *mi)),
+                ~"all" => mis.all(|mi| cfg_matches(cfg, *mi)),
+                ~"any" => mis.any(|mi| cfg_matches(cfg, *mi)),
+                // No other lists can be set with --cfg
+                _ => false
+            }
+        }
+        _ => contains(cfg, mi)
+    }
+}
+
 pub fn contains_name(metas: &[@ast::meta_item], name: &str) -> bool {
     let matches = find_meta_items_by_name(metas, name);
     return vec::len(matches) > 0u;
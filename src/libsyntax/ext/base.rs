@@ -90,6 +90,8 @@ pub fn syntax_expander_table() -> HashMap<~str, SyntaxExtension> {
                                 ext::tt::macro_rules::add_new_extension));
     syntax_expanders.insert(~"fmt",
                             builtin_normal_tt(ext::fmt::expand_syntax_ext));
+    syntax_expanders.insert(~"write_fmt",
+                            builtin_normal_tt(ext::fmt::expand_write));
     syntax_expanders.insert(
         ~"auto_encode",
         ItemDecorator(ext::auto_encode::expand_auto_encode));
@@ -98,6 +100,8 @@ pub fn syntax_expander_table() -> HashMap<~str, SyntaxExtension> {
         ItemDecorator(ext::auto_encode::expand_auto_decode));
     syntax_expanders.insert(~"env",
                             builtin_normal_tt(ext::env::expand_syntax_ext));
+    syntax_expanders.insert(~"cfg",
+                            builtin_normal_tt(ext::cfg::expand_cfg));
     syntax_expanders.insert(~"concat_idents",
                             builtin_normal_tt(
                                 ext::concat_idents::expand_syntax_ext));
@@ -110,6 +114,18 @@ pub fn syntax_expander_table() -> HashMap<~str, SyntaxExtension> {
     syntax_expanders.insert(~"deriving_iter_bytes",
                             ItemDecorator(
                                 ext::deriving::expand_deriving_iter_bytes));
+    syntax_expanders.insert(~"deriving_clone",
+                            ItemDecorator(
+                                ext::deriving::expand_deriving_clone));
+    syntax_expanders.insert(~"deriving_ord",
+                            ItemDecorator(
+                                ext::deriving::expand_deriving_ord));
+    syntax_expanders.insert(~"deriving_to_str",
+                            ItemDecorator(
+                                ext::deriving::expand_deriving_to_str));
+    syntax_expanders.insert(~"deriving_rand",
+                            ItemDecorator(
+                                ext::deriving::expand_deriving_rand));
 
     // Quasi-quoting expanders
     syntax_expanders.insert(
@@ -184,6 +200,9 @@ pub trait ext_ctxt {
     /* for unhygienic identifier transformation */
     fn str_of(id: ast::ident) -> ~str;
     fn ident_of(st: ~str) -> ast::ident;
+    /* the sources of the macro definitions marked #[macro_export] */
+    fn export_macro(source: ~str);
+    fn exported_macros() -> ~[~str];
 }
 
 pub fn mk_ctxt(parse_sess: parse::parse_sess,
@@ -192,7 +211,8 @@ pub fn mk_ctxt(parse_sess: parse::parse_sess,
                       cfg: ast::crate_cfg,
                       mut backtrace: Option<@ExpnInfo>,
                       mut mod_path: ~[ast::ident],
-                      mut trace_mac: bool};
+                      mut trace_mac: bool,
+                      mut exported_macros: ~[~str]};
     impl ctxt_repr: ext_ctxt {
         fn codemap() -> @CodeMap { self.parse_sess.cm }
         fn parse_sess() -> parse::parse_sess { self.parse_sess }
@@ -269,13 +289,20 @@ pub fn mk_ctxt(parse_sess: parse::parse_sess,
         fn ident_of(st: ~str) -> ast::ident {
             self.parse_sess.interner.intern(@st)
         }
+        fn export_macro(source: ~str) {
+            self.exported_macros.push(source)
+        }
+        fn exported_macros() -> ~[~str] {
+            copy self.exported_macros
+        }
     }
     let imp: ctxt_repr = {
         parse_sess: parse_sess,
         cfg: cfg,
         mut backtrace: None,
         mut mod_path: ~[],
-        mut trace_mac: false
+        mut trace_mac: false,
+        mut exported_macros: ~[]
     };
     move ((move imp) as ext_ctxt)
 }
@@ -320,7 +347,8 @@ pub fn get_single_str_from_tts(cx: ext_ctxt,
     }
 
     match tts[0] {
-        ast::tt_tok(_, token::LIT_STR(ident)) => cx.str_of(ident),
+        ast::tt_tok(_, token::LIT_STR(ident)) |
+        ast::tt_tok(_, token::LIT_STR_RAW(ident, _)) => cx.str_of(ident),
         _ =>
         cx.span_fatal(sp, fmt!("%s requires a string.", name))
     }
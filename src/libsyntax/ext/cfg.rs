@@ -0,0 +1,56 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*
+ * The compiler code necessary to support the cfg! extension, which expands
+ * to a bool literal: whether the crate configuration satisfies the given
+ * predicates, in the same way as for the #[cfg] attribute.
+ */
+
+use ast;
+use attr;
+use codemap::span;
+use ext::base::*;
+use ext::base;
+use ext::build::mk_bool;
+use parse;
+use parse::attr::parser_attr;
+use parse::token;
+
+use core::prelude::*;
+
+pub fn expand_cfg(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
+    -> base::MacResult {
+    let p = parse::new_parser_from_tts(cx.parse_sess(), cx.cfg(), tts);
+
+    let mut cfgs = ~[];
+    while p.token != token::EOF {
+        if !cfgs.is_empty() {
+            p.expect(token::COMMA);
+        }
+        cfgs.push(p.parse_meta_item());
+    }
+    if cfgs.is_empty() {
+        cx.span_fatal(sp, "cfg! takes at least one predicate");
+    }
+
+    let cfg = cx.cfg();
+    MRExpr(mk_bool(cx, sp, cfgs.any(|mi| attr::cfg_matches(cfg, *mi))))
+}
+
+//
+// Local Variables:
+// mode: rust
+// fill-column: 78;
+// indent-tabs-mode: nil
+// c-basic-offset: 4
+// buffer-file-coding-system: utf-8-unix
+// End:
+//
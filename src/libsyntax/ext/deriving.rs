@@ -8,19 +8,22 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-/// The compiler code necessary to implement the #[deriving_eq] and
-/// #[deriving_iter_bytes] extensions.
+/// The compiler code necessary to implement the #[deriving_eq],
+/// #[deriving_iter_bytes], #[deriving_clone], #[deriving_ord],
+/// #[deriving_to_str] and #[deriving_rand] extensions.
 
 use core::prelude::*;
 
 use ast;
-use ast::{TraitTyParamBound, Ty, and, bind_by_ref, binop, deref, enum_def};
-use ast::{enum_variant_kind, expr, expr_match, ident, item, item_};
-use ast::{item_enum, item_impl, item_struct, m_imm, meta_item, method};
-use ast::{named_field, or, pat, pat_ident, pat_wild, public, pure_fn};
-use ast::{re_anon, stmt, struct_def, struct_variant_kind};
-use ast::{sty_by_ref, sty_region, tuple_variant_kind, ty_nil, ty_param};
-use ast::{ty_param_bound, ty_path, ty_rptr, unnamed_field, variant};
+use ast::{TraitTyParamBound, Ty, and, arm, bind_by_ref, binop, blk, deref};
+use ast::{enum_def, enum_variant_kind, expr, expr_match, fn_decl, gt, ident};
+use ast::{impure_fn, item, item_, item_enum, item_impl, item_struct, lt};
+use ast::{m_imm, meta_item, method, named_field, not, or, pat, pat_enum};
+use ast::{pat_ident, pat_lit, pat_struct, pat_wild, public, pure_fn, purity};
+use ast::{re_anon, self_ty_, stmt, struct_def, struct_variant_kind};
+use ast::{sty_by_ref, sty_region, sty_static, tuple_variant_kind, ty_nil};
+use ast::{ty_param, ty_param_bound, ty_path, ty_rptr, ty_uniq};
+use ast::{unnamed_field, variant};
 use ext::base::ext_ctxt;
 use ext::build;
 use codemap::{span, spanned};
@@ -28,6 +31,7 @@ use parse::token::special_idents::clownshoes_extensions;
 
 use core::dvec;
 use core::uint;
+use core::vec;
 
 enum Junction {
     Conjunction,
@@ -80,6 +84,54 @@ pub fn expand_deriving_iter_bytes(cx: ext_ctxt,
                     expand_deriving_iter_bytes_enum_def)
 }
 
+pub fn expand_deriving_clone(cx: ext_ctxt,
+                             span: span,
+                             _mitem: meta_item,
+                             in_items: ~[@item])
+                          -> ~[@item] {
+    expand_deriving(cx,
+                    span,
+                    in_items,
+                    expand_deriving_clone_struct_def,
+                    expand_deriving_clone_enum_def)
+}
+
+pub fn expand_deriving_ord(cx: ext_ctxt,
+                           span: span,
+                           _mitem: meta_item,
+                           in_items: ~[@item])
+                        -> ~[@item] {
+    expand_deriving(cx,
+                    span,
+                    in_items,
+                    expand_deriving_ord_struct_def,
+                    expand_deriving_ord_enum_def)
+}
+
+pub fn expand_deriving_to_str(cx: ext_ctxt,
+                              span: span,
+                              _mitem: meta_item,
+                              in_items: ~[@item])
+                           -> ~[@item] {
+    expand_deriving(cx,
+                    span,
+                    in_items,
+                    expand_deriving_to_str_struct_def,
+                    expand_deriving_to_str_enum_def)
+}
+
+pub fn expand_deriving_rand(cx: ext_ctxt,
+                            span: span,
+                            _mitem: meta_item,
+                            in_items: ~[@item])
+                         -> ~[@item] {
+    expand_deriving(cx,
+                    span,
+                    in_items,
+                    expand_deriving_rand_struct_def,
+                    expand_deriving_rand_enum_def)
+}
+
 fn expand_deriving(cx: ext_ctxt,
                    span: span,
                    in_items: ~[@item],
@@ -122,7 +174,8 @@ fn create_impl_item(cx: ext_ctxt, span: span, +item: item_) -> @item {
 }
 
 /// Creates a method from the given expression, the signature of which
-/// conforms to the `eq` or `ne` method.
+/// conforms to the `eq` or `ne` method (or to one of the `Ord` methods,
+/// which share it).
 fn create_eq_method(cx: ext_ctxt,
                     span: span,
                     method_ident: ident,
@@ -285,6 +338,187 @@ fn create_derived_iter_bytes_impl(cx: ext_ctxt,
     create_derived_impl(cx, span, type_ident, ty_params, methods, trait_path)
 }
 
+fn create_derived_clone_impl(cx: ext_ctxt,
+                             span: span,
+                             type_ident: ident,
+                             +ty_params: ~[ty_param],
+                             method: @method)
+                          -> @item {
+    let methods = [ method ];
+    let trait_path = [
+        cx.ident_of(~"core"),
+        cx.ident_of(~"clone"),
+        cx.ident_of(~"Clone")
+    ];
+    create_derived_impl(cx, span, type_ident, ty_params, methods, trait_path)
+}
+
+fn create_derived_ord_impl(cx: ext_ctxt,
+                           span: span,
+                           type_ident: ident,
+                           +ty_params: ~[ty_param],
+                           methods: &[@method])
+                        -> @item {
+    let trait_path = [
+        cx.ident_of(~"core"),
+        cx.ident_of(~"cmp"),
+        cx.ident_of(~"Ord")
+    ];
+    create_derived_impl(cx, span, type_ident, ty_params, methods, trait_path)
+}
+
+fn create_derived_to_str_impl(cx: ext_ctxt,
+                              span: span,
+                              type_ident: ident,
+                              +ty_params: ~[ty_param],
+                              method: @method)
+                           -> @item {
+    let methods = [ method ];
+    let trait_path = [
+        cx.ident_of(~"core"),
+        cx.ident_of(~"to_str"),
+        cx.ident_of(~"ToStr")
+    ];
+    create_derived_impl(cx, span, type_ident, ty_params, methods, trait_path)
+}
+
+fn create_derived_rand_impl(cx: ext_ctxt,
+                            span: span,
+                            type_ident: ident,
+                            +ty_params: ~[ty_param],
+                            method: @method)
+                         -> @item {
+    let methods = [ method ];
+    let trait_path = [
+        cx.ident_of(~"core"),
+        cx.ident_of(~"rand"),
+        cx.ident_of(~"Rand")
+    ];
+    create_derived_impl(cx, span, type_ident, ty_params, methods, trait_path)
+}
+
+// Creates a public method with the given declaration and body.
+fn create_method(cx: ext_ctxt,
+                 span: span,
+                 method_ident: ident,
+                 self_ty: self_ty_,
+                 purity: purity,
+                 +fn_decl: fn_decl,
+                 +body_block: blk)
+              -> @method {
+    let self_ty = spanned { node: self_ty, span: span };
+    @ast::method {
+        ident: method_ident,
+        attrs: ~[],
+        tps: ~[],
+        self_ty: self_ty,
+        purity: purity,
+        decl: move fn_decl,
+        body: move body_block,
+        id: cx.next_id(),
+        span: span,
+        self_id: cx.next_id(),
+        vis: public
+    }
+}
+
+// Creates a method from the given expression conforming to the signature of
+// the `clone` method.
+fn create_clone_method(cx: ext_ctxt,
+                       span: span,
+                       type_ident: ident,
+                       ty_params: &[ty_param],
+                       body: @expr)
+                    -> @method {
+    // Create the type of the return value.
+    let output_type = create_self_type_with_params(cx,
+                                                   span,
+                                                   type_ident,
+                                                   ty_params);
+
+    // Create the function declaration and the body block.
+    let fn_decl = build::mk_fn_decl(~[], output_type);
+    let body_block = build::mk_simple_block(cx, span, body);
+
+    // Create the method.
+    let method_ident = cx.ident_of(~"clone");
+    create_method(cx,
+                  span,
+                  method_ident,
+                  sty_region(m_imm),
+                  impure_fn,
+                  move fn_decl,
+                  move body_block)
+}
+
+// Creates a method from the given expression conforming to the signature of
+// the `to_str` method.
+fn create_to_str_method(cx: ext_ctxt, span: span, body: @expr) -> @method {
+    // Create the type of the return value, `~str`.
+    let str_ident = cx.ident_of(~"str");
+    let str_type = build::mk_simple_ty_path(cx, span, str_ident);
+    let output_type = ty_uniq(ast::mt { ty: str_type, mutbl: m_imm });
+    let output_type = @ast::Ty {
+        id: cx.next_id(),
+        node: output_type,
+        span: span,
+    };
+
+    // Create the function declaration and the body block.
+    let fn_decl = build::mk_fn_decl(~[], output_type);
+    let body_block = build::mk_simple_block(cx, span, body);
+
+    // Create the method.
+    let method_ident = cx.ident_of(~"to_str");
+    create_method(cx,
+                  span,
+                  method_ident,
+                  sty_region(m_imm),
+                  pure_fn,
+                  move fn_decl,
+                  move body_block)
+}
+
+// Creates a method from the given expression conforming to the signature of
+// the static `rand` method.
+fn create_rand_method(cx: ext_ctxt,
+                      span: span,
+                      type_ident: ident,
+                      ty_params: &[ty_param],
+                      body: @expr)
+                   -> @method {
+    // Create the `rng` parameter.
+    let core_ident = cx.ident_of(~"core");
+    let rand_ident = cx.ident_of(~"rand");
+    let rng_ident = cx.ident_of(~"Rng");
+    let core_rand_rng_ident = ~[ core_ident, rand_ident, rng_ident ];
+    let rng_arg_type = build::mk_ty_path_global(cx,
+                                                span,
+                                                core_rand_rng_ident);
+    let rng_arg_ident = cx.ident_of(~"__rng");
+    let rng_arg = build::mk_arg(cx, span, rng_arg_ident, rng_arg_type);
+
+    // Create the type of the return value.
+    let output_type = create_self_type_with_params(cx,
+                                                   span,
+                                                   type_ident,
+                                                   ty_params);
+
+    // Create the function declaration and the body block.
+    let fn_decl = build::mk_fn_decl(~[ move rng_arg ], output_type);
+    let body_block = build::mk_simple_block(cx, span, body);
+
+    // Create the method.
+    let method_ident = cx.ident_of(~"rand");
+    create_method(cx,
+                  span,
+                  method_ident,
+                  sty_static,
+                  impure_fn,
+                  move fn_decl,
+                  move body_block)
+}
+
 // Creates a method from the given set of statements conforming to the
 // signature of the `iter_bytes` method.
 fn create_iter_bytes_method(cx: ext_ctxt,
@@ -480,6 +714,269 @@ fn variant_arg_count(cx: ext_ctxt, span: span, variant: &variant) -> uint {
     }
 }
 
+// Creates a pattern matching the given variant without binding its fields.
+fn create_enum_variant_wild_pattern(cx: ext_ctxt,
+                                    span: span,
+                                    variant: &variant)
+                                 -> @pat {
+    let variant_ident = variant.node.name;
+    let matching_path = build::mk_raw_path(span, ~[ variant_ident ]);
+    match variant.node.kind {
+        tuple_variant_kind(ref variant_args) => {
+            if variant_args.len() == 0 {
+                return build::mk_pat_ident_with_binding_mode(
+                    cx, span, variant_ident, ast::bind_infer);
+            }
+            build::mk_pat(cx, span, pat_enum(matching_path, None))
+        }
+        struct_variant_kind(*) => {
+            build::mk_pat(cx, span, pat_struct(matching_path, ~[], true))
+        }
+        enum_variant_kind(*) => {
+            cx.span_unimpl(span, ~"enum variants for `deriving`");
+        }
+    }
+}
+
+// Creates paths to the fields of the given variant as bound by the pattern
+// that `create_enum_variant_pattern` makes with the same prefix.
+fn create_enum_variant_field_paths(cx: ext_ctxt,
+                                   span: span,
+                                   variant: &variant,
+                                   prefix: ~str)
+                                -> ~[@expr] {
+    do vec::from_fn(variant_arg_count(cx, span, variant)) |i| {
+        let ident = cx.ident_of(prefix + i.to_str());
+        build::mk_path(cx, span, ~[ ident ])
+    }
+}
+
+// Creates an expression that constructs the given variant out of the given
+// expressions, one for each of its fields.
+fn create_enum_variant_expr(cx: ext_ctxt,
+                            span: span,
+                            variant: &variant,
+                            +args: ~[@expr])
+                         -> @expr {
+    let variant_ident = variant.node.name;
+    match variant.node.kind {
+        tuple_variant_kind(ref variant_args) => {
+            if variant_args.len() == 0 {
+                return build::mk_path(cx, span, ~[ variant_ident ]);
+            }
+            build::mk_call(cx, span, ~[ variant_ident ], move args)
+        }
+        struct_variant_kind(struct_def) => {
+            let fields = do struct_def.fields.mapi |i, struct_field| {
+                let ident = match struct_field.node.kind {
+                    named_field(ident, _, _) => ident,
+                    unnamed_field => {
+                        cx.span_bug(span, ~"unexpected unnamed field");
+                    }
+                };
+                let field = { ident: ident, ex: args[i] };
+                field
+            };
+            build::mk_struct_e(cx, span, ~[ variant_ident ], move fields)
+        }
+        enum_variant_kind(*) => {
+            cx.span_unimpl(span, ~"enum variants for `deriving`");
+        }
+    }
+}
+
+// Creates an expression that matches on the value pointed to by the given
+// identifier.
+fn create_deref_match_expr(cx: ext_ctxt,
+                           span: span,
+                           ident: ident,
+                           +arms: ~[arm])
+                        -> @expr {
+    let expr = build::mk_path(cx, span, ~[ ident ]);
+    let expr = build::mk_unary(cx, span, deref, expr);
+    build::mk_expr(cx, span, expr_match(expr, move arms))
+}
+
+fn create_arm(cx: ext_ctxt, span: span, pat: @pat, body: @expr) -> arm {
+    ast::arm {
+        pats: ~[ pat ],
+        guard: None,
+        body: build::mk_simple_block(cx, span, body),
+    }
+}
+
+// Creates an expression that evaluates to the index of the variant of the
+// enum pointed to by the given identifier.
+fn create_variant_index_expr(cx: ext_ctxt,
+                             span: span,
+                             enum_definition: &enum_def,
+                             ident: ident)
+                          -> @expr {
+    let arms = do enum_definition.variants.mapi |i, variant| {
+        let pat = create_enum_variant_wild_pattern(cx, span, variant);
+        create_arm(cx, span, pat, build::mk_uint(cx, span, i))
+    };
+    create_deref_match_expr(cx, span, ident, move arms)
+}
+
+fn call_substructure_clone_method(cx: ext_ctxt,
+                                  span: span,
+                                  self_field: @expr)
+                               -> @expr {
+    let clone_ident = cx.ident_of(~"clone");
+    let self_method = build::mk_access_(cx, span, self_field, clone_ident);
+    build::mk_call_(cx, span, self_method, ~[])
+}
+
+// One of the methods of `Ord`. Each compares the fields in order with the
+// strict comparison `lt` or `gt`; `le` and `ge` also hold when all the
+// fields are equal.
+struct OrdMethod {
+    name: ~str,
+    strict: ~str,
+    strict_op: binop,
+    or_equal: bool,
+}
+
+fn ord_methods() -> ~[OrdMethod] {
+    ~[
+        OrdMethod { name: ~"lt", strict: ~"lt", strict_op: lt,
+                    or_equal: false },
+        OrdMethod { name: ~"le", strict: ~"lt", strict_op: lt,
+                    or_equal: true },
+        OrdMethod { name: ~"ge", strict: ~"gt", strict_op: gt,
+                    or_equal: true },
+        OrdMethod { name: ~"gt", strict: ~"gt", strict_op: gt,
+                    or_equal: false },
+    ]
+}
+
+fn call_substructure_ord_method(cx: ext_ctxt,
+                                span: span,
+                                self_field: @expr,
+                                other_field: @expr,
+                                fields_are_refs: bool,
+                                method_ident: ident)
+                             -> @expr {
+    let other_field_ref = if fields_are_refs {
+        other_field
+    } else {
+        build::mk_addr_of(cx, span, other_field)
+    };
+    let self_method = build::mk_access_(cx, span, self_field, method_ident);
+    build::mk_call_(cx, span, self_method, ~[ other_field_ref ])
+}
+
+// Creates the lexicographic comparison of the given pairs of fields of
+// `self` and of the other value.
+fn create_ord_chain_expr(cx: ext_ctxt,
+                         span: span,
+                         ord_method: &OrdMethod,
+                         fields: &[(@expr, @expr)],
+                         fields_are_refs: bool)
+                      -> @expr {
+    let strict_ident = cx.ident_of(copy ord_method.strict);
+
+    // Build the chain from the last field outwards: a field decides the
+    // comparison unless it is equal in both values, when the rest do.
+    let mut chain_expr = None;
+    for vec::rev_each(fields) |&(self_field, other_field)| {
+        let strict_expr = call_substructure_ord_method(cx,
+                                                       span,
+                                                       self_field,
+                                                       other_field,
+                                                       fields_are_refs,
+                                                       strict_ident);
+        let reverse_expr = call_substructure_ord_method(cx,
+                                                        span,
+                                                        other_field,
+                                                        self_field,
+                                                        fields_are_refs,
+                                                        strict_ident);
+        let not_reverse_expr = build::mk_unary(cx, span, not, reverse_expr);
+        chain_expr = Some(match chain_expr {
+            None if ord_method.or_equal => {
+                build::mk_binary(cx, span, or, strict_expr, not_reverse_expr)
+            }
+            None => strict_expr,
+            Some(rest_expr) => {
+                let rest_expr = build::mk_binary(cx,
+                                                 span,
+                                                 and,
+                                                 not_reverse_expr,
+                                                 rest_expr);
+                build::mk_binary(cx, span, or, strict_expr, rest_expr)
+            }
+        });
+    }
+
+    match chain_expr {
+        None => build::mk_bool(cx, span, ord_method.or_equal),
+        Some(chain_expr) => chain_expr,
+    }
+}
+
+// Creates an expression that formats a value the way `core::repr` does,
+// but led by the name of its type or variant: `Name`, `Name(1, 2)` or
+// `Name{a: 1, b: 2}`. Each field is given as a pointer to it, along with
+// its name if it has one.
+fn create_to_str_expr(cx: ext_ctxt,
+                      span: span,
+                      name: ident,
+                      fields: &[(Option<ident>, @expr)])
+                   -> @expr {
+    let name = cx.str_of(name);
+    if fields.len() == 0 {
+        return build::mk_uniq_str(cx, span, name);
+    }
+    let (open, close) = match fields[0] {
+        (Some(_), _) => (~"{", ~"}"),
+        (None, _) => (~"(", ~")"),
+    };
+
+    let core_ident = cx.ident_of(~"core");
+    let repr_ident = cx.ident_of(~"repr");
+    let repr_to_str_ident = cx.ident_of(~"repr_to_str");
+
+    // Alternate between the punctuation and the formatted fields.
+    let pieces = dvec::DVec();
+    let mut literal = name + open;
+    for fields.eachi |i, &(field_name, field)| {
+        if i > 0 {
+            literal += ~", ";
+        }
+        match field_name {
+            Some(ident) => literal += cx.str_of(ident) + ~": ",
+            None => ()
+        }
+        pieces.push(build::mk_uniq_str(cx, span, copy literal));
+        literal = ~"";
+
+        let repr_path = ~[ core_ident, repr_ident, repr_to_str_ident ];
+        pieces.push(build::mk_call_global(cx, span, repr_path, ~[ field ]));
+    }
+    pieces.push(build::mk_uniq_str(cx, span, literal + close));
+
+    // Concatenate the pieces.
+    let pieces = build::mk_slice_vec_e(cx, span, dvec::unwrap(move pieces));
+    let str_ident = cx.ident_of(~"str");
+    let concat_ident = cx.ident_of(~"concat");
+    let concat_path = ~[ core_ident, str_ident, concat_ident ];
+    build::mk_call_global(cx, span, concat_path, ~[ pieces ])
+}
+
+fn call_substructure_rand_method(cx: ext_ctxt, span: span) -> @expr {
+    let rng_ident = cx.ident_of(~"__rng");
+    let rng_expr = build::mk_path(cx, span, ~[ rng_ident ]);
+    let rand_path = ~[
+        cx.ident_of(~"core"),
+        cx.ident_of(~"rand"),
+        cx.ident_of(~"Rand"),
+        cx.ident_of(~"rand")
+    ];
+    build::mk_call_global(cx, span, rand_path, ~[ rng_expr ])
+}
+
 fn expand_deriving_eq_struct_def(cx: ext_ctxt,
                                  span: span,
                                  struct_def: &struct_def,
@@ -871,3 +1368,454 @@ fn expand_deriving_iter_bytes_enum_method(cx: ext_ctxt,
     create_iter_bytes_method(cx, span, ~[ self_match_stmt ])
 }
 
+fn expand_deriving_clone_struct_def(cx: ext_ctxt,
+                                    span: span,
+                                    struct_def: &struct_def,
+                                    type_ident: ident,
+                                    +ty_params: ~[ty_param])
+                                 -> @item {
+    // Create the method.
+    let method = expand_deriving_clone_struct_method(cx,
+                                                     span,
+                                                     struct_def,
+                                                     type_ident,
+                                                     ty_params);
+
+    // Create the implementation.
+    return create_derived_clone_impl(cx,
+                                     span,
+                                     type_ident,
+                                     move ty_params,
+                                     method);
+}
+
+fn expand_deriving_clone_enum_def(cx: ext_ctxt,
+                                  span: span,
+                                  enum_definition: &enum_def,
+                                  type_ident: ident,
+                                  +ty_params: ~[ty_param])
+                               -> @item {
+    // Create the method.
+    let method = expand_deriving_clone_enum_method(cx,
+                                                   span,
+                                                   enum_definition,
+                                                   type_ident,
+                                                   ty_params);
+
+    // Create the implementation.
+    return create_derived_clone_impl(cx,
+                                     span,
+                                     type_ident,
+                                     move ty_params,
+                                     method);
+}
+
+fn expand_deriving_clone_struct_method(cx: ext_ctxt,
+                                       span: span,
+                                       struct_def: &struct_def,
+                                       type_ident: ident,
+                                       ty_params: &[ty_param])
+                                    -> @method {
+    let self_ident = cx.ident_of(~"self");
+
+    // Clone each field into the new structure.
+    let fields = dvec::DVec();
+    for struct_def.fields.each |struct_field| {
+        match struct_field.node.kind {
+            named_field(ident, _, _) => {
+                // Create the accessor for this field.
+                let self_field = build::mk_access(cx,
+                                                  span,
+                                                  ~[ self_ident ],
+                                                  ident);
+
+                // Call the substructure method.
+                let clone_expr = call_substructure_clone_method(cx,
+                                                                span,
+                                                                self_field);
+                fields.push({ ident: ident, ex: clone_expr });
+            }
+            unnamed_field => {
+                cx.span_unimpl(span, ~"unnamed fields with `deriving_clone`");
+            }
+        }
+    }
+
+    // Create the method itself.
+    let fields = dvec::unwrap(move fields);
+    let body = build::mk_struct_e(cx, span, ~[ type_ident ], move fields);
+    return create_clone_method(cx, span, type_ident, ty_params, body);
+}
+
+fn expand_deriving_clone_enum_method(cx: ext_ctxt,
+                                     span: span,
+                                     enum_definition: &enum_def,
+                                     type_ident: ident,
+                                     ty_params: &[ty_param])
+                                  -> @method {
+    // Create the arms of the match in the method body, each of which builds
+    // the same variant out of clones of the fields.
+    let arms = do enum_definition.variants.map |variant| {
+        let pat = create_enum_variant_pattern(cx, span, variant, ~"__self");
+        let fields = create_enum_variant_field_paths(cx,
+                                                     span,
+                                                     variant,
+                                                     ~"__self");
+        let args = do fields.map |field| {
+            call_substructure_clone_method(cx, span, *field)
+        };
+        let expr = create_enum_variant_expr(cx, span, variant, move args);
+        create_arm(cx, span, pat, expr)
+    };
+
+    // Create the method.
+    let self_ident = cx.ident_of(~"self");
+    let body = create_deref_match_expr(cx, span, self_ident, move arms);
+    return create_clone_method(cx, span, type_ident, ty_params, body);
+}
+
+fn expand_deriving_ord_struct_def(cx: ext_ctxt,
+                                  span: span,
+                                  struct_def: &struct_def,
+                                  type_ident: ident,
+                                  +ty_params: ~[ty_param])
+                               -> @item {
+    // Create the methods.
+    let methods = do ord_methods().map |ord_method| {
+        expand_deriving_ord_struct_method(cx,
+                                          span,
+                                          struct_def,
+                                          ord_method,
+                                          type_ident,
+                                          ty_params)
+    };
+
+    // Create the implementation.
+    return create_derived_ord_impl(cx,
+                                   span,
+                                   type_ident,
+                                   move ty_params,
+                                   methods);
+}
+
+fn expand_deriving_ord_enum_def(cx: ext_ctxt,
+                                span: span,
+                                enum_definition: &enum_def,
+                                type_ident: ident,
+                                +ty_params: ~[ty_param])
+                             -> @item {
+    // Create the methods.
+    let methods = do ord_methods().map |ord_method| {
+        expand_deriving_ord_enum_method(cx,
+                                        span,
+                                        enum_definition,
+                                        ord_method,
+                                        type_ident,
+                                        ty_params)
+    };
+
+    // Create the implementation.
+    return create_derived_ord_impl(cx,
+                                   span,
+                                   type_ident,
+                                   move ty_params,
+                                   methods);
+}
+
+fn expand_deriving_ord_struct_method(cx: ext_ctxt,
+                                     span: span,
+                                     struct_def: &struct_def,
+                                     ord_method: &OrdMethod,
+                                     type_ident: ident,
+                                     ty_params: &[ty_param])
+                                  -> @method {
+    let self_ident = cx.ident_of(~"self");
+    let other_ident = cx.ident_of(~"__other");
+
+    // Gather up the pairs of fields to compare.
+    let fields = dvec::DVec();
+    for struct_def.fields.each |struct_field| {
+        match struct_field.node.kind {
+            named_field(ident, _, _) => {
+                let self_field = build::mk_access(cx,
+                                                  span,
+                                                  ~[ self_ident ],
+                                                  ident);
+                let other_field = build::mk_access(cx,
+                                                   span,
+                                                   ~[ other_ident ],
+                                                   ident);
+                fields.push((self_field, other_field));
+            }
+            unnamed_field => {
+                cx.span_unimpl(span, ~"unnamed fields with `deriving_ord`");
+            }
+        }
+    }
+
+    // Create the method itself.
+    let fields = dvec::unwrap(move fields);
+    let body = create_ord_chain_expr(cx, span, ord_method, fields, false);
+    let method_ident = cx.ident_of(copy ord_method.name);
+    return create_eq_method(cx,
+                            span,
+                            method_ident,
+                            type_ident,
+                            ty_params,
+                            body);
+}
+
+fn expand_deriving_ord_enum_method(cx: ext_ctxt,
+                                   span: span,
+                                   enum_definition: &enum_def,
+                                   ord_method: &OrdMethod,
+                                   type_ident: ident,
+                                   ty_params: &[ty_param])
+                                -> @method {
+    let self_ident = cx.ident_of(~"self");
+    let other_ident = cx.ident_of(~"__other");
+
+    // Create the arms of the self match in the method body.
+    let self_arms = do enum_definition.variants.mapi |i, self_variant| {
+        // If both values are this variant, compare their fields.
+        let matching_pat = create_enum_variant_pattern(cx,
+                                                       span,
+                                                       self_variant,
+                                                       ~"__other");
+        let self_fields = create_enum_variant_field_paths(cx,
+                                                          span,
+                                                          self_variant,
+                                                          ~"__self");
+        let other_fields = create_enum_variant_field_paths(cx,
+                                                           span,
+                                                           self_variant,
+                                                           ~"__other");
+        let fields = vec::zip(move self_fields, move other_fields);
+        let matching_expr = create_ord_chain_expr(cx,
+                                                  span,
+                                                  ord_method,
+                                                  fields,
+                                                  true);
+        let mut other_arms = ~[
+            create_arm(cx, span, matching_pat, matching_expr)
+        ];
+
+        // Otherwise the values are ordered by the indices of their variants.
+        // If there is only one variant then there will always be a match.
+        if enum_definition.variants.len() > 1 {
+            let nonmatching_pat = @ast::pat {
+                id: cx.next_id(),
+                node: pat_wild,
+                span: span
+            };
+            let self_index = build::mk_uint(cx, span, i);
+            let other_index = create_variant_index_expr(cx,
+                                                        span,
+                                                        enum_definition,
+                                                        other_ident);
+            let nonmatching_expr = build::mk_binary(cx,
+                                                    span,
+                                                    ord_method.strict_op,
+                                                    self_index,
+                                                    other_index);
+            other_arms.push(create_arm(cx,
+                                       span,
+                                       nonmatching_pat,
+                                       nonmatching_expr));
+        }
+
+        // Create the self arm.
+        let self_pat = create_enum_variant_pattern(cx,
+                                                   span,
+                                                   self_variant,
+                                                   ~"__self");
+        let other_match_expr = create_deref_match_expr(cx,
+                                                       span,
+                                                       other_ident,
+                                                       move other_arms);
+        create_arm(cx, span, self_pat, other_match_expr)
+    };
+
+    // Create the method.
+    let self_match_expr = create_deref_match_expr(cx,
+                                                  span,
+                                                  self_ident,
+                                                  move self_arms);
+    let method_ident = cx.ident_of(copy ord_method.name);
+    return create_eq_method(cx,
+                            span,
+                            method_ident,
+                            type_ident,
+                            ty_params,
+                            self_match_expr);
+}
+
+fn expand_deriving_to_str_struct_def(cx: ext_ctxt,
+                                     span: span,
+                                     struct_def: &struct_def,
+                                     type_ident: ident,
+                                     +ty_params: ~[ty_param])
+                                  -> @item {
+    let self_ident = cx.ident_of(~"self");
+
+    // Gather up pointers to the fields to format.
+    let fields = dvec::DVec();
+    for struct_def.fields.each |struct_field| {
+        match struct_field.node.kind {
+            named_field(ident, _, _) => {
+                let self_field = build::mk_access(cx,
+                                                  span,
+                                                  ~[ self_ident ],
+                                                  ident);
+                let self_field_ref = build::mk_addr_of(cx, span, self_field);
+                fields.push((Some(ident), self_field_ref));
+            }
+            unnamed_field => {
+                cx.span_unimpl(span,
+                               ~"unnamed fields with `deriving_to_str`");
+            }
+        }
+    }
+
+    // Create the method.
+    let fields = dvec::unwrap(move fields);
+    let body = create_to_str_expr(cx, span, type_ident, fields);
+    let method = create_to_str_method(cx, span, body);
+
+    // Create the implementation.
+    return create_derived_to_str_impl(cx,
+                                      span,
+                                      type_ident,
+                                      move ty_params,
+                                      method);
+}
+
+fn expand_deriving_to_str_enum_def(cx: ext_ctxt,
+                                   span: span,
+                                   enum_definition: &enum_def,
+                                   type_ident: ident,
+                                   +ty_params: ~[ty_param])
+                                -> @item {
+    // Create the arms of the match in the method body.
+    let arms = do enum_definition.variants.map |variant| {
+        let pat = create_enum_variant_pattern(cx, span, variant, ~"__self");
+
+        // Pair each field, already bound by reference, with its name.
+        let field_paths = create_enum_variant_field_paths(cx,
+                                                          span,
+                                                          variant,
+                                                          ~"__self");
+        let fields = match variant.node.kind {
+            struct_variant_kind(struct_def) => {
+                do struct_def.fields.mapi |i, struct_field| {
+                    match struct_field.node.kind {
+                        named_field(ident, _, _) => {
+                            (Some(ident), field_paths[i])
+                        }
+                        unnamed_field => {
+                            cx.span_bug(span, ~"unexpected unnamed field");
+                        }
+                    }
+                }
+            }
+            _ => field_paths.map(|field_path| (None, *field_path))
+        };
+
+        let expr = create_to_str_expr(cx, span, variant.node.name, fields);
+        create_arm(cx, span, pat, expr)
+    };
+
+    // Create the method.
+    let self_ident = cx.ident_of(~"self");
+    let body = create_deref_match_expr(cx, span, self_ident, move arms);
+    let method = create_to_str_method(cx, span, body);
+
+    // Create the implementation.
+    return create_derived_to_str_impl(cx,
+                                      span,
+                                      type_ident,
+                                      move ty_params,
+                                      method);
+}
+
+fn expand_deriving_rand_struct_def(cx: ext_ctxt,
+                                   span: span,
+                                   struct_def: &struct_def,
+                                   type_ident: ident,
+                                   +ty_params: ~[ty_param])
+                                -> @item {
+    // Generate each field at random.
+    let fields = dvec::DVec();
+    for struct_def.fields.each |struct_field| {
+        match struct_field.node.kind {
+            named_field(ident, _, _) => {
+                let rand_expr = call_substructure_rand_method(cx, span);
+                fields.push({ ident: ident, ex: rand_expr });
+            }
+            unnamed_field => {
+                cx.span_unimpl(span, ~"unnamed fields with `deriving_rand`");
+            }
+        }
+    }
+
+    // Create the method.
+    let fields = dvec::unwrap(move fields);
+    let body = build::mk_struct_e(cx, span, ~[ type_ident ], move fields);
+    let method = create_rand_method(cx, span, type_ident, ty_params, body);
+
+    // Create the implementation.
+    return create_derived_rand_impl(cx,
+                                    span,
+                                    type_ident,
+                                    move ty_params,
+                                    method);
+}
+
+fn expand_deriving_rand_enum_def(cx: ext_ctxt,
+                                 span: span,
+                                 enum_definition: &enum_def,
+                                 type_ident: ident,
+                                 +ty_params: ~[ty_param])
+                              -> @item {
+    let variant_count = enum_definition.variants.len();
+
+    // Create the arms of the match on the chosen variant index. The last
+    // variant takes any index, so that the match is exhaustive.
+    let arms = do enum_definition.variants.mapi |i, variant| {
+        let pat = if i == variant_count - 1 {
+            build::mk_pat(cx, span, pat_wild)
+        } else {
+            build::mk_pat(cx, span, pat_lit(build::mk_uint(cx, span, i)))
+        };
+        let args = do vec::from_fn(variant_arg_count(cx, span, variant)) |_| {
+            call_substructure_rand_method(cx, span)
+        };
+        let expr = create_enum_variant_expr(cx, span, variant, move args);
+        create_arm(cx, span, pat, expr)
+    };
+
+    // Choose a variant at random.
+    let rng_ident = cx.ident_of(~"__rng");
+    let rng_expr = build::mk_path(cx, span, ~[ rng_ident ]);
+    let range_ident = cx.ident_of(~"gen_uint_range");
+    let range_method = build::mk_access_(cx, span, rng_expr, range_ident);
+    let index_expr = build::mk_call_(cx,
+                                     span,
+                                     range_method,
+                                     ~[ build::mk_uint(cx, span, 0),
+                                        build::mk_uint(cx,
+                                                       span,
+                                                       variant_count) ]);
+    let body = build::mk_expr(cx, span, expr_match(index_expr, move arms));
+
+    // Create the method.
+    let method = create_rand_method(cx, span, type_ident, ty_params, body);
+
+    // Create the implementation.
+    return create_derived_rand_impl(cx,
+                                    span,
+                                    type_ident,
+                                    move ty_params,
+                                    method);
+}
+
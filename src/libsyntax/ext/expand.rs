@@ -10,13 +10,19 @@
 
 use core::prelude::*;
 
-use ast::{crate, expr_, expr_mac, mac_invoc_tt};
-use ast::{tt_delim, tt_tok, item_mac, stmt_, stmt_mac, stmt_expr, stmt_semi};
+use ast::{crate, expr, expr_, expr_mac, ident, item, mac_invoc_tt, stmt};
+use ast::{token_tree, tt_delim, tt_seq, tt_tok};
+use ast::{item_mac, stmt_, stmt_mac, stmt_expr, stmt_semi};
 use ast;
+use attr;
 use codemap::{span, ExpandedFrom};
 use ext::base::*;
 use fold::*;
 use parse::{parser, parse_expr_from_source_str, new_parser_from_tts};
+use parse::{parse_item_from_source_str};
+use parse::token;
+use print::pprust;
+use visit;
 
 use core::option;
 use core::vec;
@@ -161,6 +167,17 @@ pub fn expand_item_mac(exts: HashMap<~str, SyntaxExtension>,
     };
 
     let extname = cx.parse_sess().interner.get(pth.idents[0]);
+
+    // A macro definition that other crates can import keeps its source, to
+    // go into the crate metadata.
+    let export_source = if attr::attrs_contains_name(it.attrs,
+                                                     ~"macro_export") {
+        Some(fmt!("%s! %s (%s)", *extname, cx.str_of(it.ident),
+                  pprust::tts_to_str(tts, cx.parse_sess().interner)))
+    } else {
+        None
+    };
+
     let expanded = match exts.find(extname) {
         None => cx.span_fatal(pth.span,
                               fmt!("macro undefined: '%s!'", *extname)),
@@ -201,6 +218,10 @@ pub fn expand_item_mac(exts: HashMap<~str, SyntaxExtension>,
             option::chain(item_maker(), |i| {fld.fold_item(i)}),
         MRDef(ref mdef) => {
             exts.insert((*mdef).name, (*mdef).ext);
+            match export_source {
+                Some(source) => cx.export_macro(source),
+                None => ()
+            }
             None
         }
     };
@@ -268,6 +289,172 @@ pub fn new_span(cx: ext_ctxt, sp: span) -> span {
     return span {lo: sp.lo, hi: sp.hi, expn_info: cx.backtrace()};
 }
 
+// Macro hygiene. Each expansion of a `macro_rules!` macro marks the
+// identifiers written by its transcriber, replacing each of them with a
+// gensym of the same name, while those that come from the arguments of the
+// invocation are left alone. Once the expansion has been parsed, the marks
+// are renamed away again, except on the variables that the expansion binds
+// itself: those keep their marked names, so that they can neither capture
+// nor shadow the variables of the same name at the call site. Everything
+// else, from items to fields and methods, goes by its original name (and
+// resolve looks items up by the original name of a marked identifier).
+
+/// Maps the identifiers marked by one macro expansion to their originals
+pub type Marks = HashMap<ident, ident>;
+
+/// Marks the identifiers in the transcriber of a macro expansion
+pub fn mark_tts(cx: ext_ctxt, tts: &[token_tree]) -> (~[token_tree], Marks) {
+    let marks = HashMap();
+    let fresh = HashMap();
+    let keywords = token::keyword_table();
+
+    fn mark(cx: ext_ctxt, tts: &[token_tree], marks: Marks,
+            fresh: HashMap<ident, ident>,
+            keywords: HashMap<~str, ()>) -> ~[token_tree] {
+        let itr = cx.parse_sess().interner;
+        do tts.map |tt| {
+            match *tt {
+              // Keywords are not marked, since the parser compares some of
+              // them (such as `self`) by ident.
+              tt_tok(sp, token::IDENT(id, false))
+                      if !keywords.contains_key_ref(&*itr.get(id)) => {
+                let marked = match fresh.find(&id) {
+                  Some(marked) => marked,
+                  None => {
+                    let marked = itr.gensym(@copy *itr.get(id));
+                    fresh.insert(id, marked);
+                    marks.insert(marked, id);
+                    marked
+                  }
+                };
+                tt_tok(sp, token::IDENT(marked, false))
+              }
+              tt_delim(ref tts) => {
+                tt_delim(mark(cx, *tts, marks, fresh, keywords))
+              }
+              tt_seq(sp, ref tts, ref sep, zerok) => {
+                tt_seq(sp, mark(cx, *tts, marks, fresh, keywords),
+                       copy *sep, zerok)
+              }
+              _ => copy *tt
+            }
+        }
+    }
+
+    let marked = mark(cx, tts, marks, fresh, keywords);
+    (marked, marks)
+}
+
+// Collects the marked identifiers that are bound as variables
+fn binding_visitor(marks: Marks, bound: HashMap<ident, ()>) -> visit::vt<()> {
+    visit::mk_simple_visitor(@visit::SimpleVisitor {
+        visit_pat: |p| {
+            match p.node {
+              ast::pat_ident(_, path, _)
+                      if !path.global && path.idents.len() == 1 => {
+                if marks.contains_key_ref(&path.idents[0]) {
+                    bound.insert(path.idents[0], ());
+                }
+              }
+              _ => ()
+            }
+        },
+        .. *visit::default_simple_visitor()
+    })
+}
+
+fn unmark(marks: Marks, id: ident) -> ident {
+    match marks.find(&id) {
+      Some(orig) => orig,
+      None => id
+    }
+}
+
+// Renames away the marks that are not on bound variables. Fields and
+// methods are looked up by name, so their uses are always renamed; their
+// declarations, like the paths of view items, go through fold_ident.
+fn rename_fold(marks: Marks, bound: HashMap<ident, ()>) -> ast_fold {
+    fn unmark_fields(marks: Marks, fields: &[ast::field]) -> ~[ast::field] {
+        do fields.map |f| {
+            codemap::spanned {
+                node: ast::field_ { ident: unmark(marks, f.node.ident),
+                                    .. copy f.node },
+                span: f.span
+            }
+        }
+    }
+    fn unmark_field_pats(marks: Marks,
+                         fields: &[ast::field_pat]) -> ~[ast::field_pat] {
+        do fields.map |f| {
+            ast::field_pat { ident: unmark(marks, f.ident), pat: f.pat }
+        }
+    }
+
+    let afp = default_ast_fold();
+    let f_pre = @AstFoldFns {
+        fold_ident: |id, _fld| {
+            if bound.contains_key_ref(&id) { id } else { unmark(marks, id) }
+        },
+        fold_expr: |e, sp, fld| {
+            let (e, sp) = (afp.fold_expr)(e, sp, fld);
+            let e = match e {
+              ast::expr_field(base, id, ref tys) => {
+                ast::expr_field(base, unmark(marks, id), copy *tys)
+              }
+              ast::expr_method_call(f, id, ref tys, ref args, sugar) => {
+                ast::expr_method_call(f, unmark(marks, id), copy *tys,
+                                      copy *args, sugar)
+              }
+              ast::expr_rec(ref fields, base) => {
+                ast::expr_rec(unmark_fields(marks, *fields), base)
+              }
+              ast::expr_struct(path, ref fields, base) => {
+                ast::expr_struct(path, unmark_fields(marks, *fields), base)
+              }
+              _ => e
+            };
+            (e, sp)
+        },
+        fold_pat: |p, sp, fld| {
+            let (p, sp) = (afp.fold_pat)(p, sp, fld);
+            let p = match p {
+              ast::pat_rec(ref fields, etc) => {
+                ast::pat_rec(unmark_field_pats(marks, *fields), etc)
+              }
+              ast::pat_struct(path, ref fields, etc) => {
+                ast::pat_struct(path, unmark_field_pats(marks, *fields), etc)
+              }
+              _ => p
+            };
+            (p, sp)
+        },
+        .. *afp};
+    make_fold(f_pre)
+}
+
+/// Renames the marks in an expression parsed from a macro expansion
+pub fn rename_expr(marks: Marks, e: @expr) -> @expr {
+    let bound = HashMap();
+    visit::visit_expr(e, (), binding_visitor(marks, bound));
+    rename_fold(marks, bound).fold_expr(e)
+}
+
+/// Renames the marks in an item parsed from a macro expansion
+pub fn rename_item(marks: Marks, it: Option<@item>) -> Option<@item> {
+    do it.chain |it| {
+        let bound = HashMap();
+        visit::visit_item(it, (), binding_visitor(marks, bound));
+        rename_fold(marks, bound).fold_item(it)
+    }
+}
+
+/// Renames the marks in a statement parsed from a macro expansion
+pub fn rename_stmt(marks: Marks, s: @stmt) -> @stmt {
+    let bound = HashMap();
+    visit::visit_stmt(s, (), binding_visitor(marks, bound));
+    rename_fold(marks, bound).fold_stmt(s)
+}
+
 // FIXME (#2247): this is a terrible kludge to inject some macros into
 // the default compilation environment. When the macro-definition system
 // is substantially more mature, these should move from here, into a
@@ -334,6 +521,20 @@ pub fn core_macros() -> ~str {
 
 pub fn expand_crate(parse_sess: parse::parse_sess,
                     cfg: ast::crate_cfg, c: @crate) -> @crate {
+    let (expanded, _) = expand_crate_with_macros(parse_sess, cfg, ~[], c);
+    return expanded;
+}
+
+/**
+ * Expands a crate that imports the given `macro_rules!` definitions, as
+ * exported by other crates, returning the expanded crate along with the
+ * sources of the definitions that it exports itself with
+ * `#[macro_export]`.
+ */
+pub fn expand_crate_with_macros(parse_sess: parse::parse_sess,
+                                cfg: ast::crate_cfg,
+                                imported_macros: ~[~str],
+                                c: @crate) -> (@crate, ~[~str]) {
     let exts = syntax_expander_table();
     let afp = default_ast_fold();
     let cx: ext_ctxt = mk_ctxt(parse_sess, cfg);
@@ -354,8 +555,21 @@ pub fn expand_crate(parse_sess: parse::parse_sess,
     // as it registers all the core macros as expanders.
     f.fold_expr(cm);
 
+    // Likewise for the macros from other crates, which are registered as
+    // though they were defined at the top of this one.
+    for imported_macros.each |source| {
+        match parse_item_from_source_str(~"<imported-macros>",
+                                         @copy *source,
+                                         copy cfg,
+                                         ~[],
+                                         parse_sess) {
+            Some(item) => { f.fold_item(item); }
+            None => cx.bug(~"imported macro is not an item")
+        }
+    }
+
     let res = @f.fold_crate(*c);
-    return res;
+    return (res, cx.exported_macros());
 }
 // Local Variables:
 // mode: rust
@@ -25,15 +25,58 @@ use ext::base;
 use ext::build::*;
 use extfmt::ct::*;
 
+use core::uint;
+use core::vec;
+
 pub fn expand_syntax_ext(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
     -> base::MacResult {
     let args = get_exprs_from_tts(cx, copy tts);
     if args.len() == 0 {
         cx.span_fatal(sp, "fmt! takes at least 1 argument.");
     }
+    let piece_exprs = fmt_args_to_exprs(cx, sp, ~"fmt!", args);
+    let fmt_sp = args[0].span;
+    let arg_vec = mk_fixed_vec_e(cx, fmt_sp, piece_exprs);
+    MRExpr(mk_call_global(cx,
+                          fmt_sp,
+                          ~[cx.parse_sess().interner.intern(@~"str"),
+                            cx.parse_sess().interner.intern(@~"concat")],
+                          ~[arg_vec]))
+}
+
+// write_fmt!(writer, fmt, args...) writes each piece of the output in turn:
+//
+//     { let w = writer; ::extfmt::rt::write_str(w, piece); ... }
+pub fn expand_write(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
+    -> base::MacResult {
+    let args = get_exprs_from_tts(cx, copy tts);
+    if args.len() < 2 {
+        cx.span_fatal(sp, "write_fmt! takes at least 2 arguments.");
+    }
+    let piece_exprs = fmt_args_to_exprs(cx, sp, ~"write_fmt!",
+                                        vec::tail(args));
+    let intr = cx.parse_sess().interner;
+    let writer = intr.gensym(@~"__fmt_writer");
+    let mut stmts = ~[mk_local(cx, args[0].span, false, writer, args[0])];
+    for piece_exprs.each |e| {
+        let write = mk_call_global(cx, e.span,
+                                   ~[intr.intern(@~"extfmt"),
+                                     intr.intern(@~"rt"),
+                                     intr.intern(@~"write_str")],
+                                   ~[mk_path(cx, e.span, ~[writer]), *e]);
+        stmts.push(mk_stmt(cx, e.span, write));
+    }
+    MRExpr(mk_block(cx, sp, ~[], stmts, None))
+}
+
+// Parses the format string that is the first of `args`, returning an
+// expression for each piece of the output
+fn fmt_args_to_exprs(cx: ext_ctxt, sp: span, name: ~str,
+                     args: ~[@ast::expr]) -> ~[@ast::expr] {
     let fmt =
         expr_to_str(cx, args[0],
-                    ~"first argument to fmt! must be a string literal.");
+                    fmt!("format string of %s must be a string literal.",
+                         name));
     let fmtspan = args[0].span;
     debug!("Format string:");
     log(debug, fmt);
@@ -44,16 +87,16 @@ pub fn expand_syntax_ext(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
         parse_fmt_err_(cx, fmtspan, s)
     };
     let pieces = parse_fmt_string(fmt, parse_fmt_err);
-    MRExpr(pieces_to_expr(cx, sp, pieces, args))
+    pieces_to_exprs(cx, sp, name, pieces, args)
 }
 
 // FIXME (#2249): A lot of these functions for producing expressions can
 // probably be factored out in common with other code that builds
 // expressions.  Also: Cleanup the naming of these functions.
 // Note: Moved many of the common ones to build.rs --kevina
-fn pieces_to_expr(cx: ext_ctxt, sp: span,
-                  pieces: ~[Piece], args: ~[@ast::expr])
-   -> @ast::expr {
+fn pieces_to_exprs(cx: ext_ctxt, sp: span, name: ~str,
+                   pieces: ~[Piece], args: ~[@ast::expr])
+   -> ~[@ast::expr] {
     fn make_path_vec(cx: ext_ctxt, ident: @~str) -> ~[ast::ident] {
         let intr = cx.parse_sess().interner;
         return ~[intr.intern(@~"extfmt"), intr.intern(@~"rt"),
@@ -141,8 +184,8 @@ fn pieces_to_expr(cx: ext_ctxt, sp: span,
         return mk_call_global(cx, arg.span, path, args);
     }
 
-    fn make_new_conv(cx: ext_ctxt, sp: span, cnv: Conv, arg: @ast::expr) ->
-       @ast::expr {
+    fn make_new_conv(cx: ext_ctxt, sp: span, name: &str, cnv: Conv,
+                     arg: @ast::expr) -> @ast::expr {
         // FIXME: Move validation code into core::extfmt (Issue #2249)
 
         fn is_signed_type(cnv: Conv) -> bool {
@@ -155,29 +198,29 @@ fn pieces_to_expr(cx: ext_ctxt, sp: span,
               _ => return false
             }
         }
-        let unsupported = ~"conversion not supported in fmt! string";
-        match cnv.param {
-          option::None => (),
-          _ => cx.span_unimpl(sp, unsupported)
-        }
+        let unsupported = fmt!("conversion not supported in %s string",
+                               name);
+        // A Format impl decides what the flags mean for its type
+        let is_format = cnv.ty == TyFormat;
         for cnv.flags.each |f| {
             match *f {
               FlagLeftJustify => (),
               FlagSignAlways => {
-                if !is_signed_type(cnv) {
+                if !is_signed_type(cnv) && !is_format {
                     cx.span_fatal(sp,
-                                  ~"+ flag only valid in " +
-                                      ~"signed fmt! conversion");
+                                  fmt!("+ flag only valid in signed %s \
+                                        conversion", name));
                 }
               }
               FlagSpaceForSign => {
-                if !is_signed_type(cnv) {
+                if !is_signed_type(cnv) && !is_format {
                     cx.span_fatal(sp,
-                                  ~"space flag only valid in " +
-                                      ~"signed fmt! conversions");
+                                  fmt!("space flag only valid in signed \
+                                        %s conversions", name));
                 }
               }
               FlagLeftZeroPad => (),
+              FlagAlternate if is_format => (),
               _ => cx.span_unimpl(sp, unsupported)
             }
         }
@@ -209,6 +252,8 @@ fn pieces_to_expr(cx: ext_ctxt, sp: span,
           TyFloat => {
             return make_conv_call(cx, arg.span, ~"float", cnv, arg);
           }
+          TyFormat => return make_conv_call(cx, arg.span, ~"format", cnv,
+                         mk_addr_of(cx, sp, arg)),
           TyPoly => return make_conv_call(cx, arg.span, ~"poly", cnv,
                        mk_addr_of(cx, sp, arg))
         }
@@ -258,11 +303,14 @@ fn pieces_to_expr(cx: ext_ctxt, sp: span,
           },
           TyOctal => debug!("type: octal"),
           TyFloat => debug!("type: float"),
+          TyFormat => debug!("type: format"),
           TyPoly => debug!("type: poly")
         }
     }
     let fmt_sp = args[0].span;
+    // The last argument taken by a conversion without a parameter
     let mut n = 0u;
+    let mut used = vec::from_elem(args.len(), false);
     let mut piece_exprs = ~[];
     let nargs = args.len();
     for pieces.each |pc| {
@@ -271,34 +319,45 @@ fn pieces_to_expr(cx: ext_ctxt, sp: span,
             piece_exprs.push(mk_uniq_str(cx, fmt_sp, (*s)))
           }
           PieceConv(conv) => {
-            n += 1u;
-            if n >= nargs {
-                cx.span_fatal(sp,
-                              ~"not enough arguments to fmt! " +
-                                  ~"for the given format string");
-            }
+            let i = match conv.param {
+              Some(p) => {
+                if p == 0u || p >= nargs {
+                    cx.span_fatal(sp,
+                                  fmt!("not enough arguments to %s for \
+                                        argument %u of the format string",
+                                       name, p));
+                }
+                p
+              }
+              None => {
+                n += 1u;
+                if n >= nargs {
+                    cx.span_fatal(sp,
+                                  fmt!("not enough arguments to %s for \
+                                        the given format string", name));
+                }
+                n
+              }
+            };
+            used[i] = true;
             debug!("Building conversion:");
             log_conv(conv);
-            let arg_expr = args[n];
-            let c_expr = make_new_conv(cx, fmt_sp, conv, arg_expr);
+            let arg_expr = args[i];
+            let c_expr = make_new_conv(cx, fmt_sp, name, conv, arg_expr);
             piece_exprs.push(c_expr);
           }
         }
     }
-    let expected_nargs = n + 1u; // n conversions + the fmt string
 
-    if expected_nargs < nargs {
-        cx.span_fatal
-            (sp, fmt!("too many arguments to fmt!. found %u, expected %u",
-                           nargs, expected_nargs));
+    for uint::range(1u, nargs) |i| {
+        if !used[i] {
+            cx.span_fatal(args[i].span,
+                          fmt!("too many arguments to %s: argument %u is \
+                                never used", name, i));
+        }
     }
 
-    let arg_vec = mk_fixed_vec_e(cx, fmt_sp, piece_exprs);
-    return mk_call_global(cx,
-                          fmt_sp,
-                          ~[cx.parse_sess().interner.intern(@~"str"),
-                            cx.parse_sess().interner.intern(@~"concat")],
-                          ~[arg_vec]);
+    return piece_exprs;
 }
 //
 // Local Variables:
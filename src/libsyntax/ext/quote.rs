@@ -386,6 +386,19 @@ fn mk_token(cx: ext_ctxt, sp: span, tok: token::Token) -> @ast::expr {
                                   ~[mk_ident(cx, sp, ident)]);
         }
 
+        LIT_STR_RAW(ident, hashes) => {
+            return build::mk_call(cx, sp,
+                                  ids_ext(cx, ~[~"LIT_STR_RAW"]),
+                                  ~[mk_ident(cx, sp, ident),
+                                    build::mk_uint(cx, sp, hashes)]);
+        }
+
+        LIT_BINARY(ident) => {
+            return build::mk_call(cx, sp,
+                                  ids_ext(cx, ~[~"LIT_BINARY"]),
+                                  ~[mk_ident(cx, sp, ident)]);
+        }
+
         IDENT(ident, b) => {
             return build::mk_call(cx, sp,
                                   ids_ext(cx, ~[~"IDENT"]),
@@ -16,6 +16,7 @@ use ast;
 use codemap::{span, spanned, dummy_sp};
 use ext::base::{ext_ctxt, MacResult, MRAny, MRDef, MacroDef, NormalTT};
 use ext::base;
+use ext::expand::{mark_tts, rename_expr, rename_item, rename_stmt};
 use ext::tt::macro_parser::{error};
 use ext::tt::macro_parser::{named_match, matched_seq, matched_nonterminal};
 use ext::tt::macro_parser::{parse, parse_or_else, success, failure};
@@ -110,6 +111,9 @@ pub fn add_new_extension(cx: ext_ctxt, sp: span, name: ident,
                         },
                         _ => cx.span_bug(sp, ~"bad thing in rhs")
                     };
+                    // Mark the identifiers that this expansion introduces,
+                    // for hygiene; those from the arguments stay unmarked
+                    let (rhs, marks) = mark_tts(cx, rhs);
                     // rhs has holes ( `$id` and `$(...)` that need filled)
                     let trncbr = new_tt_reader(s_d, itr, Some(named_matches),
                                                rhs);
@@ -117,10 +121,10 @@ pub fn add_new_extension(cx: ext_ctxt, sp: span, name: ident,
                                     trncbr as reader);
 
                     // Let the context choose how to interpret the result.
-                    // Weird, but useful for X-macros.
-                    return MRAny(|| p.parse_expr(),
-                                  || p.parse_item(~[/* no attrs*/]),
-                                  || p.parse_stmt(~[/* no attrs*/]));
+                    // Weird, but useful for X-macros. (No attrs, either.)
+                    return MRAny(|| rename_expr(marks, p.parse_expr()),
+                                  || rename_item(marks, p.parse_item(~[])),
+                                  || rename_stmt(marks, p.parse_stmt(~[])));
                   }
                   failure(sp, ref msg) => if sp.lo >= best_fail_spot.lo {
                     best_fail_spot = sp;
@@ -13,6 +13,7 @@ use core::prelude::*;
 use ast::*;
 use ast;
 use codemap::span;
+use parse::token;
 
 use core::option;
 use core::vec;
@@ -117,11 +118,31 @@ fn fold_arg_(a: arg, fld: ast_fold) -> arg {
 //used in noop_fold_expr, and possibly elsewhere in the future
 fn fold_mac_(m: mac, fld: ast_fold) -> mac {
     spanned { node: match m.node {
-                        mac_invoc_tt(*) => m.node,
+                        mac_invoc_tt(pth, ref tts) => {
+                            mac_invoc_tt(fld.fold_path(pth),
+                                         fold_tts(*tts, fld))
+                        }
                     },
               span: fld.new_span(m.span) }
 }
 
+// The identifiers in the token trees of a macro invocation are folded too,
+// since they may end up anywhere in its expansion
+fn fold_tts(tts: &[token_tree], fld: ast_fold) -> ~[token_tree] {
+    do tts.map |tt| {
+        match *tt {
+          tt_tok(sp, token::IDENT(id, is_mod_name)) => {
+            tt_tok(sp, token::IDENT(fld.fold_ident(id), is_mod_name))
+          }
+          tt_delim(ref tts) => tt_delim(fold_tts(*tts, fld)),
+          tt_seq(sp, ref tts, ref sep, zerok) => {
+            tt_seq(sp, fold_tts(*tts, fld), copy *sep, zerok)
+          }
+          _ => copy *tt
+        }
+    }
+}
+
 pub fn fold_fn_decl(decl: ast::fn_decl, fld: ast_fold) -> ast::fn_decl {
     ast::fn_decl {
         inputs: decl.inputs.map(|x| fold_arg_(*x, fld)),
@@ -158,8 +179,43 @@ pub fn noop_fold_crate(c: crate_, fld: ast_fold) -> crate_ {
     }
 }
 
-fn noop_fold_view_item(vi: view_item_, _fld: ast_fold) -> view_item_ {
-    return /* FIXME (#2543) */ copy vi;
+fn noop_fold_view_item(vi: view_item_, fld: ast_fold) -> view_item_ {
+    match vi {
+      view_item_use(id, ref mis, node_id) => {
+        view_item_use(fld.fold_ident(id), /* FIXME (#2543) */ copy *mis,
+                      node_id)
+      }
+      view_item_import(ref vps) => {
+        view_item_import(vps.map(|vp| fold_view_path(*vp, fld)))
+      }
+    }
+}
+
+fn fold_view_path(&&vp: @view_path, fld: ast_fold) -> @view_path {
+    @spanned {
+        node: match vp.node {
+          view_path_simple(id, pth, ns, node_id) => {
+            view_path_simple(fld.fold_ident(id), fld.fold_path(pth), ns,
+                             node_id)
+          }
+          view_path_glob(pth, node_id) => {
+            view_path_glob(fld.fold_path(pth), node_id)
+          }
+          view_path_list(pth, ref idents, node_id) => {
+            let idents = do idents.map |pli| {
+                spanned {
+                    node: ast::path_list_ident_ {
+                        name: fld.fold_ident(pli.node.name),
+                        id: pli.node.id
+                    },
+                    span: fld.new_span(pli.span)
+                }
+            };
+            view_path_list(fld.fold_path(pth), idents, node_id)
+          }
+        },
+        span: fld.new_span(vp.span)
+    }
 }
 
 
@@ -204,9 +260,20 @@ pub fn noop_fold_item(&&i: @item, fld: ast_fold) -> Option<@item> {
                       span: fld.new_span(i.span) })
 }
 
+fn fold_struct_field_kind(kind: struct_field_kind,
+                          fld: ast_fold) -> struct_field_kind {
+    match kind {
+      named_field(id, mutability, vis) => {
+        named_field(fld.fold_ident(id), mutability, vis)
+      }
+      unnamed_field => unnamed_field
+    }
+}
+
 fn noop_fold_struct_field(&&sf: @struct_field, fld: ast_fold)
                        -> @struct_field {
-    @spanned { node: ast::struct_field_ { kind: copy sf.node.kind,
+    let kind = fold_struct_field_kind(sf.node.kind, fld);
+    @spanned { node: ast::struct_field_ { kind: kind,
                                           id: sf.node.id,
                                           ty: fld.fold_ty(sf.node.ty) },
                span: sf.span }
@@ -246,7 +313,12 @@ pub fn noop_fold_item_underscore(i: item_, fld: ast_fold) -> item_ {
           item_trait(tps, traits, ref methods) => {
               let methods = do (*methods).map |method| {
                   match *method {
-                      required(*) => copy *method,
+                      required(ref m) => {
+                          required(ast::ty_method {
+                              ident: fld.fold_ident(m.ident),
+                              .. copy *m
+                          })
+                      }
                       provided(method) => provided(fld.fold_method(method))
                   }
               };
@@ -254,10 +326,7 @@ pub fn noop_fold_item_underscore(i: item_, fld: ast_fold) -> item_ {
                        vec::map(traits, |p| fold_trait_ref(*p, fld)),
                        move methods)
           }
-      item_mac(ref m) => {
-        // FIXME #2888: we might actually want to do something here.
-        item_mac((*m))
-      }
+      item_mac(ref m) => item_mac(fold_mac_(*m, fld))
         };
 }
 
@@ -286,7 +355,8 @@ fn fold_trait_ref(&&p: @trait_ref, fld: ast_fold) -> @trait_ref {
 }
 
 fn fold_struct_field(&&f: @struct_field, fld: ast_fold) -> @struct_field {
-    @spanned { node: ast::struct_field_ { kind: copy f.node.kind,
+    let kind = fold_struct_field_kind(f.node.kind, fld);
+    @spanned { node: ast::struct_field_ { kind: kind,
                                           id: fld.new_id(f.node.id),
                                           ty: fld.fold_ty(f.node.ty) },
                span: fld.new_span(f.span) }
@@ -504,6 +574,7 @@ pub fn noop_fold_expr(e: expr_, fld: ast_fold) -> expr_ {
                                          fld.fold_expr(e)),
           expr_assert(e) => expr_assert(fld.fold_expr(e)),
           expr_mac(ref mac) => expr_mac(fold_mac((*mac))),
+          expr_err => expr_err,
           expr_struct(path, ref fields, maybe_expr) => {
             expr_struct(fld.fold_path(path),
                         vec::map((*fields), |x| fold_field(*x)),
@@ -718,7 +789,8 @@ pub impl ast_fold_fns: ast_fold {
     fn fold_struct_field(&&sf: @struct_field) -> @struct_field {
         @spanned {
             node: ast::struct_field_ {
-                kind: copy sf.node.kind,
+                kind: fold_struct_field_kind(sf.node.kind,
+                                             self as ast_fold),
                 id: sf.node.id,
                 ty: (self as ast_fold).fold_ty(sf.node.ty),
             },
@@ -59,7 +59,7 @@ pub impl Parser {
             s += token_to_str(self.reader, t);
             s += ~"` but found `";
             s += token_to_str(self.reader, self.token);
-            self.fatal(s + ~"`");
+            self.recoverable_err(copy self.span, s + ~"`");
         }
     }
 
@@ -70,9 +70,13 @@ pub impl Parser {
           token::IDENT(i, _) => { self.bump(); return i; }
           token::INTERPOLATED(token::nt_ident(*)) => { self.bug(
               ~"ident interpolation not converted to real token"); }
-          _ => { self.fatal(~"expected ident, found `"
-                         + token_to_str(self.reader, self.token)
-                         + ~"`"); }
+          _ => {
+            self.recoverable_err(copy self.span,
+                                 ~"expected ident, found `"
+                                 + token_to_str(self.reader, self.token)
+                                 + ~"`");
+            return token::special_idents::invalid;
+          }
         }
     }
 
@@ -139,9 +143,10 @@ pub impl Parser {
     fn expect_keyword(word: ~str) {
         self.require_keyword(word);
         if !self.eat_keyword(word) {
-            self.fatal(~"expected `" + word + ~"`, found `" +
-                       token_to_str(self.reader, self.token) +
-                       ~"`");
+            self.recoverable_err(copy self.span,
+                                 ~"expected `" + word + ~"`, found `" +
+                                 token_to_str(self.reader, self.token) +
+                                 ~"`");
         }
     }
 
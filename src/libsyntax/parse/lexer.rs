@@ -20,9 +20,12 @@ use ext::tt::transcribe::{dup_tt_reader};
 use parse::token;
 
 use core::char;
+use core::dvec::DVec;
 use core::either;
 use core::str;
 use core::u64;
+use core::uint;
+use core::vec;
 
 pub use ext::tt::transcribe::{tt_reader, new_tt_reader};
 
@@ -264,7 +267,7 @@ fn consume_any_line_comment(rdr: string_reader)
             bump(rdr);
             // line comments starting with "///" or "//!" are doc-comments
             if rdr.curr == '/' || rdr.curr == '!' {
-                let start_bpos = rdr.pos - BytePos(2u);
+                let start_bpos = rdr.last_pos - BytePos(2u);
                 let mut acc = ~"//";
                 while rdr.curr != '\n' && !is_eof(rdr) {
                     str::push_char(&mut acc, rdr.curr);
@@ -272,7 +275,7 @@ fn consume_any_line_comment(rdr: string_reader)
                 }
                 return Some(TokenAndSpan{
                     tok: token::DOC_COMMENT(rdr.interner.intern(@acc)),
-                    sp: codemap::mk_sp(start_bpos, rdr.pos)
+                    sp: codemap::mk_sp(start_bpos, rdr.last_pos)
                 });
             } else {
                 while rdr.curr != '\n' && !is_eof(rdr) { bump(rdr); }
@@ -303,7 +306,7 @@ fn consume_block_comment(rdr: string_reader)
 
     // block comments starting with "/**" or "/*!" are doc-comments
     if rdr.curr == '*' || rdr.curr == '!' {
-        let start_bpos = rdr.pos - BytePos(2u);
+        let start_bpos = rdr.last_pos - BytePos(2u);
         let mut acc = ~"/*";
         while !(rdr.curr == '*' && nextch(rdr) == '/') && !is_eof(rdr) {
             str::push_char(&mut acc, rdr.curr);
@@ -317,7 +320,7 @@ fn consume_block_comment(rdr: string_reader)
             bump(rdr);
             return Some(TokenAndSpan{
                 tok: token::DOC_COMMENT(rdr.interner.intern(@acc)),
-                sp: codemap::mk_sp(start_bpos, rdr.pos)
+                sp: codemap::mk_sp(start_bpos, rdr.last_pos)
             });
         }
     } else {
@@ -501,9 +504,102 @@ fn scan_numeric_escape(rdr: string_reader, n_hex_digits: uint) -> char {
     return accum_int as char;
 }
 
+// Scans `r"..."`, or `r#"..."#` with any number of `#`s, in which nothing
+// is escaped.
+fn scan_raw_str(rdr: string_reader) -> token::Token {
+    let n = byte_offset(rdr);
+    bump(rdr);
+    let mut hashes = 0u;
+    while rdr.curr == '#' {
+        bump(rdr);
+        hashes += 1u;
+    }
+    if rdr.curr != '"' {
+        rdr.fatal(fmt!("only `#` is allowed between `r` and the `\"` of \
+                        a raw string, found `%s`",
+                       char::escape_default(rdr.curr)));
+    }
+    bump(rdr);
+    let mut accum_str = ~"";
+    loop {
+        if is_eof(rdr) {
+            rdr.fatal(fmt!("unterminated raw string: %s",
+                           get_str_from(rdr, n)));
+        }
+        if rdr.curr == '"' && ends_raw_str(rdr, hashes) { break; }
+        str::push_char(&mut accum_str, rdr.curr);
+        bump(rdr);
+    }
+    for uint::range(0u, hashes + 1u) |_i| { bump(rdr); }
+    return token::LIT_STR_RAW(rdr.interner.intern(@accum_str), hashes);
+}
+
+// Whether the `"` at `rdr.curr` is followed by `hashes` `#`s
+fn ends_raw_str(rdr: string_reader, hashes: uint) -> bool {
+    let offset = byte_offset(rdr).to_uint();
+    if offset + hashes > (*rdr.src).len() { return false; }
+    for uint::range(offset, offset + hashes) |i| {
+        if (*rdr.src)[i] != '#' as u8 { return false; }
+    }
+    return true;
+}
+
+// Scans `b"..."`, keeping each byte as the character of the same value.
+fn scan_byte_str(rdr: string_reader) -> token::Token {
+    let n = byte_offset(rdr);
+    bump(rdr);
+    bump(rdr);
+    let mut accum_str = ~"";
+    while rdr.curr != '"' {
+        if is_eof(rdr) {
+            rdr.fatal(fmt!("unterminated byte string: %s",
+                           get_str_from(rdr, n)));
+        }
+
+        let ch = rdr.curr;
+        bump(rdr);
+        match ch {
+          '\\' => {
+            let escaped = rdr.curr;
+            bump(rdr);
+            match escaped {
+              'n' => str::push_char(&mut accum_str, '\n'),
+              'r' => str::push_char(&mut accum_str, '\r'),
+              't' => str::push_char(&mut accum_str, '\t'),
+              '\\' => str::push_char(&mut accum_str, '\\'),
+              '\'' => str::push_char(&mut accum_str, '\''),
+              '"' => str::push_char(&mut accum_str, '"'),
+              '\n' => consume_whitespace(rdr),
+              'x' => {
+                str::push_char(&mut accum_str,
+                               scan_numeric_escape(rdr, 2u));
+              }
+              c2 => {
+                rdr.fatal(fmt!("unknown byte string escape: %d",
+                               c2 as int));
+              }
+            }
+          }
+          _ if ch > '\x7f' => {
+            rdr.fatal(fmt!("non-ASCII character in byte string: %s",
+                           char::escape_unicode(ch)));
+          }
+          _ => str::push_char(&mut accum_str, ch)
+        }
+    }
+    bump(rdr);
+    return token::LIT_BINARY(rdr.interner.intern(@accum_str));
+}
+
 fn next_token_inner(rdr: string_reader) -> token::Token {
     let mut accum_str = ~"";
     let mut c = rdr.curr;
+    if c == 'r' && (nextch(rdr) == '"' || nextch(rdr) == '#') {
+        return scan_raw_str(rdr);
+    }
+    if c == 'b' && nextch(rdr) == '"' {
+        return scan_byte_str(rdr);
+    }
     if (c >= 'a' && c <= 'z')
         || (c >= 'A' && c <= 'Z')
         || c == '_'
@@ -723,12 +819,161 @@ fn consume_whitespace(rdr: string_reader) {
     while is_whitespace(rdr.curr) && !is_eof(rdr) { bump(rdr); }
 }
 
+/**
+ * A token together with its trivia, the whitespace and comments around it
+ * that the parser never sees. Joining the leading trivia, the token and the
+ * trailing trivia of each token of a file in turn gives back its source.
+ */
+#[deriving_eq]
+pub struct TriviaToken {
+    tok: token::Token,
+    sp: span,
+    /// From the end of the previous token's trivia to the token
+    leading: span,
+    /// From the token to the end of its line, when nothing but whitespace
+    /// and comments follow it there; empty otherwise
+    trailing: span
+}
+
+/**
+ * Lexes `filemap` without dropping anything: the lossless mode of the
+ * lexer, for tools that rewrite source. The last token is `EOF`, whose
+ * leading trivia runs to the end of the file.
+ *
+ * The spans are those of the `CodeMap` that `filemap` belongs to, so they
+ * line up with the spans of the AST parsed from it.
+ */
+pub fn lossless_tokens(span_diagnostic: span_handler,
+                       filemap: @codemap::FileMap,
+                       itr: @token::ident_interner) -> ~[TriviaToken] {
+    // Lex a copy of the file map, so as not to record its lines twice
+    let fm = @codemap::FileMap {
+        name: copy filemap.name,
+        substr: codemap::FssNone,
+        src: filemap.src,
+        start_pos: filemap.start_pos,
+        mut lines: ~[],
+        multibyte_chars: DVec()
+    };
+    let rdr = new_string_reader(span_diagnostic, fm, itr);
+    let end = fm.start_pos + BytePos((*fm.src).len());
+
+    let mut toks = ~[];
+    let mut trivia_lo = fm.start_pos;
+    loop {
+        let t = rdr.next_token();
+        if t.tok == token::EOF {
+            toks.push(TriviaToken {
+                tok: token::EOF,
+                sp: codemap::mk_sp(end, end),
+                leading: codemap::mk_sp(trivia_lo, end),
+                trailing: codemap::mk_sp(end, end)
+            });
+            return toks;
+        }
+        let next_lo = if rdr.peek_tok == token::EOF {
+            end
+        } else {
+            rdr.peek_span.lo
+        };
+        let trailing_hi = trailing_trivia_end(fm, t.sp.hi, next_lo);
+        toks.push(TriviaToken {
+            tok: t.tok,
+            sp: t.sp,
+            leading: codemap::mk_sp(trivia_lo, t.sp.lo),
+            trailing: codemap::mk_sp(t.sp.hi, trailing_hi)
+        });
+        trivia_lo = trailing_hi;
+    }
+}
+
+// Where the trivia between `lo` and `hi` stops belonging to the token
+// before it: just after the first newline that is not within a block
+// comment, or at `lo` if the next token is on the same line
+fn trailing_trivia_end(fm: @codemap::FileMap, lo: BytePos, hi: BytePos)
+    -> BytePos {
+    let src: &str = *fm.src;
+    let hi = (hi - fm.start_pos).to_uint();
+    let mut i = (lo - fm.start_pos).to_uint();
+    while i < hi {
+        let c = src[i] as char;
+        let next = if i + 1u < hi { src[i + 1u] as char } else { ' ' };
+        if c == '\n' {
+            return fm.start_pos + BytePos(i + 1u);
+        } else if c == '/' && next == '/' {
+            while i < hi && src[i] as char != '\n' { i += 1u; }
+        } else if c == '/' && next == '*' {
+            i += 2u;
+            while i + 1u < hi &&
+                !(src[i] as char == '*' && src[i + 1u] as char == '/') {
+                i += 1u;
+            }
+            i += 2u;
+        } else {
+            i += 1u;
+        }
+    }
+    lo
+}
+
+/**
+ * The span of the source that the AST node at `sp` takes up together with
+ * its trivia: the comments and blank lines before its first token, and the
+ * rest of the line after its last. Replacing or removing that span takes
+ * the node's comments with it, and leaves those of its neighbours alone.
+ */
+pub fn trivia_span(toks: &[TriviaToken], sp: span) -> span {
+    let first = vec::position(toks, |t| t.sp.hi > sp.lo);
+    let last = vec::rposition(toks, |t| t.sp.lo < sp.hi);
+    match (first, last) {
+      (Some(first), Some(last)) if first <= last => {
+        codemap::mk_sp(toks[first].leading.lo, toks[last].trailing.hi)
+      }
+      _ => sp
+    }
+}
+
+/**
+ * Regenerates the source of `filemap` with each `(span, text)` edit made
+ * to it, and every byte outside the edited spans as it was. The spans may
+ * be those of AST nodes or those given by `trivia_span`, and must not
+ * overlap.
+ */
+pub fn apply_edits(filemap: @codemap::FileMap, edits: &[(span, ~str)])
+    -> ~str {
+    pure fn lteq(a: &(span, ~str), b: &(span, ~str)) -> bool {
+        let (ref sa, _) = *a;
+        let (ref sb, _) = *b;
+        sa.lo <= sb.lo
+    }
+    let edits = std::sort::merge_sort(edits, lteq);
+
+    let src: &str = *filemap.src;
+    let mut out = ~"";
+    let mut pos = 0u;
+    for edits.each |edit| {
+        let (ref sp, ref text) = *edit;
+        let lo = (sp.lo - filemap.start_pos).to_uint();
+        let hi = (sp.hi - filemap.start_pos).to_uint();
+        if lo < pos || hi < lo || hi > src.len() {
+            die!(fmt!("apply_edits: edit of bytes %u to %u overlaps another \
+                       or is outside the file", lo, hi));
+        }
+        str::push_str(&mut out, str::slice(src, pos, lo));
+        str::push_str(&mut out, *text);
+        pos = hi;
+    }
+    str::push_str(&mut out, str::slice(src, pos, src.len()));
+    out
+}
+
 #[cfg(test)]
 pub mod test {
 
     use super::*;
     use util::interner;
     use diagnostic;
+    use parse;
     use util::testing::{check_equal, check_equal_ptr};
     #[test] fn t1 () {
         let teststr =
@@ -757,6 +1002,87 @@ fn main() { io::println(~\"zebra\"); }\n";
         // the lparen is already read:
         check_equal (string_reader.last_pos,BytePos(29))
     }
+
+    fn lossless_setup(src: ~str) -> (@codemap::FileMap, ~[TriviaToken]) {
+        let cm = CodeMap::new();
+        let fm = cm.new_filemap(~"lossless.rs", @src);
+        let span_handler =
+            diagnostic::mk_span_handler(diagnostic::mk_handler(None),@cm);
+        let toks = lossless_tokens(span_handler, fm,
+                                   token::mk_ident_interner());
+        (fm, toks)
+    }
+
+    fn snippet(fm: @codemap::FileMap, sp: span) -> ~str {
+        str::slice(*fm.src, (sp.lo - fm.start_pos).to_uint(),
+                   (sp.hi - fm.start_pos).to_uint())
+    }
+
+    #[test] fn lossless_round_trip() {
+        let src = ~"#!/bin/rust\n/* one */ fn f() { // two\n\n    g(); \
+                    /* three\n four */\n}\n/// five\nfn h() {}\n// six";
+        let (fm, toks) = lossless_setup(copy src);
+        let mut out = ~"";
+        for toks.each |t| {
+            out += snippet(fm, t.leading);
+            out += snippet(fm, t.sp);
+            out += snippet(fm, t.trailing);
+        }
+        check_equal(out, src);
+        check_equal(toks.last().tok, token::EOF);
+        check_equal(snippet(fm, toks.last().leading), ~"// six");
+        // The doc comment is a token of its own
+        assert vec::any(toks, |t| snippet(fm, t.sp) == ~"/// five");
+    }
+
+    #[test] fn lossless_trivia() {
+        let (fm, toks) =
+            lossless_setup(~"fn f() {} // one\n// two\nfn g() {}\n");
+        // The comment on the line of `}` trails it, the one after leads `fn`
+        check_equal(snippet(fm, toks[5].trailing), ~" // one\n");
+        check_equal(snippet(fm, toks[6].leading), ~"// two\n");
+        check_equal(snippet(fm, toks[1].trailing), ~"");
+
+        let g = codemap::mk_sp(toks[6].sp.lo, toks[11].sp.hi);
+        check_equal(snippet(fm, trivia_span(toks, g)),
+                    ~"// two\nfn g() {}\n");
+        check_equal(apply_edits(fm, ~[(trivia_span(toks, g), ~""),
+                                      (toks[1].sp, ~"main")]),
+                    ~"fn main() {} // one\n");
+    }
+
+    #[test] fn lossless_item_edits() {
+        let src = ~"// one\nfn f() {}\n\n// two\nfn g() {\n    \
+                    f(); // three\n}\n// four\nfn h() {}\n";
+        let sess = parse::new_parse_sess(None);
+        let crate = parse::parse_crate_from_source_str(
+            ~"lossless.rs", @copy src, ~[], sess);
+        let fm = sess.cm.files[0];
+        let toks = lossless_tokens(sess.span_diagnostic, fm, sess.interner);
+        let items = crate.node.module.items;
+
+        // An item's trivia are the comments above it and its line's end
+        check_equal(snippet(fm, trivia_span(toks, items[1].span)),
+                    ~"\n// two\nfn g() {\n    f(); // three\n}\n");
+        check_equal(apply_edits(fm, ~[(trivia_span(toks, items[1].span),
+                                       ~""),
+                                      (items[2].span,
+                                       ~"fn h() -> int { 1 }")]),
+                    ~"// one\nfn f() {}\n// four\nfn h() -> int { 1 }\n");
+        check_equal(apply_edits(fm, ~[]), src);
+    }
+
+    #[test] fn doc_comment_spans() {
+        let (fm, toks) =
+            lossless_setup(~"fn f() {}\n/// five\n/** six */ fn g() {}\n");
+        let docs = do toks.filtered |t| {
+            match t.tok { token::DOC_COMMENT(_) => true, _ => false }
+        };
+        check_equal(docs.len(), 2u);
+        check_equal(docs[0].sp.lo, fm.start_pos + BytePos(10u));
+        check_equal(snippet(fm, docs[0].sp), ~"/// five");
+        check_equal(snippet(fm, docs[1].sp), ~"/** six */");
+    }
 }
 
 //
@@ -23,7 +23,7 @@ use ast::{decl_local, default_blk, deref, div, enum_def, enum_variant_kind};
 use ast::{expl, expr, expr_, expr_addr_of, expr_match, expr_again};
 use ast::{expr_assert, expr_assign, expr_assign_op, expr_binary, expr_block};
 use ast::{expr_break, expr_call, expr_cast, expr_copy, expr_do_body};
-use ast::{expr_field, expr_fn, expr_fn_block, expr_if, expr_index};
+use ast::{expr_err, expr_field, expr_fn, expr_fn_block, expr_if, expr_index};
 use ast::{expr_lit, expr_log, expr_loop, expr_loop_body, expr_mac};
 use ast::{expr_method_call, expr_paren, expr_path, expr_rec, expr_repeat};
 use ast::{expr_ret, expr_swap, expr_struct, expr_tup, expr_unary};
@@ -35,7 +35,7 @@ use ast::{foreign_item, foreign_item_const, foreign_item_fn, foreign_mod};
 use ast::{ident, impure_fn, infer, inherited, item, item_, item_const};
 use ast::{item_const, item_enum, item_fn, item_foreign_mod, item_impl};
 use ast::{item_mac, item_mod, item_struct, item_trait, item_ty, lit, lit_};
-use ast::{lit_bool, lit_float, lit_float_unsuffixed, lit_int};
+use ast::{lit_binary, lit_bool, lit_float, lit_float_unsuffixed, lit_int};
 use ast::{lit_int_unsuffixed, lit_nil, lit_str, lit_uint, local, m_const};
 use ast::{m_imm, m_mutbl, mac_, mac_invoc_tt, matcher, match_nonterminal};
 use ast::{match_seq, match_tok, method, mode, module_ns, mt, mul, mutability};
@@ -89,10 +89,16 @@ use core::dvec;
 use core::either::{Either, Left, Right};
 use core::either;
 use core::result::Result;
+use core::str;
 use core::vec::push;
 use core::vec;
 use std::oldmap::HashMap;
 
+/// The error for input that ends inside a delimited token tree, which tools
+/// reading a line at a time can take as a sign of more to come
+pub const eof_in_token_tree: &static/str =
+    "file ended in the middle of a macro invocation";
+
 #[deriving_eq]
 enum restriction {
     UNRESTRICTED,
@@ -209,6 +215,9 @@ pub fn Parser(sess: parse_sess,
         reserved_keywords: token::reserved_keyword_table(),
         obsolete_set: HashMap(),
         mod_path_stack: ~[],
+        recovering: false,
+        last_error_at: 0u,
+        stalled_errors: 0u,
     }
 }
 
@@ -234,6 +243,13 @@ pub struct Parser {
     obsolete_set: HashMap<ObsoleteSyntax, ()>,
     /// Used to determine the path to externally loaded source files
     mut mod_path_stack: ~[~str],
+    /// Set by a syntax error until the parser has skipped to the end of the
+    /// statement or item it was in; see `recover`
+    mut recovering: bool,
+    /// The token count at the last syntax error, and the number of errors
+    /// in a row at that point, so that the parser can't get stuck
+    mut last_error_at: uint,
+    mut stalled_errors: uint,
 
     drop {} /* do not copy the parser; its state is tied to outside state */
 }
@@ -293,6 +309,80 @@ pub impl Parser {
     fn abort_if_errors() {
         self.sess.span_diagnostic.handler().abort_if_errors();
     }
+
+    // Reports a syntax error that the parser can carry on after, rather
+    // than aborting. The errors that follow it before the parser recovers
+    // are most likely caused by it, so those are not reported.
+    fn recoverable_err(sp: span, m: ~str) {
+        if self.tokens_consumed == self.last_error_at {
+            self.stalled_errors += 1u;
+            if self.stalled_errors > 100u {
+                // Not getting anywhere; give up on the errors so far
+                self.abort_if_errors();
+            }
+        } else {
+            self.last_error_at = self.tokens_consumed;
+            self.stalled_errors = 0u;
+        }
+        if !self.recovering {
+            self.span_err(sp, m);
+            self.recovering = true;
+        }
+    }
+
+    fn is_item_start() -> bool {
+        if self.token == token::POUND {
+            return true;
+        }
+        let words = ~[~"const", ~"enum", ~"extern", ~"fn", ~"impl", ~"let",
+                      ~"mod", ~"priv", ~"pub", ~"pure", ~"struct", ~"trait",
+                      ~"type", ~"use"];
+        for words.each |word| {
+            if self.is_keyword(*word) {
+                return true;
+            }
+        }
+        false
+    }
+
+    // After a syntax error, skips to the end of the statement or item that
+    // the parser was in: past the next `;` or closing `}` at this level,
+    // or up to the start of the next item or `let`, or to the end of the
+    // enclosing block. Any delimited groups along the way are skipped whole.
+    fn recover() {
+        if !self.recovering {
+            return;
+        }
+        let mut depth = 0u;
+        loop {
+            match self.token {
+              token::EOF => break,
+              token::SEMI if depth == 0u => {
+                self.bump();
+                break;
+              }
+              token::RBRACE if depth == 0u => break,
+              token::LBRACE | token::LPAREN | token::LBRACKET => {
+                depth += 1u;
+              }
+              token::RBRACE if depth == 1u => {
+                self.bump();
+                break;
+              }
+              token::RBRACE | token::RPAREN | token::RBRACKET => {
+                // A stray closing delimiter at this level is skipped
+                if depth > 0u { depth -= 1u; }
+              }
+              _ => {
+                if depth == 0u && self.is_item_start() {
+                    break;
+                }
+              }
+            }
+            self.bump();
+        }
+        self.recovering = false;
+    }
     fn get_id() -> node_id { next_node_id(self.sess) }
 
     pure fn id_to_str(id: ident) -> @~str { self.sess.interner.get(id) }
@@ -805,7 +895,11 @@ pub impl Parser {
           token::LIT_FLOAT(s, ft) => lit_float(self.id_to_str(s), ft),
           token::LIT_FLOAT_UNSUFFIXED(s) =>
             lit_float_unsuffixed(self.id_to_str(s)),
-          token::LIT_STR(s) => lit_str(self.id_to_str(s)),
+          token::LIT_STR(s) | token::LIT_STR_RAW(s, _) =>
+            lit_str(self.id_to_str(s)),
+          token::LIT_BINARY(s) => {
+            lit_binary(@str::chars(*self.id_to_str(s)).map(|c| *c as u8))
+          }
           token::LPAREN => { self.expect(token::RPAREN); lit_nil },
           _ => { self.unexpected_last(tok); }
         }
@@ -1145,10 +1239,19 @@ pub impl Parser {
 
             hi = pth.span.hi;
             ex = expr_path(pth);
-        } else {
+        } else if token::is_lit(self.token) || self.is_keyword(~"true")
+                || self.is_keyword(~"false") {
             let lit = self.parse_lit();
             hi = lit.span.hi;
             ex = expr_lit(@lit);
+        } else {
+            // Leave the token for recovery to skip, in case it closes the
+            // statement or block
+            self.recoverable_err(copy self.span,
+                                 ~"unexpected token: `" +
+                                 token_to_str(self.reader, self.token) +
+                                 ~"`");
+            ex = expr_err;
         }
 
         return self.mk_expr(lo, hi, ex);
@@ -1270,7 +1373,7 @@ pub impl Parser {
                            + token_to_str(p.reader, p.token) + ~"`");
               }
               token::EOF => {
-                p.fatal(~"file ended in the middle of a macro invocation");
+                p.fatal(eof_in_token_tree.to_owned());
               }
               /* we ought to allow different depths of unquotation */
               token::DOLLAR if p.quote_depth > 0u => {
@@ -1424,6 +1527,8 @@ pub impl Parser {
                 ex = match e.node {
                   expr_vec(*) | expr_lit(@codemap::spanned {
                     node: lit_str(_), span: _
+                  }) | expr_lit(@codemap::spanned {
+                    node: lit_binary(_), span: _
                   })
                   if m == m_imm => {
                     expr_vstore(e, expr_vstore_slice)
@@ -1448,7 +1553,9 @@ pub impl Parser {
                 expr_vstore(e, expr_vstore_mut_box),
               expr_vec(*) if m == m_imm => expr_vstore(e, expr_vstore_box),
               expr_lit(@codemap::spanned {
-                  node: lit_str(_), span: _}) if m == m_imm =>
+                  node: lit_str(_), span: _}) |
+              expr_lit(@codemap::spanned {
+                  node: lit_binary(_), span: _}) if m == m_imm =>
                 expr_vstore(e, expr_vstore_box),
               _ => expr_unary(box(m), e)
             };
@@ -1461,7 +1568,8 @@ pub impl Parser {
             // HACK: turn ~[...] into a ~-evec
             ex = match e.node {
               expr_vec(*) | expr_lit(@codemap::spanned {
-                node: lit_str(_), span: _})
+                node: lit_str(_), span: _}) | expr_lit(@codemap::spanned {
+                node: lit_binary(_), span: _})
               if m == m_imm => expr_vstore(e, expr_vstore_uniq),
               _ => expr_unary(uniq(m), e)
             };
@@ -2405,7 +2513,7 @@ pub impl Parser {
             self.fatal(~"expected item");
         }
 
-        while self.token != token::RBRACE {
+        while self.token != token::RBRACE && self.token != token::EOF {
             match self.token {
                 token::SEMI => {
                     self.bump(); // empty
@@ -2413,6 +2521,12 @@ pub impl Parser {
                 _ => {
                     let stmt = self.parse_stmt(initial_attrs);
                     initial_attrs = ~[];
+                    if self.recovering {
+                        // Carry on after the statement with the error
+                        self.recover();
+                        stmts.push(stmt);
+                        loop;
+                    }
                     match stmt.node {
                         stmt_expr(e, stmt_id) => {
                             // Expression without semicolon
@@ -2428,11 +2542,13 @@ pub impl Parser {
                                 }
                                 copy t => {
                                     if classify::stmt_ends_with_semi(*stmt) {
-                                        self.fatal(
+                                        self.recoverable_err(
+                                            copy self.span,
                                             ~"expected `;` or `}` after \
                                               expression but found `"
                                             + token_to_str(self.reader, t)
                                             + ~"`");
+                                        self.recover();
                                     }
                                     stmts.push(stmt);
                                 }
@@ -2465,6 +2581,7 @@ pub impl Parser {
 
                             if classify::stmt_ends_with_semi(*stmt) {
                                 self.expect(token::SEMI);
+                                self.recover();
                             }
                         }
                     }
@@ -2472,7 +2589,7 @@ pub impl Parser {
             }
         }
         let mut hi = self.span.hi;
-        self.bump();
+        self.expect(token::RBRACE);
         let bloc = ast::blk_ {
             view_items: view_items,
             stmts: stmts,
@@ -3105,7 +3222,7 @@ pub impl Parser {
         let mut items: ~[@item] = move starting_items;
 
         let mut first = true;
-        while self.token != term {
+        while self.token != term && self.token != token::EOF {
             let mut attrs = self.parse_outer_attributes();
             if first {
                 attrs = vec::append(attrs_remaining, attrs);
@@ -3116,15 +3233,23 @@ pub impl Parser {
             match self.parse_item_or_view_item(attrs, true, false, true) {
               iovi_item(item) => items.push(item),
               iovi_view_item(view_item) => {
-                self.span_fatal(view_item.span, ~"view items must be \
-                                                  declared at the top of the \
-                                                  module");
+                self.span_err(view_item.span, ~"view items must be \
+                                                declared at the top of the \
+                                                module");
               }
               _ => {
-                self.fatal(~"expected item but found `" +
-                           token_to_str(self.reader, self.token) + ~"`");
+                self.recoverable_err(copy self.span,
+                                     ~"expected item but found `" +
+                                     token_to_str(self.reader, self.token) +
+                                     ~"`");
+                // Skip at least the token that can't start an item
+                if self.token != term && self.token != token::EOF {
+                    self.bump();
+                }
               }
             }
+            // Carry on with the next item after one with an error in it
+            self.recover();
             debug!("parse_mod_items: attrs=%?", attrs);
         }
 
@@ -3357,7 +3482,8 @@ pub impl Parser {
         // Parse the ABI.
         let abi_opt;
         match self.token {
-            token::LIT_STR(copy found_abi) => {
+            token::LIT_STR(copy found_abi) |
+            token::LIT_STR_RAW(copy found_abi, _) => {
                 self.bump();
                 abi_opt = Some(found_abi);
             }
@@ -3993,6 +4119,7 @@ pub impl Parser {
                     foreign_items.push(foreign_item);
                 }
             }
+            self.recover();
             attrs = self.parse_outer_attributes();
         }
 
@@ -4016,7 +4143,10 @@ pub impl Parser {
 
     fn parse_str() -> @~str {
         match copy self.token {
-          token::LIT_STR(s) => { self.bump(); self.id_to_str(s) }
+          token::LIT_STR(s) | token::LIT_STR_RAW(s, _) => {
+            self.bump();
+            self.id_to_str(s)
+          }
           _ =>  self.fatal(~"expected string literal")
         }
     }
@@ -84,6 +84,10 @@ pub enum Token {
     LIT_FLOAT(ast::ident, ast::float_ty),
     LIT_FLOAT_UNSUFFIXED(ast::ident),
     LIT_STR(ast::ident),
+    /// A raw string and the number of `#`s around it
+    LIT_STR_RAW(ast::ident, uint),
+    /// A byte string, whose characters stand for its bytes
+    LIT_BINARY(ast::ident),
 
     /* Name components */
     IDENT(ast::ident, bool),
@@ -190,6 +194,14 @@ pub fn to_str(in: @ident_interner, t: Token) -> ~str {
         body
       }
       LIT_STR(s) => { ~"\"" + str::escape_default(*in.get(s)) + ~"\"" }
+      LIT_STR_RAW(s, n) => {
+        let hashes = str::repeat(~"#", n);
+        fmt!("r%s\"%s\"%s", hashes, *in.get(s), hashes)
+      }
+      LIT_BINARY(s) => {
+        let bytes = str::chars(*in.get(s)).map(|c| *c as u8);
+        ast_util::binary_lit_to_str(bytes)
+      }
 
       /* Name components */
       IDENT(s, _) => *in.get(s),
@@ -236,6 +248,8 @@ pub pure fn can_begin_expr(t: Token) -> bool {
       LIT_FLOAT(_, _) => true,
       LIT_FLOAT_UNSUFFIXED(_) => true,
       LIT_STR(_) => true,
+      LIT_STR_RAW(_, _) => true,
+      LIT_BINARY(_) => true,
       POUND => true,
       AT => true,
       NOT => true,
@@ -276,6 +290,8 @@ pub fn is_lit(t: Token) -> bool {
       LIT_FLOAT(_, _) => true,
       LIT_FLOAT_UNSUFFIXED(_) => true,
       LIT_STR(_) => true,
+      LIT_STR_RAW(_, _) => true,
+      LIT_BINARY(_) => true,
       _ => false
     }
 }
@@ -363,6 +379,11 @@ pub impl ident_interner {
     pure fn get(idx: ast::ident) -> @~str {
         self.interner.get(idx.repr)
     }
+    /// The interned ident with the same name as `idx`, which undoes a
+    /// gensym such as the marks that macro expansion puts on identifiers
+    fn unmark(idx: ast::ident) -> ast::ident {
+        self.intern(self.get(idx))
+    }
     fn len() -> uint {
         self.interner.len()
     }
@@ -754,6 +775,18 @@ impl Token : cmp::Eq {
                     _ => false
                 }
             }
+            LIT_STR_RAW(e0a, e1a) => {
+                match (*other) {
+                    LIT_STR_RAW(e0b, e1b) => e0a == e0b && e1a == e1b,
+                    _ => false
+                }
+            }
+            LIT_BINARY(e0a) => {
+                match (*other) {
+                    LIT_BINARY(e0b) => e0a == e0b,
+                    _ => false
+                }
+            }
             IDENT(e0a, e1a) => {
                 match (*other) {
                     IDENT(e0b, e1b) => e0a == e0b && e1a == e1b,
@@ -63,7 +63,12 @@ pub type ps =
       mut cur_cmnt: uint,
       mut cur_lit: uint,
       boxes: DVec<pp::breaks>,
-      ann: pp_ann};
+      ann: pp_ann,
+      indent: uint,
+      match_indent: uint,
+      // Whether to print `mod foo;` for the modules that were read from
+      // files of their own, rather than what was read from them
+      mod_decls: bool};
 
 pub fn ibox(s: ps, u: uint) {
     s.boxes.push(pp::inconsistent);
@@ -84,7 +89,10 @@ pub fn rust_printer(writer: io::Writer, intr: @ident_interner) -> ps {
              mut cur_cmnt: 0u,
              mut cur_lit: 0u,
              boxes: DVec(),
-             ann: no_ann()};
+             ann: no_ann(),
+             indent: indent_unit,
+             match_indent: match_indent_unit,
+             mod_decls: false};
 }
 
 pub const indent_unit: uint = 4u;
@@ -113,7 +121,10 @@ pub fn print_crate(cm: @CodeMap, intr: @ident_interner,
           mut cur_cmnt: 0u,
           mut cur_lit: 0u,
           boxes: DVec(),
-          ann: ann};
+          ann: ann,
+          indent: indent_unit,
+          match_indent: match_indent_unit,
+          mod_decls: false};
     print_crate_(s, crate);
 }
 
@@ -174,7 +185,7 @@ pub fn block_to_str(blk: ast::blk, intr: @ident_interner) -> ~str {
     do io::with_str_writer |wr| {
         let s = rust_printer(wr, intr);
         // containing cbox, will be closed by print-block at }
-        cbox(s, indent_unit);
+        cbox(s, s.indent);
         // head-ibox, will be closed by print-block after {
         ibox(s, 0u);
         print_block(s, blk);
@@ -216,7 +227,7 @@ pub fn pclose(s: ps) { word(s.s, ~")"); }
 
 pub fn head(s: ps, w: ~str) {
     // outer-box is consistent
-    cbox(s, indent_unit);
+    cbox(s, s.indent);
     // head-box is inconsistent
     ibox(s, str::len(w) + 1);
     // keyword that starts the head
@@ -242,7 +253,7 @@ pub fn bclose_maybe_open (s: ps, span: codemap::span, indented: uint,
         end(s); // close the outer-box
     }
 }
-pub fn bclose(s: ps, span: codemap::span) { bclose_(s, span, indent_unit); }
+pub fn bclose(s: ps, span: codemap::span) { bclose_(s, span, s.indent); }
 
 pub fn is_begin(s: ps) -> bool {
     match s.s.last_token() { pp::BEGIN(_) => true, _ => false }
@@ -388,7 +399,7 @@ pub fn print_type_ex(s: ps, &&ty: @ast::Ty, print_colons: bool) {
       ast::ty_rec(ref fields) => {
         word(s.s, ~"{");
         fn print_field(s: ps, f: ast::ty_field) {
-            cbox(s, indent_unit);
+            cbox(s, s.indent);
             print_mutability(s, f.node.mt.mutbl);
             print_ident(s, f.node.ident);
             word_space(s, ~":");
@@ -462,6 +473,15 @@ pub fn print_foreign_item(s: ps, item: @ast::foreign_item) {
     }
 }
 
+// A module read from a file of its own is spanned by its `mod foo;`
+// declaration.
+fn is_mod_decl(s: ps, item: @ast::item) -> bool {
+    match s.cm {
+      Some(cm) => cm.span_to_snippet(item.span).ends_with(";"),
+      None => false
+    }
+}
+
 pub fn print_item(s: ps, &&item: @ast::item) {
     hardbreak_if_not_bol(s);
     maybe_print_comment(s, item.span.lo);
@@ -489,6 +509,13 @@ pub fn print_item(s: ps, &&item: @ast::item) {
         word(s.s, ~" ");
         print_block_with_attrs(s, (*body), item.attrs);
       }
+      ast::item_mod(_) if s.mod_decls && is_mod_decl(s, item) => {
+        head(s, visibility_qualified(item.vis, ~"mod"));
+        print_ident(s, item.ident);
+        word(s.s, ~";");
+        end(s); // end the head-ibox
+        end(s); // end the outer cbox
+      }
       ast::item_mod(_mod) => {
         head(s, visibility_qualified(item.vis, ~"mod"));
         print_ident(s, item.ident);
@@ -514,7 +541,7 @@ pub fn print_item(s: ps, &&item: @ast::item) {
         bclose(s, item.span);
       }
       ast::item_ty(ty, params) => {
-        ibox(s, indent_unit);
+        ibox(s, s.indent);
         ibox(s, 0u);
         word_nbsp(s, visibility_qualified(item.vis, ~"type"));
         print_ident(s, item.ident);
@@ -587,7 +614,7 @@ pub fn print_item(s: ps, &&item: @ast::item) {
         print_path(s, pth, false);
         word(s.s, ~"! ");
         print_ident(s, item.ident);
-        cbox(s, indent_unit);
+        cbox(s, s.indent);
         popen(s);
         print_tts(s, *tts);
         pclose(s);
@@ -610,7 +637,7 @@ pub fn print_enum_def(s: ps, enum_definition: ast::enum_def,
         }
     }
     if newtype {
-        ibox(s, indent_unit);
+        ibox(s, s.indent);
         word_space(s, visibility_qualified(visibility, ~"enum"));
     } else {
         head(s, visibility_qualified(visibility, ~"enum"));
@@ -640,7 +667,7 @@ pub fn print_variants(s: ps,
         space_if_not_bol(s);
         maybe_print_comment(s, v.span.lo);
         print_outer_attributes(s, v.node.attrs);
-        ibox(s, indent_unit);
+        ibox(s, s.indent);
         print_variant(s, *v);
         word(s.s, ~",");
         end(s);
@@ -905,11 +932,11 @@ pub fn print_stmt(s: ps, st: ast::stmt) {
 }
 
 pub fn print_block(s: ps, blk: ast::blk) {
-    print_possibly_embedded_block(s, blk, block_normal, indent_unit);
+    print_possibly_embedded_block(s, blk, block_normal, s.indent);
 }
 
 pub fn print_block_unclosed(s: ps, blk: ast::blk) {
-    print_possibly_embedded_block_(s, blk, block_normal, indent_unit, ~[],
+    print_possibly_embedded_block_(s, blk, block_normal, s.indent, ~[],
                                  false);
 }
 
@@ -921,7 +948,7 @@ pub fn print_block_unclosed_indent(s: ps, blk: ast::blk, indented: uint) {
 pub fn print_block_with_attrs(s: ps,
                               blk: ast::blk,
                               attrs: ~[ast::attribute]) {
-    print_possibly_embedded_block_(s, blk, block_normal, indent_unit, attrs,
+    print_possibly_embedded_block_(s, blk, block_normal, s.indent, attrs,
                                   true);
 }
 
@@ -984,7 +1011,7 @@ pub fn print_if(s: ps, test: @ast::expr, blk: ast::blk,
             match _else.node {
               // "another else-if"
               ast::expr_if(i, ref t, e) => {
-                cbox(s, indent_unit - 1u);
+                cbox(s, s.indent - 1u);
                 ibox(s, 0u);
                 word(s.s, ~" else if ");
                 print_expr(s, i);
@@ -994,7 +1021,7 @@ pub fn print_if(s: ps, test: @ast::expr, blk: ast::blk,
               }
               // "final else"
               ast::expr_block(ref b) => {
-                cbox(s, indent_unit - 1u);
+                cbox(s, s.indent - 1u);
                 ibox(s, 0u);
                 word(s.s, ~" else ");
                 print_block(s, (*b));
@@ -1097,7 +1124,7 @@ pub fn print_call_post(s: ps,
 
 pub fn print_expr(s: ps, &&expr: @ast::expr) {
     fn print_field(s: ps, field: ast::field) {
-        ibox(s, indent_unit);
+        ibox(s, s.indent);
         if field.node.mutbl == ast::m_mutbl { word_nbsp(s, ~"mut"); }
         print_ident(s, field.node.ident);
         word_space(s, ~":");
@@ -1107,7 +1134,7 @@ pub fn print_expr(s: ps, &&expr: @ast::expr) {
     fn get_span(field: ast::field) -> codemap::span { return field.span; }
 
     maybe_print_comment(s, expr.span.lo);
-    ibox(s, indent_unit);
+    ibox(s, s.indent);
     let ann_node = node_expr(s, expr);
     (s.ann.pre)(ann_node);
     match expr.node {
@@ -1123,7 +1150,7 @@ pub fn print_expr(s: ps, &&expr: @ast::expr) {
             }
         },
       ast::expr_vec(exprs, mutbl) => {
-        ibox(s, indent_unit);
+        ibox(s, s.indent);
         word(s.s, ~"[");
         if mutbl == ast::m_mutbl {
             word(s.s, ~"mut");
@@ -1135,7 +1162,7 @@ pub fn print_expr(s: ps, &&expr: @ast::expr) {
       }
 
       ast::expr_repeat(element, count, mutbl) => {
-        ibox(s, indent_unit);
+        ibox(s, s.indent);
         word(s.s, ~"[");
         if mutbl == ast::m_mutbl {
             word(s.s, ~"mut");
@@ -1154,7 +1181,7 @@ pub fn print_expr(s: ps, &&expr: @ast::expr) {
         commasep_cmnt(s, consistent, (*fields), print_field, get_span);
         match wth {
           Some(expr) => {
-            ibox(s, indent_unit);
+            ibox(s, s.indent);
             word(s.s, ~",");
             space(s.s);
             word(s.s, ~"..");
@@ -1171,7 +1198,7 @@ pub fn print_expr(s: ps, &&expr: @ast::expr) {
         commasep_cmnt(s, consistent, (*fields), print_field, get_span);
         match wth {
             Some(expr) => {
-                ibox(s, indent_unit);
+                ibox(s, s.indent);
                 word(s.s, ~",");
                 space(s.s);
                 word(s.s, ~"..");
@@ -1247,7 +1274,7 @@ pub fn print_expr(s: ps, &&expr: @ast::expr) {
         print_block(s, (*blk));
       }
       ast::expr_match(expr, ref arms) => {
-        cbox(s, match_indent_unit);
+        cbox(s, s.match_indent);
         ibox(s, 4);
         word_nbsp(s, ~"match");
         print_expr(s, expr);
@@ -1256,7 +1283,7 @@ pub fn print_expr(s: ps, &&expr: @ast::expr) {
         let len = (*arms).len();
         for (*arms).eachi |i, arm| {
             space(s.s);
-            cbox(s, match_indent_unit);
+            cbox(s, s.match_indent);
             ibox(s, 0u);
             let mut first = true;
             for arm.pats.each |p| {
@@ -1289,7 +1316,7 @@ pub fn print_expr(s: ps, &&expr: @ast::expr) {
                             ast::expr_block(ref blk) => {
                                 // the block will close the pattern's ibox
                                 print_block_unclosed_indent(
-                                    s, (*blk), match_indent_unit);
+                                    s, (*blk), s.match_indent);
                             }
                             _ => {
                                 end(s); // close the ibox for the pattern
@@ -1306,14 +1333,14 @@ pub fn print_expr(s: ps, &&expr: @ast::expr) {
                 }
             } else {
                 // the block will close the pattern's ibox
-                print_block_unclosed_indent(s, arm.body, match_indent_unit);
+                print_block_unclosed_indent(s, arm.body, s.match_indent);
             }
         }
-        bclose_(s, expr.span, match_indent_unit);
+        bclose_(s, expr.span, s.match_indent);
       }
       ast::expr_fn(sigil, decl, ref body, _) => {
         // containing cbox, will be closed by print-block at }
-        cbox(s, indent_unit);
+        cbox(s, s.indent);
         // head-box, will be closed by print-block at start
         ibox(s, 0u);
         print_fn_header_info(s, None, None, ast::Many,
@@ -1357,7 +1384,7 @@ pub fn print_expr(s: ps, &&expr: @ast::expr) {
       }
       ast::expr_block(ref blk) => {
         // containing cbox, will be closed by print-block at }
-        cbox(s, indent_unit);
+        cbox(s, s.indent);
         // head-box, will be closed by print-block after {
         ibox(s, 0u);
         print_block(s, (*blk));
@@ -1436,6 +1463,7 @@ pub fn print_expr(s: ps, &&expr: @ast::expr) {
         print_expr(s, expr);
       }
       ast::expr_mac(ref m) => print_mac(s, (*m)),
+      ast::expr_err => word(s.s, ~"/* syntax error */"),
       ast::expr_paren(e) => {
           popen(s);
           print_expr(s, e);
@@ -1459,7 +1487,7 @@ pub fn print_decl(s: ps, decl: @ast::decl) {
     match decl.node {
       ast::decl_local(locs) => {
         space_if_not_bol(s);
-        ibox(s, indent_unit);
+        ibox(s, s.indent);
         word_nbsp(s, ~"let");
 
         // if any are mut, all are mut
@@ -1469,7 +1497,7 @@ pub fn print_decl(s: ps, decl: @ast::decl) {
         }
 
         fn print_local(s: ps, &&loc: @ast::local) {
-            ibox(s, indent_unit);
+            ibox(s, s.indent);
             print_local_decl(s, loc);
             end(s);
             match loc.node.init {
@@ -1581,7 +1609,7 @@ pub fn print_pat(s: ps, &&pat: @ast::pat, refutable: bool) {
       ast::pat_rec(fields, etc) => {
         word(s.s, ~"{");
         fn print_field(s: ps, f: ast::field_pat, refutable: bool) {
-            cbox(s, indent_unit);
+            cbox(s, s.indent);
             print_ident(s, f.ident);
             word_space(s, ~":");
             print_pat(s, f.pat, refutable);
@@ -1601,7 +1629,7 @@ pub fn print_pat(s: ps, &&pat: @ast::pat, refutable: bool) {
         print_path(s, path, true);
         word(s.s, ~"{");
         fn print_field(s: ps, f: ast::field_pat, refutable: bool) {
-            cbox(s, indent_unit);
+            cbox(s, s.indent);
             print_ident(s, f.ident);
             word_space(s, ~":");
             print_pat(s, f.pat, refutable);
@@ -1787,7 +1815,7 @@ pub fn print_type_params(s: ps, &&params: ~[ast::ty_param]) {
 }
 
 pub fn print_meta_item(s: ps, &&item: @ast::meta_item) {
-    ibox(s, indent_unit);
+    ibox(s, s.indent);
     match item.node {
       ast::meta_word(ref name) => word(s.s, (*name)),
       ast::meta_name_value(ref name, value) => {
@@ -1879,7 +1907,7 @@ pub fn print_mt(s: ps, mt: ast::mt) {
 }
 
 pub fn print_arg(s: ps, input: ast::arg) {
-    ibox(s, indent_unit);
+    ibox(s, s.indent);
     print_arg_mode(s, input.mode);
     if input.is_mutbl {
         word_space(s, ~"mut");
@@ -1914,7 +1942,7 @@ pub fn print_ty_fn(s: ps,
                    decl: ast::fn_decl, id: Option<ast::ident>,
                    tps: Option<~[ast::ty_param]>,
                    opt_self_ty: Option<ast::self_ty_>) {
-    ibox(s, indent_unit);
+    ibox(s, s.indent);
 
     // Duplicates the logic in `print_fn_header_info()`.  This is because that
     // function prints the sigil in the wrong place.  That should be fixed.
@@ -1950,7 +1978,7 @@ pub fn print_ty_fn(s: ps,
         ast::ty_nil => {}
         _ => {
             space_if_not_bol(s);
-            ibox(s, indent_unit);
+            ibox(s, s.indent);
             word_space(s, ~"->");
             if decl.cf == ast::noreturn { word_nbsp(s, ~"!"); }
             else { print_type(s, decl.output); }
@@ -2039,6 +2067,7 @@ pub fn print_literal(s: ps, &&lit: @ast::lit) {
       ast::lit_bool(val) => {
         if val { word(s.s, ~"true"); } else { word(s.s, ~"false"); }
       }
+      ast::lit_binary(bytes) => word(s.s, ast_util::binary_lit_to_str(*bytes))
     }
 }
 
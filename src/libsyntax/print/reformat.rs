@@ -0,0 +1,206 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * Reformatting of source files.
+ *
+ * A file is parsed and printed again by `pprust`, carrying its comments,
+ * the spelling of its literals and its blank lines across. Runs of blank
+ * lines become a single one, and those at the start or end of a block are
+ * dropped. Modules declared with `mod foo;` stay in their own files.
+ *
+ * The result is parsed in turn, and is only handed back if it makes the
+ * same syntax tree as the original.
+ */
+
+use core::prelude::*;
+
+use ast;
+use parse;
+use parse::comments;
+use print::pp;
+use print::pprust;
+
+use core::char;
+use core::dvec::DVec;
+use core::io;
+use core::path::Path;
+use core::result::{Err, Ok, Result};
+use core::str;
+
+/// How reformatted code is laid out
+pub struct Config {
+    /// The column that lines are kept within where they can be broken
+    max_width: uint,
+    /// The number of columns that each block is indented by
+    indent: uint
+}
+
+pub fn default_config() -> Config {
+    Config {
+        max_width: pprust::default_columns,
+        indent: pprust::indent_unit
+    }
+}
+
+/**
+ * Reformats `src`, the text of the file at `path`.
+ *
+ * Syntax errors in the file are reported as the compiler reports them, and
+ * fail the task. The error is the reason the file was left alone when it
+ * parses but its reformatting would not make the same syntax tree.
+ */
+pub fn reformat(cfg: &Config, path: &Path, src: @~str)
+    -> Result<~str, ~str> {
+    let name = path.to_str();
+    let sess = parse::new_parse_sess(None);
+    let crate = parse::parse_crate_from_source_str(copy name, src, ~[],
+                                                   sess);
+    let out = do io::with_str_writer |wr| {
+        print_file(cfg, sess, crate, copy name, src, wr);
+    };
+    same_syntax_tree(name, sess, crate, out)
+}
+
+// Hands back `out`, the reformatting of `crate`, if it parses to the same
+// syntax tree
+fn same_syntax_tree(name: ~str, sess: parse::parse_sess, crate: @ast::crate,
+                    out: ~str) -> Result<~str, ~str> {
+    let out_sess = parse::new_parse_sess(None);
+    let out_crate = parse::parse_crate_from_source_str(copy name, @copy out,
+                                                       ~[], out_sess);
+    if crate_to_str(crate, sess) != crate_to_str(out_crate, out_sess) {
+        return Err(fmt!("%s: reformatting would change the syntax tree",
+                        name));
+    }
+    Ok(out)
+}
+
+fn print_file(cfg: &Config, sess: parse::parse_sess, crate: @ast::crate,
+              name: ~str, src: @~str, out: io::Writer) {
+    let r = do io::with_str_reader(*src) |rdr| {
+        comments::gather_comments_and_literals(sess.span_diagnostic,
+                                               copy name, rdr)
+    };
+    let s = @{s: pp::mk_printer(out, cfg.max_width),
+              cm: Some(sess.cm),
+              intr: sess.interner,
+              comments: Some(blank_line_groups(*src, r.cmnts)),
+              literals: Some(r.lits),
+              mut cur_cmnt: 0u,
+              mut cur_lit: 0u,
+              boxes: DVec(),
+              ann: pprust::no_ann(),
+              indent: cfg.indent,
+              match_indent: cfg.indent / 2u,
+              mod_decls: true};
+    pprust::print_crate_(s, crate);
+}
+
+// The crate as printed without comments or its own literals, which is the
+// same for two crates just when their syntax trees are.
+fn crate_to_str(crate: @ast::crate, sess: parse::parse_sess) -> ~str {
+    do io::with_str_writer |wr| {
+        let s = pprust::rust_printer(wr, sess.interner);
+        pprust::print_crate_(s, crate);
+    }
+}
+
+// Drops the blank lines that follow another blank line, or come at the
+// start or end of a block or of the file.
+fn blank_line_groups(src: &str, cmnts: ~[comments::cmnt])
+    -> ~[comments::cmnt] {
+    let mut kept = ~[];
+    let mut last_blank = None;
+    for cmnts.each |cmnt| {
+        if cmnt.style == comments::blank_line {
+            let pos = cmnt.pos.to_uint();
+            let run_on = match last_blank {
+              Some(last) => is_space(src, last, pos),
+              None => false
+            };
+            let before = prev_non_space(src, pos);
+            let after = next_non_space(src, pos);
+            if run_on || before.is_none() || before == Some('{') ||
+                after.is_none() || after == Some('}') {
+                loop;
+            }
+            last_blank = Some(pos);
+        } else {
+            last_blank = None;
+        }
+        kept.push(copy *cmnt);
+    }
+    kept
+}
+
+fn is_space(src: &str, lo: uint, hi: uint) -> bool {
+    str::all_between(src, lo, hi, char::is_whitespace)
+}
+
+fn prev_non_space(src: &str, pos: uint) -> Option<char> {
+    let mut i = pos;
+    while i > 0u {
+        i -= 1u;
+        let c = src[i] as char;
+        if !char::is_whitespace(c) { return Some(c); }
+    }
+    None
+}
+
+fn next_non_space(src: &str, pos: uint) -> Option<char> {
+    let mut i = pos;
+    while i < src.len() {
+        let c = src[i] as char;
+        if !char::is_whitespace(c) { return Some(c); }
+        i += 1u;
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use parse;
+
+    use core::path::Path;
+    use core::result::{Err, Ok};
+    use core::str;
+
+    fn reformat_str(src: ~str) -> ~str {
+        reformat(&default_config(), &Path("<test>"), @src).get()
+    }
+
+    #[test]
+    fn test_reformat() {
+        let src = ~"// one\nfn f(){g( 1 ,2 );}\n\n\n\n\n// two\nfn h(){}\n";
+        let out = reformat_str(copy src);
+        assert str::contains(out, "// one\n");
+        assert str::contains(out, "// two\n");
+        assert str::contains(out, "g(1, 2);");
+        assert !str::contains(out, "\n\n\n");
+        assert reformat_str(copy out) == out;
+    }
+
+    #[test]
+    fn test_refuse_changed_syntax_tree() {
+        let sess = parse::new_parse_sess(None);
+        let crate = parse::parse_crate_from_source_str(
+            ~"<test>", @~"fn f(){g(1);}", ~[], sess);
+        let out = same_syntax_tree(~"<test>", sess, crate,
+                                   ~"fn f() { g(1); }\n");
+        assert out == Ok(~"fn f() { g(1); }\n");
+        let out = same_syntax_tree(~"<test>", sess, crate,
+                                   ~"fn f() { g(2); }\n");
+        assert out == Err(~"<test>: reformatting would change the syntax \
+                            tree");
+    }
+}
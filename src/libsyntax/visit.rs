@@ -496,6 +496,7 @@ pub fn visit_expr<E>(ex: @expr, e: E, v: vt<E>) {
         (v.visit_expr)(x, e, v);
       }
       expr_mac(ref mac) => visit_mac((*mac), e, v),
+      expr_err => (),
       expr_paren(x) => (v.visit_expr)(x, e, v),
     }
     (v.visit_expr_post)(ex, e, v);
@@ -35,6 +35,7 @@
 #include "llvm/Support/SourceMgr.h"
 #include "llvm/Target/TargetOptions.h"
 #include "llvm/Support/Host.h"
+#include "llvm/Support/Threading.h"
 #include "llvm/Support/Debug.h"
 #include "llvm/Support/DynamicLibrary.h"
 #include "llvm/Support/Memory.h"
@@ -465,6 +466,19 @@ extern "C" LLVMModuleRef LLVMRustParseBitcode(LLVMMemoryBufferRef MemBuf) {
          ? NULL : M;
 }
 
+extern "C" LLVMModuleRef LLVMRustParseBitcodeInContext(
+    LLVMMemoryBufferRef MemBuf, LLVMContextRef C) {
+  LLVMModuleRef M;
+  return LLVMParseBitcodeInContext(C, MemBuf, &M,
+                                   const_cast<char **>(&LLVMRustError))
+         ? NULL : M;
+}
+
+// Must be called before LLVM is used from more than one thread.
+extern "C" bool LLVMRustStartMultithreading() {
+  return llvm_start_multithreaded();
+}
+
 extern "C" LLVMValueRef LLVMRustConstSmallInt(LLVMTypeRef IntTy, unsigned N,
                                               LLVMBool SignExtend) {
   return LLVMConstInt(IntTy, (unsigned long long)N, SignExtend);
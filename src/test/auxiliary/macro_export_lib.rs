@@ -0,0 +1,30 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[link(name = "macro_export_lib",
+       vers = "0.1")];
+#[crate_type = "lib"];
+
+#[macro_export]
+macro_rules! double (
+    ($e:expr) => ($e * 2)
+)
+
+#[macro_export]
+macro_rules! make_getter (
+    ($name:ident, $val:expr) => (fn $name() -> int { $val })
+)
+
+// Not exported: only usable within this crate
+macro_rules! triple (
+    ($e:expr) => ($e * 3)
+)
+
+pub fn six() -> int { triple!(2) }
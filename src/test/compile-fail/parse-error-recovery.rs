@@ -0,0 +1,25 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The parser carries on after a syntax error in a statement or item, so
+// that the errors in the statements and items after it are reported too.
+
+fn f() -> int {
+    let x = ; //~ ERROR unexpected token: `;`
+    let y = 1 + ; //~ ERROR unexpected token: `;`
+    foo(1 2); //~ ERROR expected `,` but found `2`
+    x + y
+}
+
+const c: int = ; //~ ERROR unexpected token: `;`
+
+fn main() {
+    let z = [1, 2,, 3]; //~ ERROR unexpected token: `,`
+}
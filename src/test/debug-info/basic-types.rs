@@ -0,0 +1,34 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// debugger:break _zzz
+// debugger:run
+// debugger:finish
+// debugger:print b
+// check:$1 = true
+// debugger:print i
+// check:$2 = -1
+// debugger:print u
+// check:$3 = 42
+// debugger:print c
+// check:$4 = 97
+// debugger:print f
+// check:$5 = 3.5
+
+fn main() {
+    let b: bool = true;
+    let i: int = -1;
+    let u: u16 = 42;
+    let c: char = 'a';
+    let f: f64 = 3.5;
+    _zzz();
+}
+
+fn _zzz() {()}
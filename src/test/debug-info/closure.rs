@@ -0,0 +1,28 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// debugger:break _zzz
+// debugger:run
+// debugger:finish
+// debugger:print x
+// check:$1 = 10
+// debugger:print y
+// check:$2 = 11
+
+fn main() {
+    let x = 10;
+    let f: fn() = || {
+        let y = x + 1;
+        _zzz();
+    };
+    f();
+}
+
+fn _zzz() {()}
@@ -0,0 +1,48 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// A variable declared in a nested block is visible there, shadows the
+// outer one of the same name, and keeps its name when a closure captures
+// it.
+
+// debugger:break _zzz
+// debugger:run
+// debugger:finish
+// debugger:print x
+// check:$1 = 20
+// debugger:print z
+// check:$2 = 21
+// debugger:continue
+// debugger:finish
+// debugger:print x
+// check:$3 = 20
+// debugger:print w
+// check:$4 = 22
+// debugger:continue
+// debugger:finish
+// debugger:print x
+// check:$5 = 10
+
+fn main() {
+    let x = 10;
+    {
+        let x = 20;
+        let z = x + 1;
+        _zzz();
+        let f: fn() = || {
+            let w = (x) + 2;
+            _zzz();
+        };
+        f();
+    }
+    _zzz();
+}
+
+fn _zzz() {()}
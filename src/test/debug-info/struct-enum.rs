@@ -0,0 +1,41 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// debugger:break _zzz
+// debugger:run
+// debugger:finish
+// debugger:print p
+// check:$1 = {x = 1, y = 2}
+// debugger:print c
+// check:$2 = Green
+// debugger:print s.discr
+// check:$3 = Circle
+// debugger:print s.variants.Circle
+// check:$4 = {__0 = {x = 3, y = 4}, __1 = 5}
+// debugger:print w
+// check:$5 = {__0 = 6}
+
+struct Point { x: int, y: int }
+
+enum Color { Red, Green, Blue }
+
+enum Shape { Circle(Point, int), Rect(Point, Point) }
+
+enum Wrapper { Wrapper(int) }
+
+fn main() {
+    let p = Point { x: 1, y: 2 };
+    let c = Green;
+    let s = Circle(Point { x: 3, y: 4 }, 5);
+    let w = Wrapper(6);
+    _zzz();
+}
+
+fn _zzz() {()}
@@ -0,0 +1,20 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+// pp-exact
+
+fn main() {
+    let plain = r"C:\Windows";
+    let quoted = r#"{"key": "value"}"#;
+    let nested = r##"a "# inside"##;
+    let bytes = b"\x00\xff";
+    let text = &b"GIF89a";
+}
@@ -0,0 +1,34 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+const MAGIC: [u8 * 4] = b"\x7fELF";
+const GIF: &[u8] = &b"GIF89a";
+
+fn len(bytes: &[u8]) -> uint { bytes.len() }
+
+fn main() {
+    let fixed: [u8 * 3] = b"abc";
+    assert fixed[0] == 97u8;
+    assert fixed[2] == 99u8;
+
+    let slice: &[u8] = &b"a\tb\n\\\"\x00\xff";
+    assert slice == &[97u8, 9u8, 98u8, 10u8, 92u8, 34u8, 0u8, 255u8];
+    assert len(&b"") == 0u;
+
+    let owned: ~[u8] = ~b"hi";
+    assert owned == ~[104u8, 105u8];
+    let managed: @[u8] = @b"hi";
+    assert managed.len() == 2u;
+
+    assert MAGIC[0] == 0x7fu8;
+    assert MAGIC[3] == 70u8;
+    assert len(GIF) == 6u;
+    assert GIF[5] == 97u8;
+}
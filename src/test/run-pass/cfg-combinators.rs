@@ -0,0 +1,47 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: --cfg foo
+// xfail-fast
+
+#[cfg(not(foo))]
+fn f() -> int { die!() }
+
+#[cfg(foo)]
+fn f() -> int { 1 }
+
+#[cfg(all(foo, bar))]
+fn g() -> int { die!() }
+
+#[cfg(all(foo, not(bar)))]
+fn g() -> int { 2 }
+
+#[cfg(any(bar, baz))]
+fn h() -> int { die!() }
+
+#[cfg(any(bar, foo))]
+fn h() -> int { 3 }
+
+#[cfg(not(any(bar, baz)), all())]
+fn i() -> int { 4 }
+
+pub fn main() {
+    assert f() == 1;
+    assert g() == 2;
+    assert h() == 3;
+    assert i() == 4;
+
+    assert cfg!(foo);
+    assert !cfg!(bar);
+    assert cfg!(bar, foo);
+    assert cfg!(all(foo, not(bar)));
+    assert !cfg!(any(bar, not(foo)));
+    assert cfg!(unix) != cfg!(windows);
+}
@@ -0,0 +1,51 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z codegen-units=2
+// xfail-fast
+
+// The crate root and the two modules are split between two codegen units,
+// so some of the calls below go from one unit to another.
+
+mod a {
+    pub enum Shape { Circle(int), Square(int) }
+
+    pub const scale: int = 10;
+
+    pub fn area(s: Shape) -> int {
+        match s {
+          Circle(r) => 3 * r * r,
+          Square(w) => ::b::square(w)
+        }
+    }
+
+    pub fn twice<T: Copy>(x: T) -> (T, T) { (x, x) }
+}
+
+mod b {
+    pub fn square(x: int) -> int { x * x }
+
+    pub fn scaled_area(s: ::a::Shape) -> int {
+        ::a::area(s) * ::a::scale
+    }
+
+    pub fn pair() -> (int, int) { ::a::twice(::root()) }
+}
+
+fn root() -> int { 7 }
+
+fn main() {
+    assert a::area(a::Circle(2)) == 12;
+    assert a::area(a::Square(3)) == 9;
+    assert b::scaled_area(a::Square(2)) == 40;
+    assert b::pair() == (7, 7);
+    let f = b::square;
+    assert f(5) == 25;
+}
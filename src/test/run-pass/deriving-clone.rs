@@ -0,0 +1,36 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[deriving_eq]
+#[deriving_clone]
+struct Point<T> {
+    x: int,
+    name: ~str,
+    extra: ~T
+}
+
+#[deriving_eq]
+#[deriving_clone]
+enum Shape {
+    Dot,
+    Circle(int, ~str),
+    Rect { w: int, h: int }
+}
+
+pub fn main() {
+    let a = Point { x: 1, name: ~"a", extra: ~2.0 };
+    let b = a.clone();
+    assert a == b;
+
+    let shapes = ~[Dot, Circle(1, ~"c"), Rect { w: 2, h: 3 }];
+    for shapes.each |shape| {
+        assert shape.clone() == *shape;
+    }
+}
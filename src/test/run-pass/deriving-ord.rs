@@ -0,0 +1,46 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[deriving_eq]
+#[deriving_ord]
+struct Pair<T> {
+    a: int,
+    b: T
+}
+
+#[deriving_eq]
+#[deriving_ord]
+enum Tree {
+    Leaf,
+    Node(int, int),
+    Labelled { label: ~str }
+}
+
+pub fn main() {
+    let p1 = Pair { a: 1, b: 5 };
+    let p2 = Pair { a: 1, b: 7 };
+    let p3 = Pair { a: 2, b: 0 };
+    assert p1 < p2 && p2 < p3 && p1 < p3;
+    assert p1 <= p1 && p1 >= p1;
+    assert !(p1 < p1) && !(p1 > p1);
+    assert p3 > p2 && p3 >= p2;
+
+    // Variants are ordered by their index, then by their fields.
+    let trees = ~[Leaf, Node(1, 2), Node(1, 3), Node(2, 0),
+                  Labelled { label: ~"a" }, Labelled { label: ~"b" }];
+    for trees.eachi |i, x| {
+        for trees.eachi |j, y| {
+            assert (*x < *y) == (i < j);
+            assert (*x <= *y) == (i <= j);
+            assert (*x >= *y) == (i >= j);
+            assert (*x > *y) == (i > j);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[deriving_rand]
+struct Point {
+    x: int,
+    y: float
+}
+
+#[deriving_eq]
+#[deriving_rand]
+enum Colour {
+    Red,
+    Green(u8),
+    Blue { level: u8 }
+}
+
+pub fn main() {
+    let rng = rand::Rng();
+    let _: Point = rng.gen();
+
+    // Every variant comes up eventually.
+    let mut seen_red = false, seen_green = false, seen_blue = false;
+    while !(seen_red && seen_green && seen_blue) {
+        match rng.gen::<Colour>() {
+            Red => seen_red = true,
+            Green(_) => seen_green = true,
+            Blue { level: _ } => seen_blue = true
+        }
+    }
+}
@@ -0,0 +1,32 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[deriving_to_str]
+struct Person {
+    name: ~str,
+    age: int
+}
+
+#[deriving_to_str]
+enum Message {
+    Quit,
+    Move(int, int),
+    Say { text: ~str }
+}
+
+pub fn main() {
+    let p = Person { name: ~"Bob", age: 30 };
+    assert p.to_str() == ~"Person{name: ~\"Bob\", age: 30}";
+
+    assert Quit.to_str() == ~"Quit";
+    assert Move(1, -2).to_str() == ~"Move(1, -2)";
+    let say = Say { text: ~"hi" };
+    assert say.to_str() == ~"Say{text: ~\"hi\"}";
+}
@@ -0,0 +1,48 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use core::extfmt::{Format, Spec};
+
+struct Money { cents: int }
+
+impl Money: Format {
+    pure fn format(&self, spec: &Spec) -> ~str {
+        let sign = if self.cents < 0 { ~"-" }
+                   else if spec.sign_always { ~"+" }
+                   else { ~"" };
+        let cents = if self.cents < 0 { -self.cents } else { self.cents };
+        let mut s = sign + ~"$" + int::str(cents / 100);
+        // The precision says whether to show the cents
+        match spec.precision {
+          Some(0) => (),
+          _ => s += fmt!(".%02d", cents % 100)
+        }
+        if spec.alternate { s += ~" USD"; }
+        s
+    }
+}
+
+fn test(actual: ~str, expected: ~str) {
+    log(debug, copy actual);
+    log(debug, copy expected);
+    assert actual == expected;
+}
+
+pub fn main() {
+    let m = Money { cents: 1234 };
+    test(fmt!("%T", m), ~"$12.34");
+    test(fmt!("%.0T", m), ~"$12");
+    test(fmt!("%+T", m), ~"+$12.34");
+    test(fmt!("%#T", m), ~"$12.34 USD");
+    test(fmt!("%8T|", m), ~"  $12.34|");
+    test(fmt!("%-8T|", m), ~"$12.34  |");
+    test(fmt!("%T", Money { cents: -5 }), ~"-$0.05");
+    test(fmt!("%2$T %1$s", ~"each", m), ~"$12.34 each");
+}
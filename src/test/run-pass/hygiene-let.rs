@@ -0,0 +1,62 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// xfail-pretty - token trees can't pretty print
+
+// Variables bound by a macro are distinct from those of the same name at
+// the call site, while everything else keeps referring to the caller's.
+
+struct Point { x: int, y: int }
+
+macro_rules! swap (
+    ($a:expr, $b:expr) => ({
+        let tmp = $a;
+        $a = $b;
+        $b = tmp;
+    })
+)
+
+macro_rules! add_one (
+    ($e:expr) => ({ let x = 1; $e + x })
+)
+
+macro_rules! unwrap_or_zero (
+    ($e:expr) => (match $e { Some(n) => n, None => 0 })
+)
+
+macro_rules! make_point (
+    ($v:expr) => ({ let x = $v; Point { x: x, y: x } })
+)
+
+macro_rules! bind (
+    ($x:ident, $e:expr) => (let $x = $e;)
+)
+
+pub fn main() {
+    let mut tmp = 1;
+    let mut other = 2;
+    swap!(tmp, other);
+    assert tmp == 2;
+    assert other == 1;
+
+    let x = 10;
+    assert add_one!(x) == 11;
+    assert add_one!(x * 2) == 21;
+
+    assert unwrap_or_zero!(Some(3)) == 3;
+    assert unwrap_or_zero!(None::<int>) == 0;
+
+    let p = make_point!(x + 1);
+    assert p.x == 11 && p.y == 11;
+
+    // An identifier passed in by the caller binds at the call site
+    bind!(y, x * 3);
+    assert y == 30;
+}
@@ -0,0 +1,37 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// xfail-pretty - token trees can't pretty print
+
+// The fields of a struct defined by a macro keep their names, so that they
+// can be read both inside and outside of the expansion.
+
+macro_rules! make_counter (
+    () => (
+        mod counter {
+            pub struct Counter { count: int }
+
+            pub fn new(n: int) -> Counter { Counter { count: n } }
+
+            pub fn get(c: &Counter) -> int { c.count }
+        }
+    )
+)
+
+make_counter!()
+
+pub fn main() {
+    let c = counter::new(3);
+    assert c.count == 3;
+    assert counter::get(&c) == 3;
+
+    let d = counter::Counter { count: 4 };
+    assert counter::get(&d) == 4;
+}
@@ -0,0 +1,42 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// xfail-pretty - token trees can't pretty print
+
+// The methods of a trait defined by a macro keep their names, so that an
+// impl in the same expansion, and callers outside of it, can use them.
+
+macro_rules! make_shape (
+    () => (
+        mod shape {
+            pub trait Shape {
+                fn area(&self) -> int;
+            }
+
+            pub struct Square { side: int }
+
+            pub impl Square : Shape {
+                fn area(&self) -> int { self.side * self.side }
+            }
+        }
+    )
+)
+
+make_shape!()
+
+use shape::Shape;
+
+fn area_of<T: Shape>(t: &T) -> int { t.area() }
+
+pub fn main() {
+    let s = shape::Square { side: 3 };
+    assert s.area() == 9;
+    assert area_of(&s) == 9;
+}
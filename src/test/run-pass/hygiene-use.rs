@@ -0,0 +1,41 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// xfail-pretty - token trees can't pretty print
+
+// The paths of a `use` in a macro expansion name the items they were
+// written with.
+
+mod numbers {
+    pub fn three() -> int { 3 }
+    pub fn four() -> int { 4 }
+}
+
+macro_rules! sum (
+    () => ({
+        use numbers::{three, four};
+        three() + four()
+    })
+)
+
+macro_rules! reexport (
+    () => (
+        mod more {
+            pub use numbers::three;
+        }
+    )
+)
+
+reexport!()
+
+pub fn main() {
+    assert sum!() == 7;
+    assert more::three() == 3;
+}
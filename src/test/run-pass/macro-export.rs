@@ -0,0 +1,22 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// xfail-fast
+// aux-build:macro_export_lib.rs
+
+extern mod macro_export_lib;
+
+make_getter!(seven, 7)
+
+pub fn main() {
+    assert double!(21) == 42;
+    assert seven() == 7;
+    assert macro_export_lib::six() == 6;
+}
@@ -0,0 +1,24 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn main() {
+    assert r"C:\Windows\system32" == "C:\\Windows\\system32";
+    assert r"\n" == "\\n";
+    assert r"" == "";
+    assert r#"say "hi""# == "say \"hi\"";
+    assert r##"a "# b"## == "a \"# b";
+    assert r"line one
+line two" == "line one\nline two";
+
+    let re: &str = r"^\d+(\.\d*)?$";
+    assert re.len() == 13;
+    let owned = ~r#"{"key": "value"}"#;
+    assert owned == ~"{\"key\": \"value\"}";
+}
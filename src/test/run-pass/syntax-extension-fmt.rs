@@ -32,6 +32,7 @@ pub fn main() {
     part6();
     percent();
     more_floats();
+    positional();
 }
 
 fn part1() {
@@ -260,3 +261,11 @@ fn more_floats() {
     assert ~"7.0000"      == fmt!("%.4f", 6.999999999);
     assert ~"3.141590000" == fmt!("%.9f", 3.14159);
 }
+
+fn positional() {
+    test(fmt!("%2$s %1$s", ~"world", ~"hello"), ~"hello world");
+    test(fmt!("%1$d %1$05d %1$x", 42), ~"42 00042 2a");
+    // Conversions without a parameter take the remaining arguments in order
+    test(fmt!("%2$s %s", ~"a", ~"b"), ~"b a");
+    test(fmt!("%s %1$s", ~"again"), ~"again again");
+}
@@ -0,0 +1,29 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use io::WriterUtil;
+
+pub fn main() {
+    let s = do io::with_str_writer |wr| {
+        write_fmt!(wr, "%s is %d", "the answer", 42);
+        wr.write_char('\n');
+        write_fmt!(wr, "%2$s %1$05.1f", 2.5, ~"x =");
+        write_fmt!(wr, "");
+    };
+    assert s == ~"the answer is 42\nx = 002.5";
+
+    // The writer is only evaluated once
+    let mut n = 0;
+    let s = do io::with_str_writer |wr| {
+        write_fmt!({ n += 1; wr }, "%d%d%d", 1, 2, 3);
+    };
+    assert s == ~"123";
+    assert n == 1;
+}